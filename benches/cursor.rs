@@ -3,7 +3,7 @@ mod utils;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use ffi::*;
 use libmdbx::*;
-use std::ptr;
+use std::{borrow::Cow, ptr};
 use utils::*;
 
 /// Benchmark of iterator sequential read performance.
@@ -74,6 +74,53 @@ fn bench_get_seq_cursor(c: &mut Criterion) {
     });
 }
 
+/// Benchmark of iterator sequential read performance over a larger database, so that the
+/// per-item cost dominates.
+fn bench_iter_large(c: &mut Criterion) {
+    let n = 10_000;
+    let (_dir, env) = setup_bench_db(n);
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+
+    c.bench_function("bench_iter_large", |b| {
+        b.iter(|| {
+            let mut cursor = txn.cursor(&db).unwrap();
+            let (i, count) = cursor
+                .iter_start::<Cow<'_, [u8]>, Cow<'_, [u8]>>()
+                .map(Result::unwrap)
+                .fold((0, 0), |(i, count), (key, val)| {
+                    (i + key.len() + val.len(), count + 1)
+                });
+
+            black_box(i);
+            assert_eq!(count, n);
+        })
+    });
+}
+
+/// Benchmark of batched iterator sequential read performance, see [bench_iter_large].
+fn bench_iter_batched_large(c: &mut Criterion) {
+    let n = 10_000;
+    let (_dir, env) = setup_bench_db(n);
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+
+    c.bench_function("bench_iter_batched_large", |b| {
+        b.iter(|| {
+            let mut cursor = txn.cursor(&db).unwrap();
+            let (i, count) = cursor
+                .iter_batched::<Cow<'_, [u8]>, Cow<'_, [u8]>>(256)
+                .map(Result::unwrap)
+                .fold((0, 0), |(i, count), (key, val)| {
+                    (i + key.len() + val.len(), count + 1)
+                });
+
+            black_box(i);
+            assert_eq!(count, n);
+        })
+    });
+}
+
 /// Benchmark of raw MDBX sequential read performance (control).
 fn bench_get_seq_raw(c: &mut Criterion) {
     let n = 100;
@@ -115,6 +162,8 @@ criterion_group!(
     benches,
     bench_get_seq_iter,
     bench_get_seq_cursor,
+    bench_iter_large,
+    bench_iter_batched_large,
     bench_get_seq_raw
 );
 criterion_main!(benches);
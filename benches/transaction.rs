@@ -6,7 +6,7 @@ use libc::size_t;
 use libmdbx::{ObjectLength, WriteFlags};
 use rand::{prelude::SliceRandom, SeedableRng};
 use rand_xorshift::XorShiftRng;
-use std::ptr;
+use std::{borrow::Cow, ptr};
 use utils::*;
 
 fn bench_get_rand(c: &mut Criterion) {
@@ -32,6 +32,94 @@ fn bench_get_rand(c: &mut Criterion) {
     });
 }
 
+fn bench_get_rand_cow(c: &mut Criterion) {
+    let n = 100u32;
+    let (_dir, env) = setup_bench_db(n);
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+
+    let mut keys: Vec<String> = (0..n).map(get_key).collect();
+    keys.shuffle(&mut XorShiftRng::from_seed(Default::default()));
+
+    c.bench_function("bench_get_rand_cow", |b| {
+        b.iter(|| {
+            let mut i = 0usize;
+            for key in &keys {
+                i += txn
+                    .get::<Cow<[u8]>>(&db, key.as_bytes())
+                    .unwrap()
+                    .unwrap()
+                    .len();
+            }
+            black_box(i);
+        })
+    });
+}
+
+fn bench_get_rand_slice(c: &mut Criterion) {
+    let n = 100u32;
+    let (_dir, env) = setup_bench_db(n);
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+
+    let mut keys: Vec<String> = (0..n).map(get_key).collect();
+    keys.shuffle(&mut XorShiftRng::from_seed(Default::default()));
+
+    c.bench_function("bench_get_rand_slice", |b| {
+        b.iter(|| {
+            let mut i = 0usize;
+            for key in &keys {
+                i += txn.get_slice(&db, key.as_bytes()).unwrap().unwrap().len();
+            }
+            black_box(i);
+        })
+    });
+}
+
+fn sorted_keys(n: u32, step: usize) -> Vec<String> {
+    let mut keys: Vec<String> = (0..n).map(get_key).collect();
+    keys.sort();
+    keys.into_iter().step_by(step).collect()
+}
+
+fn bench_get_sorted(c: &mut Criterion) {
+    let n = 20_000u32;
+    let (_dir, env) = setup_bench_db(n);
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let keys = sorted_keys(n, 10);
+
+    c.bench_function("bench_get_sorted", |b| {
+        b.iter(|| {
+            let values = txn
+                .get_sorted::<ObjectLength, _>(&db, keys.iter().map(String::as_bytes))
+                .unwrap();
+            black_box(values.into_iter().flatten().map(|len| *len).sum::<usize>());
+        })
+    });
+}
+
+fn bench_get_sorted_loop(c: &mut Criterion) {
+    let n = 20_000u32;
+    let (_dir, env) = setup_bench_db(n);
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let keys = sorted_keys(n, 10);
+
+    c.bench_function("bench_get_sorted_loop", |b| {
+        b.iter(|| {
+            let mut i = 0usize;
+            for key in &keys {
+                i += *txn
+                    .get::<ObjectLength>(&db, key.as_bytes())
+                    .unwrap()
+                    .unwrap();
+            }
+            black_box(i);
+        })
+    });
+}
+
 fn bench_get_rand_raw(c: &mut Criterion) {
     let n = 100u32;
     let (_dir, env) = setup_bench_db(n);
@@ -69,6 +157,50 @@ fn bench_get_rand_raw(c: &mut Criterion) {
     });
 }
 
+fn bench_get_rand_raw_cursor(c: &mut Criterion) {
+    let n = 100u32;
+    let (_dir, env) = setup_bench_db(n);
+    let _txn = env.begin_ro_txn().unwrap();
+    let db = _txn.open_db(None).unwrap();
+
+    let mut keys: Vec<String> = (0..n).map(get_key).collect();
+    keys.shuffle(&mut XorShiftRng::from_seed(Default::default()));
+
+    let dbi = db.dbi();
+    let txn = _txn.txn();
+    let mut cursor: *mut MDBX_cursor = ptr::null_mut();
+    unsafe {
+        mdbx_cursor_open(txn, dbi, &mut cursor);
+    }
+
+    let mut key_val: MDBX_val = MDBX_val {
+        iov_len: 0,
+        iov_base: ptr::null_mut(),
+    };
+    let mut data_val: MDBX_val = MDBX_val {
+        iov_len: 0,
+        iov_base: ptr::null_mut(),
+    };
+
+    c.bench_function("bench_get_rand_raw_cursor", |b| {
+        b.iter(|| unsafe {
+            let mut i: size_t = 0;
+            for key in &keys {
+                key_val.iov_len = key.len() as size_t;
+                key_val.iov_base = key.as_bytes().as_ptr() as *mut _;
+
+                mdbx_cursor_get(cursor, &mut key_val, &mut data_val, MDBX_SET_KEY);
+
+                i += key_val.iov_len;
+            }
+            black_box(i);
+        })
+    });
+    unsafe {
+        mdbx_cursor_close(cursor);
+    }
+}
+
 fn bench_put_rand(c: &mut Criterion) {
     let n = 100u32;
     let (_dir, env) = setup_bench_db(0);
@@ -133,7 +265,12 @@ fn bench_put_rand_raw(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_get_rand,
+    bench_get_rand_cow,
+    bench_get_rand_slice,
+    bench_get_sorted,
+    bench_get_sorted_loop,
     bench_get_rand_raw,
+    bench_get_rand_raw_cursor,
     bench_put_rand,
     bench_put_rand_raw
 );
@@ -11482,7 +11482,7 @@ static int mdbx_txn_renew0(MDBX_txn *txn, const unsigned flags) {
       goto bailout;
     }
 
-    txn->mt_flags = flags;
+    txn->mt_flags = flags & ~MDBX_TXN_TRY;
     txn->mt_child = NULL;
     txn->tw.loose_pages = NULL;
     txn->tw.loose_count = 0;
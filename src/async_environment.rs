@@ -0,0 +1,238 @@
+use crate::{
+    Encodable, Environment, EnvironmentKind, Error, NoWriteMap, Result, TableObject, Transaction,
+    RO, RW,
+};
+use futures_core::Stream;
+use std::{
+    fmt, future, mem,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{mpsc, Arc},
+    task::{Context, Poll},
+    thread, vec,
+};
+use tokio::{
+    sync::{mpsc as async_mpsc, oneshot},
+    task,
+};
+
+/// Number of items sent to a [CursorStream] at once.
+const STREAM_BATCH: usize = 64;
+/// Number of batches read ahead by a [CursorStream].
+const STREAM_READ_AHEAD: usize = 2;
+
+type WriteJob<E> = Box<dyn FnOnce(&Environment<E>) + Send>;
+
+/// An environment usable from asynchronous code running on Tokio.
+///
+/// Read transactions run on the blocking thread pool of the runtime. Write transactions run one
+/// after another on a dedicated writer thread, which exits when the [AsyncEnvironment] is
+/// dropped.
+pub struct AsyncEnvironment<E = NoWriteMap>
+where
+    E: EnvironmentKind,
+{
+    env: Arc<Environment<E>>,
+    writer: mpsc::Sender<WriteJob<E>>,
+}
+
+impl<E> AsyncEnvironment<E>
+where
+    E: EnvironmentKind,
+{
+    pub fn new(env: Environment<E>) -> Self {
+        let env = Arc::new(env);
+        let (writer, jobs) = mpsc::channel::<WriteJob<E>>();
+        let writer_env = env.clone();
+        thread::Builder::new()
+            .name("mdbx-writer".to_string())
+            .spawn(move || {
+                for job in jobs {
+                    job(&writer_env);
+                }
+            })
+            .expect("failed to spawn writer thread");
+        Self { env, writer }
+    }
+
+    /// Returns the underlying environment.
+    pub fn env(&self) -> &Arc<Environment<E>> {
+        &self.env
+    }
+
+    /// Runs `f` in a read-only transaction on the blocking thread pool.
+    pub async fn read<R, F>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&Transaction<'_, RO, E>) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let env = self.env.clone();
+        match task::spawn_blocking(move || env.view(f)).await {
+            Ok(res) => res,
+            Err(e) => panic::resume_unwind(e.into_panic()),
+        }
+    }
+
+    /// Runs `f` in a read-write transaction on the writer thread, committing it if `f` returns
+    /// [Ok].
+    ///
+    /// If `f` panics the transaction is aborted and the panic is resumed in the caller.
+    pub async fn write<R, F>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&Transaction<'_, RW, E>) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel::<thread::Result<Result<R>>>();
+        self.writer
+            .send(Box::new(move |env: &Environment<E>| {
+                let _ = tx.send(panic::catch_unwind(AssertUnwindSafe(|| env.update(f))));
+            }))
+            .expect("writer thread exited");
+        match rx.await.expect("writer thread exited") {
+            Ok(res) => res,
+            Err(e) => panic::resume_unwind(e),
+        }
+    }
+
+    /// Returns a stream over all items of a database.
+    ///
+    /// `name` is [None] for the main database.
+    pub fn stream<Key, Value>(&self, name: Option<&str>) -> CursorStream<Key, Value>
+    where
+        Key: for<'tx> TableObject<'tx> + Send + 'static,
+        Value: for<'tx> TableObject<'tx> + Send + 'static,
+    {
+        self.spawn_stream(name, None)
+    }
+
+    /// Returns a stream over the items of a database, starting at the first key greater than or
+    /// equal to `start`.
+    pub fn stream_from<Key, Value>(
+        &self,
+        name: Option<&str>,
+        start: impl Encodable,
+    ) -> CursorStream<Key, Value>
+    where
+        Key: for<'tx> TableObject<'tx> + Send + 'static,
+        Value: for<'tx> TableObject<'tx> + Send + 'static,
+    {
+        let big_endian_int = is_big_endian_int(&start);
+        let start = start.encode().as_ref().to_vec();
+        self.spawn_stream(name, Some((start, big_endian_int)))
+    }
+
+    fn spawn_stream<Key, Value>(
+        &self,
+        name: Option<&str>,
+        start: Option<(Vec<u8>, bool)>,
+    ) -> CursorStream<Key, Value>
+    where
+        Key: for<'tx> TableObject<'tx> + Send + 'static,
+        Value: for<'tx> TableObject<'tx> + Send + 'static,
+    {
+        let env = self.env.clone();
+        let name = name.map(ToString::to_string);
+        let (tx, rx) = async_mpsc::channel(STREAM_READ_AHEAD);
+        task::spawn_blocking(move || {
+            let res = env.view(|txn| {
+                let db = txn.open_db(name.as_deref())?;
+                if let Some((_, big_endian_int)) = start {
+                    txn.check_key_encoding(&db, big_endian_int)?;
+                }
+                let mut cursor = txn.cursor(&db)?;
+                let iter = match &start {
+                    Some((start, _)) => cursor.iter_from::<Key, Value>(start),
+                    None => cursor.iter_start::<Key, Value>(),
+                };
+                let mut batch = Vec::with_capacity(STREAM_BATCH);
+                for item in iter {
+                    let failed = item.is_err();
+                    batch.push(item);
+                    if failed {
+                        break;
+                    }
+                    if batch.len() == STREAM_BATCH {
+                        let full = mem::replace(&mut batch, Vec::with_capacity(STREAM_BATCH));
+                        // The receiver was dropped, so nobody is interested in the rest.
+                        if tx.blocking_send(full).is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+                if !batch.is_empty() {
+                    let _ = tx.blocking_send(batch);
+                }
+                Ok::<_, Error>(())
+            });
+            if let Err(e) = res {
+                let _ = tx.blocking_send(vec![Err(e)]);
+            }
+        });
+        CursorStream {
+            rx,
+            batch: Vec::new().into_iter(),
+        }
+    }
+}
+
+/// Returns [Encodable::BIG_ENDIAN_INT] of the type of a key.
+fn is_big_endian_int<Key: Encodable>(_: &Key) -> bool {
+    Key::BIG_ENDIAN_INT
+}
+
+impl<E> fmt::Debug for AsyncEnvironment<E>
+where
+    E: EnvironmentKind,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncEnvironment").finish()
+    }
+}
+
+/// A stream of items read from a database by a read-only transaction on the blocking thread pool.
+///
+/// Items are read in batches, a few of which are read ahead of the consumer. The transaction
+/// stays open until the stream is exhausted or dropped.
+pub struct CursorStream<Key, Value> {
+    rx: async_mpsc::Receiver<Vec<Result<(Key, Value)>>>,
+    batch: vec::IntoIter<Result<(Key, Value)>>,
+}
+
+impl<Key, Value> CursorStream<Key, Value> {
+    /// Returns the next item of the stream, or [None] once it is exhausted.
+    ///
+    /// The stream ends after the first error.
+    pub async fn next(&mut self) -> Option<Result<(Key, Value)>> {
+        future::poll_fn(|cx| self.poll_item(cx)).await
+    }
+
+    fn poll_item(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<(Key, Value)>>> {
+        loop {
+            if let Some(item) = self.batch.next() {
+                return Poll::Ready(Some(item));
+            }
+            match self.rx.poll_recv(cx) {
+                Poll::Ready(Some(batch)) => self.batch = batch.into_iter(),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<Key, Value> fmt::Debug for CursorStream<Key, Value> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CursorStream").finish()
+    }
+}
+
+// Items are never pinned in place.
+impl<Key, Value> Unpin for CursorStream<Key, Value> {}
+
+impl<Key, Value> Stream for CursorStream<Key, Value> {
+    type Item = Result<(Key, Value)>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_item(cx)
+    }
+}
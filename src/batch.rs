@@ -0,0 +1,228 @@
+use crate::{Encodable, Environment, EnvironmentKind, Error, Result, Transaction, WriteFlags, RW};
+use std::{
+    fmt,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+enum Op {
+    Put {
+        db: Option<String>,
+        key: Vec<u8>,
+        /// See [Encodable::BIG_ENDIAN_INT].
+        big_endian_int: bool,
+        value: Vec<u8>,
+        flags: WriteFlags,
+        reply: SyncSender<Result<()>>,
+    },
+    Del {
+        db: Option<String>,
+        key: Vec<u8>,
+        /// See [Encodable::BIG_ENDIAN_INT].
+        big_endian_int: bool,
+        reply: SyncSender<Result<bool>>,
+    },
+}
+
+impl Op {
+    fn apply<E: EnvironmentKind>(&self, txn: &Transaction<'_, RW, E>) -> Result<Option<bool>> {
+        match self {
+            Op::Put {
+                db,
+                key,
+                big_endian_int,
+                value,
+                flags,
+                ..
+            } => {
+                let db = txn.open_db(db.as_deref())?;
+                txn.check_key_encoding(&db, *big_endian_int)?;
+                txn.put(&db, key, value, *flags).map(|()| None)
+            }
+            Op::Del {
+                db,
+                key,
+                big_endian_int,
+                ..
+            } => {
+                let db = txn.open_db(db.as_deref())?;
+                txn.check_key_encoding(&db, *big_endian_int)?;
+                txn.del(&db, key, None).map(Some)
+            }
+        }
+    }
+
+    fn reply(self, res: Result<Option<bool>>) {
+        match self {
+            Op::Put { reply, .. } => {
+                let _ = reply.send(res.map(|_| ()));
+            }
+            Op::Del { reply, .. } => {
+                let _ = reply.send(res.map(|found| found.unwrap_or(false)));
+            }
+        }
+    }
+}
+
+/// Completion of an operation submitted to a [BatchWriter].
+#[derive(Debug)]
+pub struct BatchReceipt<T> {
+    rx: Receiver<Result<T>>,
+}
+
+impl<T> BatchReceipt<T> {
+    /// Blocks until the batch containing the operation has been committed.
+    ///
+    /// Returns the error of the operation, or of the commit of its batch. Returns
+    /// [Error::Problem] if the writer thread has died.
+    pub fn wait(self) -> Result<T> {
+        self.rx.recv().unwrap_or(Err(Error::Problem))
+    }
+
+    /// Returns the result if the batch containing the operation has already been committed.
+    pub fn try_wait(&self) -> Option<Result<T>> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// Groups writes submitted from many threads into few read-write transactions.
+///
+/// Operations are applied in submission order by a background thread. A batch is committed once
+/// `max_batch_size` operations have been collected, or `max_delay` after its first operation
+/// arrived, whichever comes first. Failed operations don't affect other operations in the same
+/// batch, unless they leave the transaction unable to commit.
+///
+/// Dropping the writer commits outstanding operations and waits for the background thread.
+pub struct BatchWriter {
+    sender: Option<Sender<Op>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BatchWriter {
+    pub fn new<E>(env: Arc<Environment<E>>, max_batch_size: usize, max_delay: Duration) -> Self
+    where
+        E: EnvironmentKind,
+    {
+        let (sender, ops) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("mdbx-batch-writer".to_string())
+            .spawn(move || run(&env, &ops, max_batch_size.max(1), max_delay))
+            .expect("failed to spawn batch writer thread");
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        }
+    }
+
+    fn submit<T>(&self, op: impl FnOnce(SyncSender<Result<T>>) -> Op) -> BatchReceipt<T> {
+        let (reply, rx) = mpsc::sync_channel(1);
+        // If the writer thread is gone, the receipt reports an error.
+        let _ = self.sender.as_ref().unwrap().send(op(reply));
+        BatchReceipt { rx }
+    }
+
+    /// Submits a write of an item. `db` is [None] for the main database.
+    pub fn put<Key>(
+        &self,
+        db: Option<&str>,
+        key: Key,
+        value: impl AsRef<[u8]>,
+        flags: WriteFlags,
+    ) -> BatchReceipt<()>
+    where
+        Key: Encodable,
+    {
+        let db = db.map(ToString::to_string);
+        let key = key.encode().as_ref().to_vec();
+        let value = value.as_ref().to_vec();
+        self.submit(|reply| Op::Put {
+            db,
+            key,
+            big_endian_int: Key::BIG_ENDIAN_INT,
+            value,
+            flags,
+            reply,
+        })
+    }
+
+    /// Submits a deletion of a key, resolving to whether the key was present.
+    pub fn del<Key>(&self, db: Option<&str>, key: Key) -> BatchReceipt<bool>
+    where
+        Key: Encodable,
+    {
+        let db = db.map(ToString::to_string);
+        let key = key.encode().as_ref().to_vec();
+        self.submit(|reply| Op::Del {
+            db,
+            key,
+            big_endian_int: Key::BIG_ENDIAN_INT,
+            reply,
+        })
+    }
+}
+
+impl fmt::Debug for BatchWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchWriter").finish()
+    }
+}
+
+impl Drop for BatchWriter {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run<E>(env: &Environment<E>, ops: &Receiver<Op>, max_batch_size: usize, max_delay: Duration)
+where
+    E: EnvironmentKind,
+{
+    while let Ok(first) = ops.recv() {
+        let deadline = Instant::now() + max_delay;
+        let mut batch = vec![first];
+        while batch.len() < max_batch_size {
+            match ops.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(op) => batch.push(op),
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        commit_batch(env, batch);
+    }
+}
+
+fn commit_batch<E>(env: &Environment<E>, batch: Vec<Op>)
+where
+    E: EnvironmentKind,
+{
+    let txn = match env.begin_rw_txn() {
+        Ok(txn) => txn,
+        Err(e) => {
+            let code = e.to_err_code();
+            for op in batch {
+                op.reply(Err(Error::from_err_code(code)));
+            }
+            return;
+        }
+    };
+    let results = batch.iter().map(|op| op.apply(&txn)).collect::<Vec<_>>();
+    match txn.commit() {
+        Ok(_) => {
+            for (op, res) in batch.into_iter().zip(results) {
+                op.reply(res);
+            }
+        }
+        Err(e) => {
+            let code = e.to_err_code();
+            for op in batch {
+                op.reply(Err(Error::from_err_code(code)));
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    Cursor, Database, DatabaseFlags, Encodable, Environment, EnvironmentKind, Error, Result,
+    Transaction, WriteFlags, RW,
+};
+use std::{
+    cmp::Ordering,
+    time::{Duration, Instant},
+};
+
+/// Appends an item after `prev`, the previous key, which is updated.
+fn append<'txn>(
+    cursor: &mut Cursor<'txn, RW>,
+    prev: &mut Option<Vec<u8>>,
+    dup_sort: bool,
+    key: &[u8],
+    value: &[u8],
+) -> Result<()> {
+    let flags = match prev.as_deref().map(|prev| cursor.cmp_keys(key, prev)) {
+        Some(Ordering::Equal) if dup_sort => WriteFlags::APPEND_DUP,
+        Some(Ordering::Less | Ordering::Equal) => return Err(Error::KeyMismatch),
+        _ => WriteFlags::APPEND,
+    };
+    cursor.put(key, value, flags)?;
+    let prev = prev.get_or_insert_with(Vec::new);
+    prev.clear();
+    prev.extend_from_slice(key);
+    Ok(())
+}
+
+/// Statistics of a load by a [BulkLoader].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadStats {
+    pub items: u64,
+    /// Bytes of keys and values loaded.
+    pub bytes: u64,
+    /// Number of transactions committed.
+    pub transactions: u64,
+    pub elapsed: Duration,
+}
+
+impl LoadStats {
+    pub fn items_per_sec(&self) -> f64 {
+        self.items as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+
+    pub fn bytes_per_sec(&self) -> f64 {
+        self.bytes as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Loads items sorted by key into a database, much faster than individual puts.
+///
+/// Items are written with [WriteFlags::APPEND], or [WriteFlags::APPEND_DUP] for the duplicates of
+/// a [DatabaseFlags::DUP_SORT] database, which skips searching the B-tree and fills pages
+/// completely. They are committed in transactions of about [BulkLoader::set_txn_size()] bytes
+/// each, so that a large load does not keep all of its dirty pages in memory.
+#[derive(Debug)]
+pub struct BulkLoader<'env, E>
+where
+    E: EnvironmentKind,
+{
+    env: &'env Environment<E>,
+    db: Option<String>,
+    flags: DatabaseFlags,
+    txn_size: usize,
+}
+
+impl<'env, E> BulkLoader<'env, E>
+where
+    E: EnvironmentKind,
+{
+    /// Creates a loader into a database of `env`, [None] for the main database.
+    pub fn new(env: &'env Environment<E>, db: Option<&str>) -> Self {
+        Self {
+            env,
+            db: db.map(ToString::to_string),
+            flags: DatabaseFlags::empty(),
+            txn_size: 16 << 20,
+        }
+    }
+
+    /// Sets the flags the database is created with if it does not exist.
+    pub fn set_flags(&mut self, flags: DatabaseFlags) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the bytes of keys and values written by each transaction, 16 MiB by default.
+    pub fn set_txn_size(&mut self, bytes: usize) -> &mut Self {
+        self.txn_size = bytes.max(1);
+        self
+    }
+
+    /// Loads the items, which must be sorted in the order of the database, by key and then by
+    /// value for the duplicates of a [DatabaseFlags::DUP_SORT] database. Keys must also sort
+    /// after the ones already in the database.
+    ///
+    /// Returns [Error::KeyMismatch] at the first item out of order. Items committed by the
+    /// transactions before it stay in the database.
+    pub fn load<K, V>(&self, items: impl IntoIterator<Item = (K, V)>) -> Result<LoadStats>
+    where
+        K: Encodable,
+        V: AsRef<[u8]>,
+    {
+        let start = Instant::now();
+        let mut stats = LoadStats::default();
+        let mut items = items.into_iter().peekable();
+        let mut prev: Option<Vec<u8>> = None;
+
+        while items.peek().is_some() {
+            let txn = self.env.begin_rw_txn()?;
+            let db = txn.create_db(self.db.as_deref(), self.flags)?;
+            txn.check_key_encoding(&db, K::BIG_ENDIAN_INT)?;
+            let dup_sort = txn.db_flags(&db)?.contains(DatabaseFlags::DUP_SORT);
+            let mut cursor = txn.cursor(&db)?;
+            let mut size = 0;
+            while size < self.txn_size {
+                let Some((key, value)) = items.next() else {
+                    break;
+                };
+                let key = key.encode();
+                let (key, value) = (key.as_ref(), value.as_ref());
+                append(&mut cursor, &mut prev, dup_sort, key, value)?;
+                size += key.len() + value.len();
+                stats.items += 1;
+            }
+            drop(cursor);
+            txn.commit()?;
+            stats.bytes += size as u64;
+            stats.transactions += 1;
+        }
+        stats.elapsed = start.elapsed();
+        Ok(stats)
+    }
+}
+
+impl<'env, E> Transaction<'env, RW, E>
+where
+    E: EnvironmentKind,
+{
+    /// Creates a database, see [Transaction::create_db()], and fills it with items sorted as
+    /// for [BulkLoader::load()], e.g. to seed a lookup table in a migration.
+    ///
+    /// Returns [Error::KeyMismatch] at the first item out of order, in which case the
+    /// transaction should be aborted.
+    pub fn create_db_with<'txn, K, V>(
+        &'txn self,
+        name: Option<&str>,
+        flags: DatabaseFlags,
+        items: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<Database<'txn>>
+    where
+        K: Encodable,
+        V: AsRef<[u8]>,
+    {
+        let db = self.create_db(name, flags)?;
+        self.check_key_encoding(&db, K::BIG_ENDIAN_INT)?;
+        let dup_sort = self.db_flags(&db)?.contains(DatabaseFlags::DUP_SORT);
+        let mut cursor = self.cursor(&db)?;
+        let mut prev = None;
+        for (key, value) in items {
+            let key = key.encode();
+            append(
+                &mut cursor,
+                &mut prev,
+                dup_sort,
+                key.as_ref(),
+                value.as_ref(),
+            )?;
+        }
+        Ok(db)
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    database::Database, gc::parse_pnl, Environment, EnvironmentKind, Error, PageOwner, PageType,
+    Result, Transaction, TransactionKind,
+};
+use std::ops::ControlFlow;
+
+/// Options of [Transaction::check()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckOptions {
+    /// Verify the order of keys on every page.
+    pub check_keys_ordering: bool,
+    /// Verify that pages recorded as free by the garbage collector are not in use.
+    pub check_freelist: bool,
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        Self {
+            check_keys_ordering: true,
+            check_freelist: true,
+        }
+    }
+}
+
+/// Kind of a problem found by [Transaction::check()].
+#[derive(Debug)]
+pub enum ProblemKind {
+    /// The page could not be read or its content is inconsistent, e.g. keys out of order.
+    BrokenPage(Error),
+    /// The page lies beyond the allocated part of the file.
+    PageOutOfBounds,
+    /// The page is referenced more than once.
+    PageReused,
+    /// The page is recorded as free by the garbage collector, but is in use.
+    FreePageInUse,
+    /// The page is recorded as free more than once.
+    FreePageDuplicated,
+    /// A record of the garbage collector could not be parsed.
+    BrokenFreelist,
+    /// The given number of pages is neither in use nor recorded as free.
+    LostPages(u64),
+}
+
+/// A problem found by [Transaction::check()].
+#[derive(Debug)]
+pub struct Problem {
+    /// Owner of the affected page. Problems with the freelist are reported for [PageOwner::Gc].
+    pub owner: PageOwner<'static>,
+    /// Number of the affected page, if the problem concerns a single page.
+    pub pgno: Option<u64>,
+    pub kind: ProblemKind,
+}
+
+/// Result of [Transaction::check()].
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// Number of pages allocated in the file.
+    pub allocated_pages: u64,
+    /// Number of pages in use by the meta pages, the garbage collector and all databases.
+    pub used_pages: u64,
+    /// Number of pages recorded as free by the garbage collector.
+    pub free_pages: u64,
+    pub problems: Vec<Problem>,
+}
+
+impl CheckReport {
+    /// Whether no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Problems found with pages of the given owner.
+    pub fn problems_of<'a>(
+        &'a self,
+        owner: &'a PageOwner<'_>,
+    ) -> impl Iterator<Item = &'a Problem> {
+        self.problems
+            .iter()
+            .filter(move |problem| problem.owner == *owner)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PageState {
+    Unknown,
+    Used,
+    Free,
+}
+
+impl<'env, K, E> Transaction<'env, K, E>
+where
+    K: TransactionKind,
+    E: EnvironmentKind,
+{
+    /// Checks the integrity of the snapshot of this transaction, like the `mdbx_chk` tool.
+    ///
+    /// Every page is visited to verify that it is readable, referenced only once and, with
+    /// [CheckOptions::check_freelist], not recorded as free at the same time. Problems are
+    /// collected into the report rather than failing the check.
+    pub fn check(&self, options: &CheckOptions) -> Result<CheckReport> {
+        let info = unsafe { self.env().info_for(self.txn())? };
+        let allocated = info.last_pgno() as u64 + 1;
+        let mut pages = vec![PageState::Unknown; allocated as usize];
+        let mut report = CheckReport {
+            allocated_pages: allocated,
+            ..Default::default()
+        };
+
+        self.walk_pages(options.check_keys_ordering, |visit| {
+            let owner = visit.owner.into_owned();
+            if let Some(e) = visit.error {
+                report.problems.push(Problem {
+                    owner: owner.clone(),
+                    pgno: Some(visit.pgno),
+                    kind: ProblemKind::BrokenPage(e),
+                });
+            }
+            if visit.page_type.is_subpage() || visit.page_type == PageType::Broken {
+                return ControlFlow::Continue(());
+            }
+            for pgno in visit.pgno..visit.pgno + visit.pages as u64 {
+                let kind = match pages.get_mut(pgno as usize) {
+                    None => ProblemKind::PageOutOfBounds,
+                    Some(PageState::Used) => ProblemKind::PageReused,
+                    Some(state) => {
+                        *state = PageState::Used;
+                        report.used_pages += 1;
+                        continue;
+                    }
+                };
+                report.problems.push(Problem {
+                    owner: owner.clone(),
+                    pgno: Some(pgno),
+                    kind,
+                });
+            }
+            ControlFlow::Continue(())
+        })?;
+
+        if options.check_freelist {
+            let gc = Database::freelist_db();
+            for item in self.cursor(&gc)?.iter_start::<(), Vec<u8>>() {
+                let (_, record) = item?;
+                let Some(free) = parse_pnl(&record) else {
+                    report.problems.push(Problem {
+                        owner: PageOwner::Gc,
+                        pgno: None,
+                        kind: ProblemKind::BrokenFreelist,
+                    });
+                    continue;
+                };
+                for pgno in free {
+                    let kind = match pages.get_mut(pgno as usize) {
+                        None => ProblemKind::PageOutOfBounds,
+                        Some(PageState::Used) => ProblemKind::FreePageInUse,
+                        Some(PageState::Free) => ProblemKind::FreePageDuplicated,
+                        Some(state) => {
+                            *state = PageState::Free;
+                            report.free_pages += 1;
+                            continue;
+                        }
+                    };
+                    report.problems.push(Problem {
+                        owner: PageOwner::Gc,
+                        pgno: Some(pgno),
+                        kind,
+                    });
+                }
+            }
+
+            let lost = allocated - report.used_pages - report.free_pages;
+            if lost > 0 {
+                report.problems.push(Problem {
+                    owner: PageOwner::Gc,
+                    pgno: None,
+                    kind: ProblemKind::LostPages(lost),
+                });
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+impl<E> Environment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Checks the integrity of the environment in a new read-only transaction.
+    ///
+    /// See [Transaction::check()].
+    pub fn check(&self, options: &CheckOptions) -> Result<CheckReport> {
+        self.begin_ro_txn()?.check(options)
+    }
+}
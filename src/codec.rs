@@ -1,6 +1,13 @@
 use crate::{error::mdbx_result, Error, TransactionKind};
 use derive_more::*;
-use std::{borrow::Cow, slice};
+use smallvec::SmallVec;
+use std::{
+    borrow::Cow,
+    mem,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    slice,
+    sync::Arc,
+};
 use thiserror::Error;
 
 /// Implement this to be able to decode data values
@@ -24,8 +31,8 @@ pub trait TableObject<'tx> {
 }
 
 impl<'tx> TableObject<'tx> for Cow<'tx, [u8]> {
-    fn decode(_: &[u8]) -> Result<Self, Error> {
-        unreachable!()
+    fn decode(data_val: &[u8]) -> Result<Self, Error> {
+        Ok(Cow::Owned(data_val.to_vec()))
     }
 
     #[doc(hidden)]
@@ -47,8 +54,8 @@ impl<'tx> TableObject<'tx> for Cow<'tx, [u8]> {
 
 #[cfg(feature = "lifetimed-bytes")]
 impl<'tx> TableObject<'tx> for lifetimed_bytes::Bytes<'tx> {
-    fn decode(_: &[u8]) -> Result<Self, Error> {
-        unreachable!()
+    fn decode(data_val: &[u8]) -> Result<Self, Error> {
+        Cow::<'tx, [u8]>::decode(data_val).map(From::from)
     }
 
     #[doc(hidden)]
@@ -115,3 +122,587 @@ impl<'tx, const LEN: usize> TableObject<'tx> for [u8; LEN] {
         Ok(a)
     }
 }
+
+/// Unsigned integers are stored in big-endian byte order, so that they sort numerically
+/// with the default comparator.
+///
+/// Databases with [DatabaseFlags::INTEGER_KEY](crate::DatabaseFlags::INTEGER_KEY) store keys in
+/// native byte order, so that on little-endian machines the two encodings of a number differ.
+/// Operations on such databases return [Error::Incompatible] for these keys instead of silently
+/// missing the item; use [IntegerTable](crate::keys::IntegerTable) or
+/// [IntKey](crate::keys::IntKey) there.
+macro_rules! impl_uint {
+    ($($t:ty),*) => {
+        $(
+            impl<'tx> TableObject<'tx> for $t {
+                fn decode(data_val: &[u8]) -> Result<Self, Error>
+                where
+                    Self: Sized,
+                {
+                    <[u8; mem::size_of::<$t>()] as TableObject<'tx>>::decode(data_val)
+                        .map(<$t>::from_be_bytes)
+                }
+            }
+
+            impl Encodable for $t {
+                type Encoded = [u8; mem::size_of::<$t>()];
+
+                const BIG_ENDIAN_INT: bool = true;
+
+                fn encode(self) -> Self::Encoded {
+                    self.to_be_bytes()
+                }
+            }
+        )*
+    };
+}
+
+impl_uint!(u16, u32, u64, u128);
+
+impl<'tx> TableObject<'tx> for String {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        String::from_utf8(data_val.to_vec()).map_err(|e| Error::DecodeError(Box::new(e)))
+    }
+}
+
+impl<'tx> TableObject<'tx> for Ipv4Addr {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        <[u8; 4] as TableObject<'tx>>::decode(data_val).map(From::from)
+    }
+}
+
+impl<'tx> TableObject<'tx> for Ipv6Addr {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        <[u8; 16] as TableObject<'tx>>::decode(data_val).map(From::from)
+    }
+}
+
+/// IPv4 addresses are stored as 4 bytes and IPv6 addresses as 16 bytes.
+impl<'tx> TableObject<'tx> for IpAddr {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        #[derive(Clone, Debug, Display, Error)]
+        struct InvalidIpAddrSize {
+            got: usize,
+        }
+
+        match data_val.len() {
+            4 => Ipv4Addr::decode(data_val).map(IpAddr::V4),
+            16 => Ipv6Addr::decode(data_val).map(IpAddr::V6),
+            got => Err(Error::DecodeError(Box::new(InvalidIpAddrSize { got }))),
+        }
+    }
+}
+
+impl<'tx, const N: usize> TableObject<'tx> for SmallVec<[u8; N]> {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Ok(Self::from_slice(data_val))
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl<'tx> TableObject<'tx> for uuid::Uuid {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        <[u8; 16] as TableObject<'tx>>::decode(data_val).map(Self::from_bytes)
+    }
+}
+
+/// Encoding of variable-length keys, stored inline up to 32 bytes.
+pub type SmallKey = SmallVec<[u8; 32]>;
+
+/// Implement this to be able to encode keys and data values.
+///
+/// Keys are encoded for every operation, so [Encodable::Encoded] should not allocate for common
+/// keys: fixed-size keys encode to arrays and short variable-length ones to a [SmallKey].
+pub trait Encodable {
+    type Encoded: AsRef<[u8]>;
+
+    /// Whether the key is an integer encoded in big-endian byte order. MDBX compares the keys of
+    /// databases with [DatabaseFlags::INTEGER_KEY](crate::DatabaseFlags::INTEGER_KEY) as
+    /// native-endian integers instead, so such keys are refused for them with
+    /// [Error::Incompatible].
+    const BIG_ENDIAN_INT: bool = false;
+
+    fn encode(self) -> Self::Encoded;
+}
+
+impl Encodable for &[u8] {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for &Vec<u8> {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for &str {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for &String {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl<'a> Encodable for Cow<'a, [u8]> {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl<'a> Encodable for &Cow<'a, [u8]> {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for Box<[u8]> {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for Arc<[u8]> {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for &Box<[u8]> {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for &Arc<[u8]> {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for Vec<u8> {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for String {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl<const LEN: usize> Encodable for [u8; LEN] {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl<const LEN: usize> Encodable for &[u8; LEN] {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+impl Encodable for Ipv4Addr {
+    type Encoded = [u8; 4];
+
+    fn encode(self) -> Self::Encoded {
+        self.octets()
+    }
+}
+
+impl Encodable for Ipv6Addr {
+    type Encoded = [u8; 16];
+
+    fn encode(self) -> Self::Encoded {
+        self.octets()
+    }
+}
+
+impl Encodable for IpAddr {
+    type Encoded = SmallVec<[u8; 16]>;
+
+    fn encode(self) -> Self::Encoded {
+        match self {
+            IpAddr::V4(addr) => SmallVec::from_slice(&addr.octets()),
+            IpAddr::V6(addr) => SmallVec::from_buf(addr.octets()),
+        }
+    }
+}
+
+impl<const N: usize> Encodable for SmallVec<[u8; N]> {
+    type Encoded = Self;
+
+    fn encode(self) -> Self::Encoded {
+        self
+    }
+}
+
+/// Uses the bytes of any [AsRef<\[u8\]>] type as a key, for types without an [Encodable] impl
+/// such as `bytes::Bytes`, which could be passed as keys before they became [Encodable].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawKey<T>(pub T);
+
+impl<T> Encodable for RawKey<T>
+where
+    T: AsRef<[u8]>,
+{
+    type Encoded = T;
+
+    fn encode(self) -> Self::Encoded {
+        self.0
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Encodable for uuid::Uuid {
+    type Encoded = [u8; 16];
+
+    fn encode(self) -> Self::Encoded {
+        self.into_bytes()
+    }
+}
+
+/// A serialization format used to store [serde] types as values.
+#[cfg(feature = "serde")]
+pub trait SerdeFormat {
+    fn serialize<T>(value: &T) -> Result<Vec<u8>, Error>
+    where
+        T: serde::Serialize + ?Sized;
+
+    fn deserialize<T>(data: &[u8]) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned;
+}
+
+/// The [bincode](https://docs.rs/bincode) format.
+#[cfg(feature = "bincode")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bincode;
+
+#[cfg(feature = "bincode")]
+impl SerdeFormat for Bincode {
+    fn serialize<T>(value: &T) -> Result<Vec<u8>, Error>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        bincode::serialize(value).map_err(|e| Error::EncodeError(e))
+    }
+
+    fn deserialize<T>(data: &[u8]) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        bincode::deserialize(data).map_err(|e| Error::DecodeError(e))
+    }
+}
+
+/// The [postcard](https://docs.rs/postcard) format.
+#[cfg(feature = "postcard")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Postcard;
+
+#[cfg(feature = "postcard")]
+impl SerdeFormat for Postcard {
+    fn serialize<T>(value: &T) -> Result<Vec<u8>, Error>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        postcard::to_allocvec(value).map_err(|e| Error::EncodeError(Box::new(e)))
+    }
+
+    fn deserialize<T>(data: &[u8]) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        postcard::from_bytes(data).map_err(|e| Error::DecodeError(Box::new(e)))
+    }
+}
+
+/// A value decoded with the serialization format `F`.
+///
+/// Can be used as the value type of any getter or iterator, e.g.
+/// `cursor.iter::<Vec<u8>, Serde<Bincode, MyType>>()`.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Serde<F, T>(pub T, std::marker::PhantomData<F>);
+
+#[cfg(feature = "serde")]
+impl<F, T> Serde<F, T> {
+    pub fn new(value: T) -> Self {
+        Self(value, std::marker::PhantomData)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'tx, F, T> TableObject<'tx> for Serde<F, T>
+where
+    F: SerdeFormat,
+    T: serde::de::DeserializeOwned,
+{
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        F::deserialize(data_val).map(Self::new)
+    }
+}
+
+/// An archived [rkyv] value, accessed without deserialization.
+///
+/// In read-only transactions the archive is borrowed directly from the memory map. It is only
+/// copied if the stored bytes are not sufficiently aligned for `T::Archived`, or if the value
+/// was modified in the current read-write transaction.
+#[cfg(feature = "rkyv")]
+pub struct Rkyv<'tx, T>
+where
+    T: rkyv::Archive,
+{
+    bytes: RkyvBytes<'tx>,
+    _marker: std::marker::PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "rkyv")]
+enum RkyvBytes<'tx> {
+    Borrowed(&'tx [u8]),
+    Owned(rkyv::AlignedVec),
+}
+
+#[cfg(feature = "rkyv")]
+impl<'tx, T> Rkyv<'tx, T>
+where
+    T: rkyv::Archive,
+    T::Archived: for<'a> rkyv::CheckBytes<rkyv::validation::validators::DefaultValidator<'a>>,
+{
+    fn new(data: Cow<'tx, [u8]>) -> Result<Self, Error> {
+        #[derive(Clone, Debug, Error)]
+        #[error("invalid archive: {0}")]
+        struct InvalidArchive(String);
+
+        let bytes = match data {
+            Cow::Borrowed(b)
+                if b.as_ptr().align_offset(std::mem::align_of::<T::Archived>()) == 0 =>
+            {
+                RkyvBytes::Borrowed(b)
+            }
+            data => {
+                let mut owned = rkyv::AlignedVec::with_capacity(data.len());
+                owned.extend_from_slice(&data);
+                RkyvBytes::Owned(owned)
+            }
+        };
+        let value = Self {
+            bytes,
+            _marker: std::marker::PhantomData,
+        };
+        rkyv::check_archived_root::<T>(value.as_bytes())
+            .map_err(|e| Error::DecodeError(Box::new(InvalidArchive(e.to_string()))))?;
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<'tx, T> Rkyv<'tx, T>
+where
+    T: rkyv::Archive,
+{
+    /// Returns the raw bytes of the archive.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.bytes {
+            RkyvBytes::Borrowed(b) => b,
+            RkyvBytes::Owned(owned) => owned.as_slice(),
+        }
+    }
+
+    /// Returns whether the archive is borrowed from the database without copying.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.bytes, RkyvBytes::Borrowed(_))
+    }
+}
+#[cfg(feature = "rkyv")]
+impl<'tx, T> std::ops::Deref for Rkyv<'tx, T>
+where
+    T: rkyv::Archive,
+{
+    type Target = T::Archived;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: the archive was validated on construction and its bytes are never mutated.
+        unsafe { rkyv::archived_root::<T>(self.as_bytes()) }
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<'tx, T> std::fmt::Debug for Rkyv<'tx, T>
+where
+    T: rkyv::Archive,
+    T::Archived: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<'tx, T> TableObject<'tx> for Rkyv<'tx, T>
+where
+    T: rkyv::Archive,
+    T::Archived: for<'a> rkyv::CheckBytes<rkyv::validation::validators::DefaultValidator<'a>>,
+{
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Self::new(Cow::Owned(data_val.to_vec()))
+    }
+
+    #[doc(hidden)]
+    unsafe fn decode_val<K: TransactionKind>(
+        txn: *const ffi::MDBX_txn,
+        data_val: &ffi::MDBX_val,
+    ) -> Result<Self, Error> {
+        Self::new(Cow::<'tx, [u8]>::decode_val::<K>(txn, data_val)?)
+    }
+}
+
+/// A [bytemuck::Pod] value read by reinterpreting the stored bytes.
+///
+/// The value is borrowed from the database if it is suitably aligned and was not modified in the
+/// current read-write transaction, and copied otherwise. Store values with
+/// [bytemuck::bytes_of()].
+#[cfg(feature = "bytemuck")]
+#[derive(Clone, Copy, Debug)]
+pub enum PodValue<'tx, T>
+where
+    T: bytemuck::Pod,
+{
+    Borrowed(&'tx T),
+    Owned(T),
+}
+
+#[cfg(feature = "bytemuck")]
+impl<'tx, T> PodValue<'tx, T>
+where
+    T: bytemuck::Pod,
+{
+    fn new(data: Cow<'tx, [u8]>) -> Result<Self, Error> {
+        #[derive(Clone, Debug, Error)]
+        #[error("invalid size: expected {expected} bytes, got {got}")]
+        struct InvalidSize {
+            expected: usize,
+            got: usize,
+        }
+
+        if data.len() != mem::size_of::<T>() {
+            return Err(Error::DecodeError(Box::new(InvalidSize {
+                expected: mem::size_of::<T>(),
+                got: data.len(),
+            })));
+        }
+
+        Ok(match data {
+            Cow::Borrowed(data) => match bytemuck::try_from_bytes(data) {
+                Ok(value) => PodValue::Borrowed(value),
+                Err(_) => PodValue::Owned(bytemuck::pod_read_unaligned(data)),
+            },
+            Cow::Owned(data) => PodValue::Owned(bytemuck::pod_read_unaligned(&data)),
+        })
+    }
+
+    /// Returns the value, copying it if necessary.
+    pub fn into_inner(self) -> T {
+        *self
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<'tx, T> std::ops::Deref for PodValue<'tx, T>
+where
+    T: bytemuck::Pod,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            PodValue::Borrowed(value) => value,
+            PodValue::Owned(value) => value,
+        }
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<'tx, T> TableObject<'tx> for PodValue<'tx, T>
+where
+    T: bytemuck::Pod,
+{
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Self::new(Cow::Owned(data_val.to_vec()))
+    }
+
+    #[doc(hidden)]
+    unsafe fn decode_val<K: TransactionKind>(
+        txn: *const ffi::MDBX_txn,
+        data_val: &ffi::MDBX_val,
+    ) -> Result<Self, Error> {
+        Self::new(Cow::<'tx, [u8]>::decode_val::<K>(txn, data_val)?)
+    }
+}
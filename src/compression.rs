@@ -0,0 +1,226 @@
+use crate::{Error, TableObject, TransactionKind};
+use std::{borrow::Cow, ops::Deref};
+use thiserror::Error;
+
+/// Prefix of values written by [compress()], followed by one byte identifying the codec.
+///
+/// Values without this prefix are returned as-is by [decompress()], so compressed and
+/// uncompressed values can be mixed in one database.
+pub const COMPRESSION_MAGIC: [u8; 2] = [0xfe, 0xc0];
+
+const CODEC_STORED: u8 = 0;
+#[cfg(feature = "lz4")]
+const CODEC_LZ4: u8 = 1;
+#[cfg(feature = "zstd")]
+const CODEC_ZSTD: u8 = 2;
+
+/// Compression algorithm used by [compress()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Store the value without compression.
+    None,
+    /// LZ4 block compression.
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// Zstandard compression with the given level.
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
+}
+
+#[derive(Clone, Debug, Error)]
+enum CompressionError {
+    #[error("unsupported compression codec {0}")]
+    UnsupportedCodec(u8),
+    #[error("truncated compression header")]
+    TruncatedHeader,
+    #[error("decompressed value of {len} bytes exceeds the maximum value size of {max} bytes")]
+    TooLarge { len: usize, max: usize },
+}
+
+/// Compresses a value and prepends the [COMPRESSION_MAGIC] header.
+///
+/// If compression does not make the value smaller, it is stored without a header, unless it
+/// happens to start with [COMPRESSION_MAGIC] itself.
+pub fn compress(data: &[u8], compression: Compression) -> Result<Vec<u8>, Error> {
+    let compressed: Option<(u8, Vec<u8>)> = match compression {
+        Compression::None => None,
+        #[cfg(feature = "lz4")]
+        Compression::Lz4 => Some((CODEC_LZ4, lz4_flex::compress_prepend_size(data))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd(level) => Some((
+            CODEC_ZSTD,
+            zstd::encode_all(data, level).map_err(|e| Error::EncodeError(Box::new(e)))?,
+        )),
+    };
+
+    Ok(match compressed {
+        Some((codec, compressed))
+            if compressed.len() + COMPRESSION_MAGIC.len() + 1 < data.len() =>
+        {
+            with_header(codec, &compressed)
+        }
+        _ if data.starts_with(&COMPRESSION_MAGIC) => with_header(CODEC_STORED, data),
+        _ => data.to_vec(),
+    })
+}
+
+fn with_header(codec: u8, data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(COMPRESSION_MAGIC.len() + 1 + data.len());
+    out.extend_from_slice(&COMPRESSION_MAGIC);
+    out.push(codec);
+    out.extend_from_slice(data);
+    out
+}
+
+/// Decompresses a value written by [compress()]. Values without a header are returned as-is.
+///
+/// Values which would decompress to more than the largest value MDBX can store are rejected,
+/// so that a corrupted header cannot make this allocate huge buffers.
+pub fn decompress(data: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
+    let max_len = unsafe { ffi::mdbx_limits_valsize_max(ffi::MDBX_MAX_PAGESIZE as isize, 0) };
+    decompress_limited(data, max_len.max(0) as usize)
+}
+
+/// Decompresses a value like [decompress()], failing if it exceeds `max_len` bytes.
+#[cfg_attr(not(any(feature = "lz4", feature = "zstd")), allow(unused_variables))]
+fn decompress_limited(data: &[u8], max_len: usize) -> Result<Cow<'_, [u8]>, Error> {
+    let too_large =
+        |len| Error::DecodeError(Box::new(CompressionError::TooLarge { len, max: max_len }));
+    let payload = match data.strip_prefix(&COMPRESSION_MAGIC[..]) {
+        Some(payload) => payload,
+        None => return Ok(Cow::Borrowed(data)),
+    };
+    let (&codec, payload) = payload
+        .split_first()
+        .ok_or_else(|| Error::DecodeError(Box::new(CompressionError::TruncatedHeader)))?;
+
+    match codec {
+        CODEC_STORED => Ok(Cow::Borrowed(payload)),
+        #[cfg(feature = "lz4")]
+        CODEC_LZ4 => {
+            // The size prefix is trusted by lz4_flex to allocate the output.
+            if let Some(prefix) = payload.get(..4) {
+                let len = u32::from_le_bytes(prefix.try_into().unwrap()) as usize;
+                if len > max_len {
+                    return Err(too_large(len));
+                }
+            }
+            lz4_flex::decompress_size_prepended(payload)
+                .map(Cow::Owned)
+                .map_err(|e| Error::DecodeError(Box::new(e)))
+        }
+        #[cfg(feature = "zstd")]
+        CODEC_ZSTD => {
+            use std::io::Read;
+
+            let mut out = Vec::new();
+            zstd::Decoder::new(payload)
+                .and_then(|decoder| decoder.take(max_len as u64 + 1).read_to_end(&mut out))
+                .map_err(|e| Error::DecodeError(Box::new(e)))?;
+            if out.len() > max_len {
+                return Err(too_large(out.len()));
+            }
+            Ok(Cow::Owned(out))
+        }
+        other => Err(Error::DecodeError(Box::new(
+            CompressionError::UnsupportedCodec(other),
+        ))),
+    }
+}
+
+/// A value decompressed with [decompress()].
+///
+/// Uncompressed values are borrowed without copying, the same way as with [Cow].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decompressed<'tx>(pub Cow<'tx, [u8]>);
+
+impl<'tx> Decompressed<'tx> {
+    pub fn into_inner(self) -> Cow<'tx, [u8]> {
+        self.0
+    }
+}
+
+impl<'tx> Deref for Decompressed<'tx> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'tx> TableObject<'tx> for Decompressed<'tx> {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Ok(Self(Cow::Owned(decompress(data_val)?.into_owned())))
+    }
+
+    #[doc(hidden)]
+    unsafe fn decode_val<K: TransactionKind>(
+        txn: *const ffi::MDBX_txn,
+        data_val: &ffi::MDBX_val,
+    ) -> Result<Self, Error> {
+        let max_len = ffi::mdbx_env_get_maxvalsize_ex(ffi::mdbx_txn_env(txn), 0).max(0) as usize;
+        Ok(Self(
+            match Cow::<'tx, [u8]>::decode_val::<K>(txn, data_val)? {
+                Cow::Borrowed(data) => decompress_limited(data, max_len)?,
+                Cow::Owned(data) => Cow::Owned(decompress_limited(&data, max_len)?.into_owned()),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stored() {
+        assert_eq!(compress(b"value", Compression::None).unwrap(), b"value");
+        assert_eq!(decompress(b"value").unwrap(), &b"value"[..]);
+
+        let data = [0xfe, 0xc0, 0x01];
+        let stored = compress(&data, Compression::None).unwrap();
+        assert_eq!(stored, [0xfe, 0xc0, CODEC_STORED, 0xfe, 0xc0, 0x01]);
+        assert_eq!(decompress(&stored).unwrap(), &data[..]);
+
+        assert!(decompress(&[0xfe, 0xc0]).is_err());
+        assert!(decompress(&[0xfe, 0xc0, 0xff]).is_err());
+    }
+
+    #[cfg(any(feature = "lz4", feature = "zstd"))]
+    #[test]
+    fn test_roundtrip() {
+        let data = b"value".repeat(100);
+        let mut codecs = vec![];
+        #[cfg(feature = "lz4")]
+        codecs.push(Compression::Lz4);
+        #[cfg(feature = "zstd")]
+        codecs.push(Compression::Zstd(3));
+
+        for codec in codecs {
+            let compressed = compress(&data, codec).unwrap();
+            assert!(compressed.len() < data.len());
+            assert!(compressed.starts_with(&COMPRESSION_MAGIC));
+            assert_eq!(decompress(&compressed).unwrap(), &data[..]);
+
+            // Incompressible values are stored as-is.
+            assert_eq!(compress(b"v", codec).unwrap(), b"v");
+
+            assert!(decompress_limited(&compressed, data.len()).is_ok());
+            assert!(matches!(
+                decompress_limited(&compressed, data.len() - 1),
+                Err(Error::DecodeError(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_lz4_size_prefix() {
+        let data = [0xfe, 0xc0, CODEC_LZ4, 0xff, 0xff, 0xff, 0xff, 0x00];
+        let err = decompress(&data).unwrap_err();
+        assert!(err.to_string().contains("exceeds the maximum value size"));
+    }
+}
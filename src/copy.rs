@@ -0,0 +1,166 @@
+use crate::{
+    error::{io_error, mdbx_result},
+    Environment, EnvironmentBuilder, EnvironmentFlags, EnvironmentKind, Error, Result,
+};
+use std::{
+    ffi::CString,
+    fs::{self, File},
+    io::{self, Write},
+    os::unix::{
+        ffi::OsStrExt,
+        io::{AsRawFd, FromRawFd},
+    },
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Time between two reports of [Environment::compact_to()].
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress of a compaction, as reported by [Environment::compact_to()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompactProgress {
+    /// Bytes written to the copy so far.
+    pub bytes: u64,
+    /// Pages written to the copy so far.
+    pub pages: u64,
+    /// Pages in use in the environment when the compaction started. The copy ends up with about
+    /// as many pages, as only the free pages are left out.
+    pub total_pages: u64,
+    /// Time since the compaction started.
+    pub elapsed: Duration,
+    /// Whether the copy is complete, which is only the case of the last report.
+    pub done: bool,
+}
+
+impl<E> Environment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Copies the environment into a new file at `path`, leaving out free pages and renumbering
+    /// the others so that the copy is as small as possible.
+    ///
+    /// The copy runs on a worker thread from a read-only snapshot, so writers are not blocked,
+    /// while `progress` is called on the current thread about every 100 ms, and once more when
+    /// the copy is complete. The file at `path` must not exist.
+    pub fn compact_to(
+        &self,
+        path: impl AsRef<Path>,
+        mut progress: impl FnMut(CompactProgress),
+    ) -> Result<()> {
+        let path = path.as_ref();
+        let dest = CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::Invalid)?;
+        let page_size = self.stat()?.page_size() as u64;
+        let total_pages =
+            (self.info()?.last_pgno() as u64 + 1).saturating_sub(self.gc_info()?.pages());
+        let start = Instant::now();
+        let report = |done| {
+            let bytes = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            CompactProgress {
+                bytes,
+                pages: bytes / page_size,
+                total_pages,
+                elapsed: start.elapsed(),
+                done,
+            }
+        };
+
+        let caller = thread::current();
+        thread::scope(|scope| {
+            let worker = thread::Builder::new()
+                .name("mdbx-compact".to_string())
+                .spawn_scoped(scope, || {
+                    let res = mdbx_result(unsafe {
+                        ffi::mdbx_env_copy(self.env(), dest.as_ptr(), ffi::MDBX_CP_COMPACT)
+                    });
+                    caller.unpark();
+                    res
+                })
+                .expect("failed to spawn compaction thread");
+            while !worker.is_finished() {
+                thread::park_timeout(PROGRESS_INTERVAL);
+                if !worker.is_finished() {
+                    progress(report(false));
+                }
+            }
+            worker.join().expect("compaction thread panicked")?;
+            progress(report(true));
+            Ok(())
+        })
+    }
+
+    /// Writes a copy of the environment to `out`, in the format of the data file.
+    ///
+    /// The copy runs on a worker thread from a read-only snapshot, so writers are not blocked,
+    /// and is streamed to `out` through a pipe, without a temporary file. The output can be
+    /// restored by writing it to the data file of a new environment.
+    pub fn backup(&self, mut out: impl Write) -> Result<()> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(io_error(io::Error::last_os_error()));
+        }
+        let (mut reader, writer) =
+            unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+
+        thread::scope(|scope| {
+            let worker = thread::Builder::new()
+                .name("mdbx-backup".to_string())
+                .spawn_scoped(scope, move || {
+                    mdbx_result(unsafe {
+                        ffi::mdbx_env_copy2fd(self.env(), writer.as_raw_fd(), ffi::MDBX_CP_DEFAULTS)
+                    })
+                })
+                .expect("failed to spawn backup thread");
+            let copied = io::copy(&mut reader, &mut out).and_then(|_| out.flush());
+            // Closing the pipe makes the worker fail instead of blocking if `out` failed.
+            drop(reader);
+            let res = worker.join().expect("backup thread panicked");
+            copied.map_err(io_error)?;
+            res.map(|_| ())
+        })
+    }
+}
+
+impl<E> EnvironmentBuilder<E>
+where
+    E: EnvironmentKind,
+{
+    /// Opens the environment at `path` after reclaiming its free space, by compacting it into a
+    /// temporary file next to the data file which then replaces it.
+    ///
+    /// The environment is opened in exclusive mode until the swap is done, so this fails if
+    /// another process has it open, and no other process can open it in the meantime. Readers of this process must not have it open either, as they would keep
+    /// reading the replaced file.
+    pub fn vacuum(&self, path: &Path) -> Result<Environment<E>> {
+        let data = if self.flags.no_sub_dir {
+            path.to_path_buf()
+        } else {
+            path.join("mdbx.dat")
+        };
+        let mut temp = data.clone().into_os_string();
+        temp.push("-vacuum");
+        let temp = Path::new(&temp);
+
+        let mut exclusive = self.clone();
+        exclusive.set_flags(EnvironmentFlags {
+            exclusive: true,
+            ..self.flags
+        });
+        let env = exclusive.open(path)?;
+        match fs::remove_file(temp) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(io_error(e)),
+            _ => {}
+        }
+        let compacted = env.compact_to(temp, |_| {});
+        let swapped = compacted.and_then(|()| fs::rename(temp, &data).map_err(io_error));
+        if swapped.is_err() {
+            let _ = fs::remove_file(temp);
+        }
+        // The exclusive lock is held until the new file is in place.
+        drop(env);
+        swapped?;
+
+        self.open(path)
+    }
+}
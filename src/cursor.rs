@@ -1,10 +1,12 @@
+#[cfg(feature = "serde")]
+use crate::SerdeFormat;
 use crate::{
     database::Database,
-    error::{mdbx_result, Error, Result},
+    error::{mdbx_result, Error, Operation, Result},
     flags::*,
     mdbx_try_optional,
-    transaction::{txn_execute, TransactionKind, RW},
-    EnvironmentKind, TableObject, Transaction,
+    transaction::{check_item_size, txn_execute, PoisonGuard, TransactionKind, RW},
+    EnvironmentKind, Stat, TableObject, Transaction,
 };
 use ffi::{
     MDBX_cursor_op, MDBX_FIRST, MDBX_FIRST_DUP, MDBX_GET_BOTH, MDBX_GET_BOTH_RANGE,
@@ -14,7 +16,17 @@ use ffi::{
 };
 use libc::{c_uint, c_void};
 use parking_lot::Mutex;
-use std::{borrow::Cow, fmt, marker::PhantomData, mem, ptr, result, sync::Arc};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Bound, Deref, DerefMut, RangeBounds, RangeFull},
+    ptr, result, slice,
+    sync::Arc,
+};
 
 /// A cursor for navigating the items within a database.
 pub struct Cursor<'txn, K>
@@ -23,6 +35,11 @@ where
 {
     txn: Arc<Mutex<*mut ffi::MDBX_txn>>,
     cursor: *mut ffi::MDBX_cursor,
+    /// Whether failed operations carry their context, see
+    /// [EnvironmentBuilder::set_op_context()](crate::EnvironmentBuilder::set_op_context).
+    op_context: bool,
+    /// Keeps the database handle from being closed while the cursor is open.
+    _handle: Option<Arc<Option<String>>>,
     _marker: PhantomData<fn(&'txn (), K)>,
 }
 
@@ -36,6 +53,7 @@ where
     ) -> Result<Self> {
         let mut cursor: *mut ffi::MDBX_cursor = ptr::null_mut();
 
+        let op_context = txn.env().has_op_context();
         let txn = txn.txn_mutex();
         unsafe {
             mdbx_result(txn_execute(&*txn, |txn| {
@@ -45,6 +63,8 @@ where
         Ok(Self {
             txn,
             cursor,
+            op_context,
+            _handle: db.handle(),
             _marker: PhantomData,
         })
     }
@@ -52,21 +72,35 @@ where
     fn new_at_position(other: &Self) -> Result<Self> {
         unsafe {
             let cursor = ffi::mdbx_cursor_create(ptr::null_mut());
+            if cursor.is_null() {
+                return Err(Error::from_err_code(ffi::MDBX_ENOMEM));
+            }
 
-            let res = ffi::mdbx_cursor_copy(other.cursor(), cursor);
+            // Closed directly on failure, as dropping a cursor takes the transaction lock which
+            // the caller holds.
+            if let Err(e) = mdbx_result(ffi::mdbx_cursor_copy(other.cursor(), cursor)) {
+                ffi::mdbx_cursor_close(cursor);
+                return Err(e);
+            }
 
-            let s = Self {
+            Ok(Self {
                 txn: other.txn.clone(),
                 cursor,
+                op_context: other.op_context,
+                _handle: other._handle.clone(),
                 _marker: PhantomData,
-            };
-
-            mdbx_result(res)?;
-
-            Ok(s)
+            })
         }
     }
 
+    /// Creates a new cursor positioned at the same item as this one.
+    ///
+    /// Unlike [Clone::clone()], this reports failures (e.g. out of memory or a finished
+    /// transaction) instead of panicking.
+    pub fn try_clone(&self) -> Result<Self> {
+        txn_execute(&*self.txn, |_| Self::new_at_position(self))
+    }
+
     /// Returns a raw pointer to the underlying MDBX cursor.
     ///
     /// The caller **must** ensure that the pointer is not used after the
@@ -75,6 +109,162 @@ where
         self.cursor
     }
 
+    /// Returns the number of entries in the database the cursor is bound to, if it can be determined.
+    fn db_entries(&self) -> Option<usize> {
+        unsafe {
+            let mut stat = Stat::new();
+            mdbx_result(txn_execute(&*self.txn, |txn| {
+                ffi::mdbx_dbi_stat(
+                    txn,
+                    ffi::mdbx_cursor_dbi(self.cursor),
+                    stat.mdb_stat(),
+                    mem::size_of::<Stat>(),
+                )
+            }))
+            .ok()?;
+            Some(stat.entries())
+        }
+    }
+
+    /// Estimates how many items an iterator starting with `op` and moving with `next_op` returns,
+    /// for [Iterator::size_hint()]. The lower bound is estimated from the position of the cursor,
+    /// so that collecting the iterator preallocates, and clamped to the upper bound.
+    fn estimate_len(
+        &self,
+        op: ffi::MDBX_cursor_op,
+        next_op: ffi::MDBX_cursor_op,
+    ) -> (usize, Option<usize>) {
+        if next_op == MDBX_NEXT_DUP {
+            // Iterators over duplicates start at the first one of the key. MDBX does not estimate
+            // distances between duplicates, so the rest of them is only bounded by their count.
+            let count = self.dup_count();
+            let lower = if op == MDBX_GET_CURRENT { count } else { None };
+            return (lower.unwrap_or(0), count);
+        }
+        let upper = self.db_entries();
+        let lower = if op == MDBX_FIRST {
+            upper.unwrap_or(0)
+        } else {
+            let mut distance = 0;
+            txn_execute(&*self.txn, |_| unsafe {
+                match ffi::mdbx_estimate_move(
+                    self.cursor,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    MDBX_LAST,
+                    &mut distance,
+                ) {
+                    ffi::MDBX_SUCCESS => {
+                        let current = op == MDBX_GET_CURRENT
+                            && ffi::mdbx_cursor_eof(self.cursor) == ffi::MDBX_RESULT_FALSE;
+                        distance.max(0) as usize + current as usize
+                    }
+                    // An unpositioned cursor moves to the first item.
+                    ffi::MDBX_ENODATA if op == MDBX_NEXT => upper.unwrap_or(0),
+                    _ => 0,
+                }
+            })
+        };
+        (upper.map_or(lower, |upper| lower.min(upper)), upper)
+    }
+
+    /// Returns the number of duplicates of the current key.
+    fn dup_count(&self) -> Option<usize> {
+        let mut count = 0;
+        mdbx_result(txn_execute(&*self.txn, |_| unsafe {
+            ffi::mdbx_cursor_count(self.cursor, &mut count)
+        }))
+        .ok()?;
+        Some(count)
+    }
+
+    /// Moves the cursor with `op` and decodes the item it lands on, for the iterators. Returns
+    /// [None] at the end of the database.
+    #[inline]
+    fn read_item<Key, Value>(&self, op: ffi::MDBX_cursor_op) -> Option<Result<(Key, Value)>>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        let mut key = ffi::MDBX_val {
+            iov_len: 0,
+            iov_base: ptr::null_mut(),
+        };
+        let mut data = ffi::MDBX_val {
+            iov_len: 0,
+            iov_base: ptr::null_mut(),
+        };
+        unsafe {
+            txn_execute(&*self.txn, |txn| {
+                match ffi::mdbx_cursor_get(self.cursor, &mut key, &mut data, op) {
+                    ffi::MDBX_SUCCESS => {
+                        Some(decode_pair::<K, _, _>(txn, self.cursor, &key, &data))
+                    }
+                    // MDBX_ENODATA can occur when the cursor was previously seeked to a non-existent value,
+                    // e.g. iter_from with a key greater than all values in the database.
+                    ffi::MDBX_NOTFOUND | ffi::MDBX_ENODATA => None,
+                    error => Some(Err(Error::from_err_code(error))),
+                }
+            })
+        }
+    }
+
+    /// Positions the cursor at the first item satisfying the given start bound.
+    pub(crate) fn seek_range_start<Value>(
+        &mut self,
+        start: Bound<&[u8]>,
+    ) -> Result<Option<(Cow<'txn, [u8]>, Value)>>
+    where
+        Value: TableObject<'txn>,
+    {
+        match start {
+            Bound::Included(start) => self.set_range(start),
+            Bound::Excluded(start) => {
+                let item = self.set_range::<Cow<'txn, [u8]>, Value>(start)?;
+                match item {
+                    Some((key, _)) if self.cmp_keys(&key, start) == Ordering::Equal => {
+                        self.next_nodup()
+                    }
+                    item => Ok(item),
+                }
+            }
+            Bound::Unbounded => self.first(),
+        }
+    }
+
+    /// Returns whether `key` satisfies the given end bound.
+    pub(crate) fn before_range_end(&self, key: &[u8], end: Bound<&[u8]>) -> bool {
+        match end {
+            Bound::Included(end) => self.cmp_keys(key, end) != Ordering::Greater,
+            Bound::Excluded(end) => self.cmp_keys(key, end) == Ordering::Less,
+            Bound::Unbounded => true,
+        }
+    }
+
+    /// Compares two keys using the comparator of the database the cursor is bound to.
+    pub(crate) fn cmp_keys(&self, a: &[u8], b: &[u8]) -> Ordering {
+        unsafe {
+            let a = slice_to_val(Some(a));
+            let b = slice_to_val(Some(b));
+            txn_execute(&*self.txn, |txn| {
+                ffi::mdbx_cmp(txn, ffi::mdbx_cursor_dbi(self.cursor), &a, &b)
+            })
+            .cmp(&0)
+        }
+    }
+
+    /// Compares two duplicates using the comparator of the database the cursor is bound to.
+    pub(crate) fn cmp_values(&self, a: &[u8], b: &[u8]) -> Ordering {
+        unsafe {
+            let a = slice_to_val(Some(a));
+            let b = slice_to_val(Some(b));
+            txn_execute(&*self.txn, |txn| {
+                ffi::mdbx_dcmp(txn, ffi::mdbx_cursor_dbi(self.cursor), &a, &b)
+            })
+            .cmp(&0)
+        }
+    }
+
     /// Retrieves a key/data pair from the cursor. Depending on the cursor op,
     /// the current key may be returned.
     fn get<Key, Value>(
@@ -100,20 +290,45 @@ where
                     op,
                 ))?;
                 assert_ne!(data_ptr, data_val.iov_base);
+                let with_context = |e: Error| {
+                    e.with_decode_context(
+                        txn,
+                        ffi::mdbx_cursor_dbi(self.cursor),
+                        &key_val,
+                        &data_val,
+                    )
+                };
                 let key_out = {
                     // MDBX wrote in new key
                     if key_ptr != key_val.iov_base {
-                        Some(Key::decode_val::<K>(txn, &key_val)?)
+                        Some(Key::decode_val::<K>(txn, &key_val).map_err(with_context)?)
                     } else {
                         None
                     }
                 };
-                let data_out = Value::decode_val::<K>(txn, &data_val)?;
+                let data_out = Value::decode_val::<K>(txn, &data_val).map_err(with_context)?;
                 Ok((key_out, data_out, v))
             })
+            .map_err(|e| self.op_error(e, Operation::Cursor, key))
         }
     }
 
+    /// Attaches the operation, database and key to the error of a failed operation, if enabled
+    /// with [EnvironmentBuilder::set_op_context()](crate::EnvironmentBuilder::set_op_context).
+    fn op_error(&self, error: Error, operation: Operation, key: Option<&[u8]>) -> Error {
+        if !self.op_context {
+            return error;
+        }
+        txn_execute(&*self.txn, |txn| unsafe {
+            error.with_op_context(
+                txn,
+                ffi::mdbx_cursor_dbi(self.cursor),
+                operation,
+                key.unwrap_or_default(),
+            )
+        })
+    }
+
     fn get_value<Value>(
         &mut self,
         key: Option<&[u8]>,
@@ -152,76 +367,533 @@ where
         self.get_full(None, None, MDBX_FIRST)
     }
 
-    /// [DatabaseFlags::DUP_SORT]-only: Position at first data item of current key.
-    pub fn first_dup<Value>(&mut self) -> Result<Option<Value>>
+    /// Return key/data at current cursor position.
+    pub fn get_current<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
+        Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_value(None, None, MDBX_FIRST_DUP)
+        self.get_full(None, None, MDBX_GET_CURRENT)
     }
 
-    /// [DatabaseFlags::DUP_SORT]-only: Position at key/data pair.
-    pub fn get_both<Value>(&mut self, k: &[u8], v: &[u8]) -> Result<Option<Value>>
+    /// Position at last key/data item.
+    pub fn last<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
+        Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_value(Some(k), Some(v), MDBX_GET_BOTH)
+        self.get_full(None, None, MDBX_LAST)
     }
 
-    /// [DatabaseFlags::DUP_SORT]-only: Position at given key and at first data greater than or equal to specified data.
-    pub fn get_both_range<Value>(&mut self, k: &[u8], v: &[u8]) -> Result<Option<Value>>
+    /// Position at next data item
+    #[allow(clippy::should_implement_trait)]
+    pub fn next<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
+        Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_value(Some(k), Some(v), MDBX_GET_BOTH_RANGE)
+        self.get_full(None, None, MDBX_NEXT)
     }
 
-    /// Return key/data at current cursor position.
-    pub fn get_current<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
+    /// Position at first data item of next key.
+    pub fn next_nodup<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(None, None, MDBX_GET_CURRENT)
+        self.get_full(None, None, MDBX_NEXT_NODUP)
     }
 
-    /// DupFixed-only: Return up to a page of duplicate data items from current cursor position.
-    /// Move cursor to prepare for [Self::next_multiple()].
-    pub fn get_multiple<Value>(&mut self) -> Result<Option<Value>>
+    /// Position at previous data item.
+    pub fn prev<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
+        Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_value(None, None, MDBX_GET_MULTIPLE)
+        self.get_full(None, None, MDBX_PREV)
     }
 
-    /// Position at last key/data item.
-    pub fn last<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
+    /// Position at last data item of previous key.
+    pub fn prev_nodup<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(None, None, MDBX_LAST)
+        self.get_full(None, None, MDBX_PREV_NODUP)
     }
 
-    /// DupSort-only: Position at last data item of current key.
-    pub fn last_dup<Value>(&mut self) -> Result<Option<Value>>
+    /// Position at specified key.
+    pub fn set<Value>(&mut self, key: &[u8]) -> Result<Option<Value>>
     where
         Value: TableObject<'txn>,
     {
-        self.get_value(None, None, MDBX_LAST_DUP)
+        self.get_value(Some(key), None, MDBX_SET)
     }
 
-    /// Position at next data item
-    #[allow(clippy::should_implement_trait)]
-    pub fn next<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
+    /// Position at specified key, return both key and data.
+    pub fn set_key<Key, Value>(&mut self, key: &[u8]) -> Result<Option<(Key, Value)>>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(None, None, MDBX_NEXT)
+        self.get_full(Some(key), None, MDBX_SET_KEY)
     }
 
-    /// [DatabaseFlags::DUP_SORT]-only: Position at next data item of current key.
+    /// Position at exactly the specified key, return both key and data.
+    ///
+    /// Returns [None] if the key is not present in the database.
+    pub fn seek_exact<Key, Value>(&mut self, key: &[u8]) -> Result<Option<(Key, Value)>>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        self.get_full(Some(key), None, MDBX_SET_KEY)
+    }
+
+    /// Position at exactly the specified key/data pair, return both key and data.
+    ///
+    /// For [DatabaseFlags::DUP_SORT] databases this seeks to the given duplicate of the key.
+    /// Returns [None] if the pair is not present in the database.
+    pub fn seek_exact_dup<Key, Value>(
+        &mut self,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<(Key, Value)>>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        if self
+            .get_value::<()>(Some(key), Some(value), MDBX_GET_BOTH)?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        self.get_current()
+    }
+
+    /// Gets the values of multiple keys which are sorted in the order of the database.
+    ///
+    /// The cursor moves forward from the first key, stepping to the following keys while they
+    /// are close to each other, so that they are found without descending the B-tree from the
+    /// root again. Duplicate keys are allowed. Returns [Error::InvalidValue] at the first key out
+    /// of order. The cursor is left at the first item at or after the last key.
+    pub fn get_sorted<Key, Value>(
+        &mut self,
+        keys: impl IntoIterator<Item = Key>,
+    ) -> Result<Vec<Option<Value>>>
+    where
+        Key: AsRef<[u8]>,
+        Value: TableObject<'txn>,
+    {
+        // Keys further apart than this many steps are sought directly.
+        const MAX_STEPS: usize = 8;
+
+        let mut values = Vec::new();
+        let mut prev: Option<Key> = None;
+        // The key the cursor is at, None once it moved past the last item.
+        let mut current: Option<Cow<'txn, [u8]>> = None;
+        for key in keys {
+            let target = key.as_ref();
+            let mut steps = 0;
+            match &prev {
+                None => current = self.set_range::<_, ()>(target)?.map(|(key, ())| key),
+                Some(prev) => {
+                    if self.cmp_keys(target, prev.as_ref()) == Ordering::Less {
+                        return Err(Error::InvalidValue);
+                    }
+                    while let Some(key) = &current {
+                        if self.cmp_keys(key, target) != Ordering::Less {
+                            break;
+                        }
+                        current = if steps < MAX_STEPS {
+                            steps += 1;
+                            self.next_nodup::<_, ()>()?
+                        } else {
+                            self.set_range::<_, ()>(target)?
+                        }
+                        .map(|(key, ())| key);
+                    }
+                }
+            }
+            values.push(match &current {
+                Some(key) if self.cmp_keys(key, target) == Ordering::Equal => {
+                    self.get_current::<(), _>()?.map(|((), value)| value)
+                }
+                _ => None,
+            });
+            prev = Some(key);
+        }
+        Ok(values)
+    }
+
+    /// Position at first key greater than or equal to specified key.
+    pub fn set_range<Key, Value>(&mut self, key: &[u8]) -> Result<Option<(Key, Value)>>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        self.get_full(Some(key), None, MDBX_SET_RANGE)
+    }
+
+    /// Position at first key-value pair greater than or equal to specified, return both key and data, and the return code depends on a exact match.
+    ///
+    /// For non DupSort-ed collections this works the same as [Self::set_range()], but returns [false] if key found exactly and [true] if greater key was found.
+    ///
+    /// For DupSort-ed a data value is taken into account for duplicates, i.e. for a pairs/tuples of a key and an each data value of duplicates.
+    /// Returns [false] if key-value pair found exactly and [true] if the next pair was returned.
+    pub fn set_lowerbound<Key, Value>(&mut self, key: &[u8]) -> Result<Option<(bool, Key, Value)>>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        let (k, v, found) = mdbx_try_optional!(self.get(Some(key), None, MDBX_SET_LOWERBOUND));
+
+        Ok(Some((found, k.unwrap(), v)))
+    }
+
+    /// Iterate over database items. The iterator will begin with item next
+    /// after the cursor, and continue until the end of the database. For new
+    /// cursors, the iterator will begin with the first item in the database.
+    ///
+    /// For databases with duplicate data items ([DatabaseFlags::DUP_SORT]), the
+    /// duplicate data items of each key will be returned before moving on to
+    /// the next key.
+    pub fn iter<Key, Value>(&mut self) -> Iter<'txn, '_, K, Key, Value>
+    where
+        Self: Sized,
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        Iter::new(self, ffi::MDBX_NEXT, ffi::MDBX_NEXT)
+    }
+
+    /// Iterate over database items starting from the beginning of the database.
+    ///
+    /// For databases with duplicate data items ([DatabaseFlags::DUP_SORT]), the
+    /// duplicate data items of each key will be returned before moving on to
+    /// the next key.
+    pub fn iter_start<Key, Value>(&mut self) -> Iter<'txn, '_, K, Key, Value>
+    where
+        Self: Sized,
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        Iter::new(self, ffi::MDBX_FIRST, ffi::MDBX_NEXT)
+    }
+
+    /// Iterate over database items starting from the given key.
+    ///
+    /// For databases with duplicate data items ([DatabaseFlags::DUP_SORT]), the
+    /// duplicate data items of each key will be returned before moving on to
+    /// the next key.
+    pub fn iter_from<Key, Value>(&mut self, key: &[u8]) -> Iter<'txn, '_, K, Key, Value>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        let res: Result<Option<((), ())>> = self.set_range(key);
+        if let Err(error) = res {
+            return Iter::Err(Some(error));
+        };
+        Iter::new(self, ffi::MDBX_GET_CURRENT, ffi::MDBX_NEXT)
+    }
+
+    /// Iterate over database items like [Cursor::iter()], reading up to `batch` items at a
+    /// time.
+    ///
+    /// Items are read a page at a time with a single call into MDBX where possible, and decoded
+    /// ahead of being returned. [DatabaseFlags::DUP_SORT] databases are read one item per call,
+    /// still in batches of `batch` items. Once the iterator is dropped, the cursor may be
+    /// positioned after the items it returned.
+    pub fn iter_batched<Key, Value>(&mut self, batch: usize) -> IterBatched<'txn, '_, K, Key, Value>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        IterBatched::new(self, batch)
+    }
+
+    /// Hints the operating system to read the `pages` database pages from the one the cursor is
+    /// at into memory, before scanning forward from there.
+    ///
+    /// This helps large scans of databases which are not cached yet, especially on spinning or
+    /// network disks, when read-ahead is disabled with
+    /// [EnvironmentFlags::no_rdahead](crate::EnvironmentFlags::no_rdahead) or too small. The
+    /// pages following in the file are only the next ones in key order for databases written
+    /// in order, e.g. by a [BulkLoader](crate::BulkLoader). Does nothing if the cursor is
+    /// unpositioned, or at a page modified by the current transaction.
+    pub fn prefetch_hint(&self, pages: usize) -> Result<()> {
+        let mut key_val = unsafe { slice_to_val(None) };
+        let mut data_val = unsafe { slice_to_val(None) };
+        txn_execute(&*self.txn, |txn| unsafe {
+            match ffi::mdbx_cursor_get(self.cursor, &mut key_val, &mut data_val, MDBX_GET_CURRENT) {
+                ffi::MDBX_SUCCESS => {}
+                ffi::MDBX_NOTFOUND | ffi::MDBX_ENODATA => return Ok(()),
+                err_code => return Err(Error::from_err_code(err_code)),
+            }
+            if mdbx_result(ffi::mdbx_is_dirty(txn, key_val.iov_base))? {
+                return Ok(());
+            }
+            let mut info: ffi::MDBX_envinfo = mem::zeroed();
+            mdbx_result(ffi::mdbx_env_info_ex(
+                ffi::mdbx_txn_env(txn),
+                txn,
+                &mut info,
+                mem::size_of::<ffi::MDBX_envinfo>(),
+            ))?;
+            let sys_page = info.mi_sys_pagesize as usize;
+            let start = key_val.iov_base as usize / sys_page * sys_page;
+            let len = pages
+                .saturating_mul(info.mi_dxb_pagesize as usize)
+                .min(isize::MAX as usize - start);
+            // Parts of the range past the end of the memory map are ignored.
+            match libc::madvise(start as *mut c_void, len, libc::MADV_WILLNEED) {
+                0 => Ok(()),
+                _ => match std::io::Error::last_os_error().raw_os_error() {
+                    Some(libc::ENOMEM) => Ok(()),
+                    Some(code) => Err(Error::from_err_code(code)),
+                    None => Ok(()),
+                },
+            }
+        })
+    }
+}
+
+impl<'txn> Cursor<'txn, RW> {
+    /// Puts a key/data pair into the database. The cursor will be positioned at
+    /// the new data item, or on failure usually near it.
+    pub fn put(&mut self, key: &[u8], data: &[u8], flags: WriteFlags) -> Result<()> {
+        let key_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: key.len(),
+            iov_base: key.as_ptr() as *mut c_void,
+        };
+        let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: data.len(),
+            iov_base: data.as_ptr() as *mut c_void,
+        };
+        unsafe {
+            txn_execute(&*self.txn, |txn| {
+                check_item_size(
+                    txn,
+                    ffi::mdbx_cursor_dbi(self.cursor),
+                    key.len(),
+                    data.len(),
+                )?;
+                mdbx_result(ffi::mdbx_cursor_put(
+                    self.cursor,
+                    &key_val,
+                    &mut data_val,
+                    flags.bits(),
+                ))
+            })
+            .map_err(|e| self.op_error(e, Operation::Put, Some(key)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the value with the format `F` and puts it into the database.
+    ///
+    /// See [Cursor::put()].
+    #[cfg(feature = "serde")]
+    pub fn put_ser<F, T>(&mut self, key: &[u8], value: &T, flags: WriteFlags) -> Result<()>
+    where
+        F: SerdeFormat,
+        T: serde::Serialize + ?Sized,
+    {
+        self.put(key, &F::serialize(value)?, flags)
+    }
+
+    /// Returns a buffer which can be used to write a value into the item at the
+    /// given key and with the given length. The buffer must be completely
+    /// filled by the caller. The cursor will be positioned at the new data item.
+    pub fn reserve(&mut self, key: &[u8], len: usize, flags: WriteFlags) -> Result<&'txn mut [u8]> {
+        let key_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: key.len(),
+            iov_base: key.as_ptr() as *mut c_void,
+        };
+        let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: len,
+            iov_base: ptr::null_mut::<c_void>(),
+        };
+        unsafe {
+            txn_execute(&*self.txn, |txn| {
+                check_item_size(txn, ffi::mdbx_cursor_dbi(self.cursor), key.len(), len)?;
+                mdbx_result(ffi::mdbx_cursor_put(
+                    self.cursor,
+                    &key_val,
+                    &mut data_val,
+                    flags.bits() | ffi::MDBX_RESERVE,
+                ))
+            })?;
+            Ok(slice::from_raw_parts_mut(
+                data_val.iov_base as *mut u8,
+                data_val.iov_len,
+            ))
+        }
+    }
+
+    /// Deletes the current key/data pair.
+    ///
+    /// ### Flags
+    ///
+    /// [WriteFlags::NO_DUP_DATA] may be used to delete all data items for the
+    /// current key, if the database was opened with [DatabaseFlags::DUP_SORT].
+    pub fn del(&mut self, flags: WriteFlags) -> Result<()> {
+        mdbx_result(unsafe {
+            txn_execute(&*self.txn, |_| {
+                ffi::mdbx_cursor_del(self.cursor, flags.bits())
+            })
+        })
+        .map_err(|e| self.op_error(e, Operation::Del, None))?;
+
+        Ok(())
+    }
+
+    /// Reads the value stored at `key`, passes it to `f` and stores the result.
+    ///
+    /// If `f` returns [None], an existing item is deleted. The write-back of an existing item
+    /// reuses the cursor position via [WriteFlags::CURRENT] instead of looking the key up again.
+    /// For databases with duplicate data items ([DatabaseFlags::DUP_SORT]), only the first
+    /// data item of the key is passed to `f` and replaced. The transaction is poisoned if `f`
+    /// panics, see [Transaction::is_poisoned()].
+    pub fn update_with<F>(&mut self, key: &[u8], f: F) -> Result<()>
+    where
+        F: FnOnce(Option<&[u8]>) -> Option<Vec<u8>>,
+    {
+        let found = self.set::<Cow<'txn, [u8]>>(key)?;
+        let exists = found.is_some();
+        let data = {
+            let _guard = PoisonGuard(&self.txn);
+            f(found.as_deref())
+        };
+        match (data, exists) {
+            (Some(data), true) => self.put(key, &data, WriteFlags::CURRENT),
+            (Some(data), false) => self.put(key, &data, WriteFlags::empty()),
+            (None, true) => self.del(WriteFlags::empty()),
+            (None, false) => Ok(()),
+        }
+    }
+
+    /// Deletes all items with keys inside the given range, returning the number of deleted items.
+    ///
+    /// Keys are compared using the comparator of the database. For databases with duplicate data
+    /// items ([DatabaseFlags::DUP_SORT]), every deleted duplicate is counted.
+    pub fn delete_range<R>(&mut self, range: R) -> Result<usize>
+    where
+        R: RangeBounds<[u8]>,
+    {
+        let mut item = self.seek_range_start::<()>(range.start_bound())?;
+
+        let mut deleted = 0;
+        while let Some((key, ())) = item {
+            if !self.before_range_end(&key, range.end_bound()) {
+                break;
+            }
+
+            self.del(WriteFlags::empty())?;
+            deleted += 1;
+            item = self.next()?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Deletes all items for which `f` returns `false`, keeping the rest.
+    ///
+    /// See [Cursor::retain_range()] for restricting the scan to a range of keys.
+    pub fn retain<F>(&mut self, f: F) -> Result<RetainStats>
+    where
+        F: FnMut(&[u8], &[u8]) -> bool,
+    {
+        self.retain_range::<RangeFull, F>(.., f)
+    }
+
+    /// Deletes all items with keys inside the given range for which `f` returns `false`.
+    ///
+    /// Keys are compared using the comparator of the database. For databases with duplicate data
+    /// items ([DatabaseFlags::DUP_SORT]), `f` is called once for every duplicate. The transaction
+    /// is poisoned if `f` panics, see [Transaction::is_poisoned()].
+    pub fn retain_range<R, F>(&mut self, range: R, mut f: F) -> Result<RetainStats>
+    where
+        R: RangeBounds<[u8]>,
+        F: FnMut(&[u8], &[u8]) -> bool,
+    {
+        let mut item = self.seek_range_start::<Cow<'txn, [u8]>>(range.start_bound())?;
+
+        let mut stats = RetainStats::default();
+        while let Some((key, data)) = item {
+            if !self.before_range_end(&key, range.end_bound()) {
+                break;
+            }
+
+            stats.scanned += 1;
+            let retain = {
+                let _guard = PoisonGuard(&self.txn);
+                f(&key, &data)
+            };
+            if retain {
+                stats.retained += 1;
+            } else {
+                self.del(WriteFlags::empty())?;
+                stats.deleted += 1;
+            }
+            item = self.next()?;
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Duplicate-item operations kept on [Cursor] for existing callers. They do not check that the
+/// database was opened with [DatabaseFlags::DUP_SORT]; use a [DupCursor] instead.
+impl<'txn, K> Cursor<'txn, K>
+where
+    K: TransactionKind,
+{
+    #[deprecated(note = "use DupCursor::first_dup(), see Transaction::dup_cursor()")]
+    pub fn first_dup<Value>(&mut self) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        self.get_value(None, None, MDBX_FIRST_DUP)
+    }
+
+    #[deprecated(note = "use DupCursor::get_both(), see Transaction::dup_cursor()")]
+    pub fn get_both<Value>(&mut self, k: &[u8], v: &[u8]) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        self.get_value(Some(k), Some(v), MDBX_GET_BOTH)
+    }
+
+    #[deprecated(note = "use DupCursor::get_both_range(), see Transaction::dup_cursor()")]
+    pub fn get_both_range<Value>(&mut self, k: &[u8], v: &[u8]) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        self.get_value(Some(k), Some(v), MDBX_GET_BOTH_RANGE)
+    }
+
+    #[deprecated(note = "use DupCursor::get_multiple(), see Transaction::dup_cursor()")]
+    pub fn get_multiple<Value>(&mut self) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        self.get_value(None, None, MDBX_GET_MULTIPLE)
+    }
+
+    #[deprecated(note = "use DupCursor::last_dup(), see Transaction::dup_cursor()")]
+    pub fn last_dup<Value>(&mut self) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        self.get_value(None, None, MDBX_LAST_DUP)
+    }
+
+    #[deprecated(note = "use DupCursor::next_dup(), see Transaction::dup_cursor()")]
     pub fn next_dup<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
         Key: TableObject<'txn>,
@@ -230,7 +902,7 @@ where
         self.get_full(None, None, MDBX_NEXT_DUP)
     }
 
-    /// [DatabaseFlags::DUP_FIXED]-only: Return up to a page of duplicate data items from next cursor position. Move cursor to prepare for MDBX_NEXT_MULTIPLE.
+    #[deprecated(note = "use DupCursor::next_multiple(), see Transaction::dup_cursor()")]
     pub fn next_multiple<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
         Key: TableObject<'txn>,
@@ -239,138 +911,218 @@ where
         self.get_full(None, None, MDBX_NEXT_MULTIPLE)
     }
 
-    /// Position at first data item of next key.
-    pub fn next_nodup<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
+    #[deprecated(note = "use DupCursor::prev_dup(), see Transaction::dup_cursor()")]
+    pub fn prev_dup<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(None, None, MDBX_NEXT_NODUP)
+        self.get_full(None, None, MDBX_PREV_DUP)
     }
 
-    /// Position at previous data item.
-    pub fn prev<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
+    #[deprecated(note = "use DupCursor::prev_multiple(), see Transaction::dup_cursor()")]
+    pub fn prev_multiple<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(None, None, MDBX_PREV)
+        self.get_full(None, None, MDBX_PREV_MULTIPLE)
     }
 
-    /// [DatabaseFlags::DUP_SORT]-only: Position at previous data item of current key.
-    pub fn prev_dup<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
+    #[deprecated(note = "use DupCursor::iter_dup(), see Transaction::dup_cursor()")]
+    pub fn iter_dup<Key, Value>(&mut self) -> IterDup<'txn, '_, K, Key, Value>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(None, None, MDBX_PREV_DUP)
+        IterDup::new(self, ffi::MDBX_NEXT as u32)
     }
 
-    /// Position at last data item of previous key.
-    pub fn prev_nodup<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
+    #[deprecated(note = "use DupCursor::iter_dup_start(), see Transaction::dup_cursor()")]
+    pub fn iter_dup_start<Key, Value>(&mut self) -> IterDup<'txn, '_, K, Key, Value>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(None, None, MDBX_PREV_NODUP)
+        IterDup::new(self, ffi::MDBX_FIRST as u32)
     }
 
-    /// Position at specified key.
-    pub fn set<Value>(&mut self, key: &[u8]) -> Result<Option<Value>>
+    #[deprecated(note = "use DupCursor::iter_dup_from(), see Transaction::dup_cursor()")]
+    pub fn iter_dup_from<Key, Value>(&mut self, key: &[u8]) -> IterDup<'txn, '_, K, Key, Value>
     where
+        Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_value(Some(key), None, MDBX_SET)
+        self.dup_iter_from(key)
     }
 
-    /// Position at specified key, return both key and data.
-    pub fn set_key<Key, Value>(&mut self, key: &[u8]) -> Result<Option<(Key, Value)>>
+    #[deprecated(note = "use DupCursor::iter_dup_of(), see Transaction::dup_cursor()")]
+    pub fn iter_dup_of<Key, Value>(&mut self, key: &[u8]) -> Iter<'txn, '_, K, Key, Value>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(Some(key), None, MDBX_SET_KEY)
+        self.dup_iter_of(key)
     }
 
-    /// Position at first key greater than or equal to specified key.
-    pub fn set_range<Key, Value>(&mut self, key: &[u8]) -> Result<Option<(Key, Value)>>
+    fn dup_iter_from<Key, Value>(&mut self, key: &[u8]) -> IterDup<'txn, '_, K, Key, Value>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(Some(key), None, MDBX_SET_RANGE)
+        let res: Result<Option<((), ())>> = self.set_range(key);
+        if let Err(error) = res {
+            return IterDup::Err(Some(error));
+        };
+        IterDup::new(self, ffi::MDBX_GET_CURRENT as u32)
     }
 
-    /// [DatabaseFlags::DUP_FIXED]-only: Position at previous page and return up to a page of duplicate data items.
-    pub fn prev_multiple<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
+    fn dup_iter_of<Key, Value>(&mut self, key: &[u8]) -> Iter<'txn, '_, K, Key, Value>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        self.get_full(None, None, MDBX_PREV_MULTIPLE)
+        let res: Result<Option<()>> = self.set(key);
+        match res {
+            Ok(Some(_)) => (),
+            Ok(None) => {
+                let res: Result<Option<((), ())>> = self.last();
+                if let Err(error) = res {
+                    return Iter::Err(Some(error));
+                }
+                return Iter::new(self, ffi::MDBX_NEXT, ffi::MDBX_NEXT);
+            }
+            Err(error) => return Iter::Err(Some(error)),
+        };
+        Iter::new(self, ffi::MDBX_GET_CURRENT, ffi::MDBX_NEXT_DUP)
     }
+}
 
-    /// Position at first key-value pair greater than or equal to specified, return both key and data, and the return code depends on a exact match.
-    ///
-    /// For non DupSort-ed collections this works the same as [Self::set_range()], but returns [false] if key found exactly and [true] if greater key was found.
-    ///
-    /// For DupSort-ed a data value is taken into account for duplicates, i.e. for a pairs/tuples of a key and an each data value of duplicates.
-    /// Returns [false] if key-value pair found exactly and [true] if the next pair was returned.
-    pub fn set_lowerbound<Key, Value>(&mut self, key: &[u8]) -> Result<Option<(bool, Key, Value)>>
+/// A cursor over a database opened with [DatabaseFlags::DUP_SORT].
+///
+/// Operations on duplicate data items belong on this type; the equivalents on [Cursor] are
+/// deprecated. All other [Cursor] operations are reachable through [Deref].
+#[derive(Clone, Debug)]
+pub struct DupCursor<'txn, K>
+where
+    K: TransactionKind,
+{
+    cursor: Cursor<'txn, K>,
+}
+
+impl<'txn, K> DupCursor<'txn, K>
+where
+    K: TransactionKind,
+{
+    /// Wraps the cursor, failing with [Error::Incompatible] if its database was not opened with
+    /// [DatabaseFlags::DUP_SORT].
+    pub(crate) fn new(cursor: Cursor<'txn, K>) -> Result<Self> {
+        let mut flags: c_uint = 0;
+        let mut state: c_uint = 0;
+        unsafe {
+            mdbx_result(txn_execute(&*cursor.txn, |txn| {
+                ffi::mdbx_dbi_flags_ex(
+                    txn,
+                    ffi::mdbx_cursor_dbi(cursor.cursor),
+                    &mut flags,
+                    &mut state,
+                )
+            }))?;
+        }
+        if !DatabaseFlags::from_bits_truncate(flags).contains(DatabaseFlags::DUP_SORT) {
+            return Err(Error::Incompatible);
+        }
+
+        Ok(Self { cursor })
+    }
+
+    /// Creates a new cursor positioned at the same item as this one.
+    pub fn try_clone(&self) -> Result<Self> {
+        Ok(Self {
+            cursor: self.cursor.try_clone()?,
+        })
+    }
+
+    /// Returns the underlying [Cursor].
+    pub fn into_inner(self) -> Cursor<'txn, K> {
+        self.cursor
+    }
+
+    /// [DatabaseFlags::DUP_SORT]-only: Position at first data item of current key.
+    pub fn first_dup<Value>(&mut self) -> Result<Option<Value>>
     where
-        Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        let (k, v, found) = mdbx_try_optional!(self.get(Some(key), None, MDBX_SET_LOWERBOUND));
+        self.cursor.get_value(None, None, MDBX_FIRST_DUP)
+    }
 
-        Ok(Some((found, k.unwrap(), v)))
+    /// [DatabaseFlags::DUP_SORT]-only: Position at key/data pair.
+    pub fn get_both<Value>(&mut self, k: &[u8], v: &[u8]) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        self.cursor.get_value(Some(k), Some(v), MDBX_GET_BOTH)
+    }
+
+    /// [DatabaseFlags::DUP_SORT]-only: Position at given key and at first data greater than or equal to specified data.
+    pub fn get_both_range<Value>(&mut self, k: &[u8], v: &[u8]) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        self.cursor.get_value(Some(k), Some(v), MDBX_GET_BOTH_RANGE)
+    }
+
+    /// DupFixed-only: Return up to a page of duplicate data items from current cursor position.
+    /// Move cursor to prepare for [Self::next_multiple()].
+    pub fn get_multiple<Value>(&mut self) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        self.cursor.get_value(None, None, MDBX_GET_MULTIPLE)
+    }
+
+    /// DupSort-only: Position at last data item of current key.
+    pub fn last_dup<Value>(&mut self) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        self.cursor.get_value(None, None, MDBX_LAST_DUP)
+    }
+
+    /// [DatabaseFlags::DUP_SORT]-only: Position at next data item of current key.
+    pub fn next_dup<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        self.cursor.get_full(None, None, MDBX_NEXT_DUP)
     }
 
-    /// Iterate over database items. The iterator will begin with item next
-    /// after the cursor, and continue until the end of the database. For new
-    /// cursors, the iterator will begin with the first item in the database.
-    ///
-    /// For databases with duplicate data items ([DatabaseFlags::DUP_SORT]), the
-    /// duplicate data items of each key will be returned before moving on to
-    /// the next key.
-    pub fn iter<Key, Value>(&mut self) -> Iter<'txn, '_, K, Key, Value>
+    /// [DatabaseFlags::DUP_FIXED]-only: Return up to a page of duplicate data items from next cursor position. Move cursor to prepare for MDBX_NEXT_MULTIPLE.
+    pub fn next_multiple<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
-        Self: Sized,
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        Iter::new(self, ffi::MDBX_NEXT, ffi::MDBX_NEXT)
+        self.cursor.get_full(None, None, MDBX_NEXT_MULTIPLE)
     }
 
-    /// Iterate over database items starting from the beginning of the database.
-    ///
-    /// For databases with duplicate data items ([DatabaseFlags::DUP_SORT]), the
-    /// duplicate data items of each key will be returned before moving on to
-    /// the next key.
-    pub fn iter_start<Key, Value>(&mut self) -> Iter<'txn, '_, K, Key, Value>
+    /// [DatabaseFlags::DUP_SORT]-only: Position at previous data item of current key.
+    pub fn prev_dup<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
-        Self: Sized,
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        Iter::new(self, ffi::MDBX_FIRST, ffi::MDBX_NEXT)
+        self.cursor.get_full(None, None, MDBX_PREV_DUP)
     }
 
-    /// Iterate over database items starting from the given key.
-    ///
-    /// For databases with duplicate data items ([DatabaseFlags::DUP_SORT]), the
-    /// duplicate data items of each key will be returned before moving on to
-    /// the next key.
-    pub fn iter_from<Key, Value>(&mut self, key: &[u8]) -> Iter<'txn, '_, K, Key, Value>
+    /// [DatabaseFlags::DUP_FIXED]-only: Position at previous page and return up to a page of duplicate data items.
+    pub fn prev_multiple<Key, Value>(&mut self) -> Result<Option<(Key, Value)>>
     where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        let res: Result<Option<((), ())>> = self.set_range(key);
-        if let Err(error) = res {
-            return Iter::Err(Some(error));
-        };
-        Iter::new(self, ffi::MDBX_GET_CURRENT, ffi::MDBX_NEXT)
+        self.cursor.get_full(None, None, MDBX_PREV_MULTIPLE)
     }
 
     /// Iterate over duplicate database items. The iterator will begin with the
@@ -381,7 +1133,7 @@ where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        IterDup::new(self, ffi::MDBX_NEXT as u32)
+        IterDup::new(&mut self.cursor, ffi::MDBX_NEXT as u32)
     }
 
     /// Iterate over duplicate database items starting from the beginning of the
@@ -391,7 +1143,7 @@ where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        IterDup::new(self, ffi::MDBX_FIRST as u32)
+        IterDup::new(&mut self.cursor, ffi::MDBX_FIRST as u32)
     }
 
     /// Iterate over duplicate items in the database starting from the given
@@ -401,11 +1153,7 @@ where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        let res: Result<Option<((), ())>> = self.set_range(key);
-        if let Err(error) = res {
-            return IterDup::Err(Some(error));
-        };
-        IterDup::new(self, ffi::MDBX_GET_CURRENT as u32)
+        self.cursor.dup_iter_from(key)
     }
 
     /// Iterate over the duplicates of the item in the database with the given key.
@@ -414,63 +1162,50 @@ where
         Key: TableObject<'txn>,
         Value: TableObject<'txn>,
     {
-        let res: Result<Option<()>> = self.set(key);
-        match res {
-            Ok(Some(_)) => (),
-            Ok(None) => {
-                let _: Result<Option<((), ())>> = self.last();
-                return Iter::new(self, ffi::MDBX_NEXT, ffi::MDBX_NEXT);
-            }
-            Err(error) => return Iter::Err(Some(error)),
-        };
-        Iter::new(self, ffi::MDBX_GET_CURRENT, ffi::MDBX_NEXT_DUP)
+        self.cursor.dup_iter_of(key)
     }
 }
 
-impl<'txn> Cursor<'txn, RW> {
-    /// Puts a key/data pair into the database. The cursor will be positioned at
-    /// the new data item, or on failure usually near it.
-    pub fn put(&mut self, key: &[u8], data: &[u8], flags: WriteFlags) -> Result<()> {
-        let key_val: ffi::MDBX_val = ffi::MDBX_val {
-            iov_len: key.len(),
-            iov_base: key.as_ptr() as *mut c_void,
-        };
-        let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
-            iov_len: data.len(),
-            iov_base: data.as_ptr() as *mut c_void,
-        };
-        mdbx_result(unsafe {
-            txn_execute(&*self.txn, |_| {
-                ffi::mdbx_cursor_put(self.cursor, &key_val, &mut data_val, flags.bits())
-            })
-        })?;
+impl<'txn, K> Deref for DupCursor<'txn, K>
+where
+    K: TransactionKind,
+{
+    type Target = Cursor<'txn, K>;
 
-        Ok(())
+    fn deref(&self) -> &Self::Target {
+        &self.cursor
     }
+}
 
-    /// Deletes the current key/data pair.
-    ///
-    /// ### Flags
-    ///
-    /// [WriteFlags::NO_DUP_DATA] may be used to delete all data items for the
-    /// current key, if the database was opened with [DatabaseFlags::DUP_SORT].
-    pub fn del(&mut self, flags: WriteFlags) -> Result<()> {
-        mdbx_result(unsafe {
-            txn_execute(&*self.txn, |_| {
-                ffi::mdbx_cursor_del(self.cursor, flags.bits())
-            })
-        })?;
-
-        Ok(())
+impl<'txn, K> DerefMut for DupCursor<'txn, K>
+where
+    K: TransactionKind,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.cursor
     }
 }
 
+/// Statistics returned by [Cursor::retain()] and [Cursor::retain_range()].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RetainStats {
+    /// Number of items visited.
+    pub scanned: usize,
+    /// Number of items kept.
+    pub retained: usize,
+    /// Number of items deleted.
+    pub deleted: usize,
+}
+
+/// # Panics
+///
+/// Panics if the cursor could not be copied. Use [Cursor::try_clone()] to handle the error instead.
 impl<'txn, K> Clone for Cursor<'txn, K>
 where
     K: TransactionKind,
 {
     fn clone(&self) -> Self {
-        txn_execute(&*self.txn, |_| Self::new_at_position(self).unwrap())
+        self.try_clone().unwrap()
     }
 }
 
@@ -522,6 +1257,26 @@ where
     }
 }
 
+/// Decodes a key/value pair read by the cursor, attaching the location of the entry to decode
+/// errors.
+unsafe fn decode_pair<'txn, K, Key, Value>(
+    txn: *const ffi::MDBX_txn,
+    cursor: *mut ffi::MDBX_cursor,
+    key: &ffi::MDBX_val,
+    data: &ffi::MDBX_val,
+) -> Result<(Key, Value)>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    let with_context =
+        |e: Error| e.with_decode_context(txn, ffi::mdbx_cursor_dbi(cursor), key, data);
+    let key = Key::decode_val::<K>(txn, key).map_err(with_context)?;
+    let data = Value::decode_val::<K>(txn, data).map_err(with_context)?;
+    Ok((key, data))
+}
+
 /// An iterator over the key/value pairs in an MDBX database.
 #[derive(Debug)]
 pub enum IntoIter<'txn, K, Key, Value>
@@ -537,11 +1292,9 @@ where
     /// need to check the result of Iter.next().
     Err(Option<Error>),
 
-    /// An iterator that returns an Item on calls to [Iter::next()].
-    /// The Item is a [Result], so this variant
-    /// might still return an error, if retrieval of the key/value pair
-    /// fails for some reason.
-    Ok {
+    /// An iterator yet to return its first item, which is read with a different operation than
+    /// the following ones. It becomes [IntoIter::Next] on the first call to [Iter::next()].
+    First {
         /// The MDBX cursor with which to iterate.
         cursor: Cursor<'txn, K>,
 
@@ -553,6 +1306,20 @@ where
 
         _marker: PhantomData<fn(&'txn (), K, Key, Value)>,
     },
+
+    /// An iterator that returns an Item on calls to [Iter::next()].
+    /// The Item is a [Result], so this variant
+    /// might still return an error, if retrieval of the key/value pair
+    /// fails for some reason.
+    Next {
+        /// The MDBX cursor with which to iterate.
+        cursor: Cursor<'txn, K>,
+
+        /// The operation to perform on every call to [Iter::next()].
+        op: ffi::MDBX_cursor_op,
+
+        _marker: PhantomData<fn(&'txn (), K, Key, Value)>,
+    },
 }
 
 impl<'txn, K, Key, Value> IntoIter<'txn, K, Key, Value>
@@ -563,11 +1330,19 @@ where
 {
     /// Creates a new iterator backed by the given cursor.
     fn new(cursor: Cursor<'txn, K>, op: ffi::MDBX_cursor_op, next_op: ffi::MDBX_cursor_op) -> Self {
-        IntoIter::Ok {
-            cursor,
-            op,
-            next_op,
-            _marker: PhantomData,
+        if op == next_op {
+            IntoIter::Next {
+                cursor,
+                op,
+                _marker: PhantomData,
+            }
+        } else {
+            IntoIter::First {
+                cursor,
+                op,
+                next_op,
+                _marker: PhantomData,
+            }
         }
     }
 }
@@ -582,46 +1357,47 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         match self {
-            Self::Ok {
-                cursor,
-                op,
-                next_op,
-                _marker,
-            } => {
-                let mut key = ffi::MDBX_val {
-                    iov_len: 0,
-                    iov_base: ptr::null_mut(),
+            Self::Next { cursor, op, .. } => cursor.read_item(*op),
+            Self::First { .. } => {
+                let Self::First {
+                    cursor,
+                    op,
+                    next_op,
+                    _marker,
+                } = mem::replace(self, Self::Err(None))
+                else {
+                    unreachable!()
                 };
-                let mut data = ffi::MDBX_val {
-                    iov_len: 0,
-                    iov_base: ptr::null_mut(),
+                let item = cursor.read_item(op);
+                *self = Self::Next {
+                    cursor,
+                    op: next_op,
+                    _marker,
                 };
-                let op = mem::replace(op, *next_op);
-                unsafe {
-                    txn_execute(&*cursor.txn, |txn| {
-                        match ffi::mdbx_cursor_get(cursor.cursor(), &mut key, &mut data, op) {
-                            ffi::MDBX_SUCCESS => {
-                                let key = match Key::decode_val::<K>(txn, &key) {
-                                    Ok(v) => v,
-                                    Err(e) => return Some(Err(e)),
-                                };
-                                let data = match Value::decode_val::<K>(txn, &data) {
-                                    Ok(v) => v,
-                                    Err(e) => return Some(Err(e)),
-                                };
-                                Some(Ok((key, data)))
-                            }
-                            // MDBX_ENODATA can occur when the cursor was previously seeked to a non-existent value,
-                            // e.g. iter_from with a key greater than all values in the database.
-                            ffi::MDBX_NOTFOUND | ffi::MDBX_ENODATA => None,
-                            error => Some(Err(Error::from_err_code(error))),
-                        }
-                    })
-                }
+                item
             }
             Self::Err(err) => err.take().map(Err),
         }
     }
+
+    /// Estimated from the position of the cursor on every call, which takes a few calls into
+    /// MDBX. The upper bound is the number of entries in the database, or of duplicates of the
+    /// key.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::First {
+                cursor,
+                op,
+                next_op,
+                ..
+            } => cursor.estimate_len(*op, *next_op),
+            Self::Next { cursor, op, .. } => cursor.estimate_len(*op, *op),
+            Self::Err(err) => {
+                let n = err.is_some() as usize;
+                (n, Some(n))
+            }
+        }
+    }
 }
 
 /// An iterator over the key/value pairs in an MDBX database.
@@ -639,11 +1415,9 @@ where
     /// need to check the result of Iter.next().
     Err(Option<Error>),
 
-    /// An iterator that returns an Item on calls to [Iter::next()].
-    /// The Item is a [Result], so this variant
-    /// might still return an error, if retrieval of the key/value pair
-    /// fails for some reason.
-    Ok {
+    /// An iterator yet to return its first item, which is read with a different operation than
+    /// the following ones. It becomes [Iter::Next] on the first call to [Iter::next()].
+    First {
         /// The MDBX cursor with which to iterate.
         cursor: &'cur mut Cursor<'txn, K>,
 
@@ -655,6 +1429,20 @@ where
 
         _marker: PhantomData<fn(&'txn (Key, Value))>,
     },
+
+    /// An iterator that returns an Item on calls to [Iter::next()].
+    /// The Item is a [Result], so this variant
+    /// might still return an error, if retrieval of the key/value pair
+    /// fails for some reason.
+    Next {
+        /// The MDBX cursor with which to iterate.
+        cursor: &'cur mut Cursor<'txn, K>,
+
+        /// The operation to perform on every call to [Iter::next()].
+        op: ffi::MDBX_cursor_op,
+
+        _marker: PhantomData<fn(&'txn (Key, Value))>,
+    },
 }
 
 impl<'txn, 'cur, K, Key, Value> Iter<'txn, 'cur, K, Key, Value>
@@ -669,11 +1457,19 @@ where
         op: ffi::MDBX_cursor_op,
         next_op: ffi::MDBX_cursor_op,
     ) -> Self {
-        Iter::Ok {
-            cursor,
-            op,
-            next_op,
-            _marker: PhantomData,
+        if op == next_op {
+            Iter::Next {
+                cursor,
+                op,
+                _marker: PhantomData,
+            }
+        } else {
+            Iter::First {
+                cursor,
+                op,
+                next_op,
+                _marker: PhantomData,
+            }
         }
     }
 }
@@ -688,44 +1484,184 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         match self {
-            Iter::Ok {
+            Iter::Next { cursor, op, .. } => cursor.read_item(*op),
+            Iter::First { .. } => {
+                let Iter::First {
+                    cursor,
+                    op,
+                    next_op,
+                    _marker,
+                } = mem::replace(self, Iter::Err(None))
+                else {
+                    unreachable!()
+                };
+                let item = cursor.read_item(op);
+                *self = Iter::Next {
+                    cursor,
+                    op: next_op,
+                    _marker,
+                };
+                item
+            }
+            Iter::Err(err) => err.take().map(Err),
+        }
+    }
+
+    /// Estimated from the position of the cursor on every call, which takes a few calls into
+    /// MDBX. The upper bound is the number of entries in the database, or of duplicates of the
+    /// key.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::First {
                 cursor,
                 op,
                 next_op,
                 ..
-            } => {
-                let mut key = ffi::MDBX_val {
-                    iov_len: 0,
-                    iov_base: ptr::null_mut(),
-                };
-                let mut data = ffi::MDBX_val {
-                    iov_len: 0,
-                    iov_base: ptr::null_mut(),
-                };
-                let op = mem::replace(op, *next_op);
-                unsafe {
-                    txn_execute(&*cursor.txn, |txn| {
-                        match ffi::mdbx_cursor_get(cursor.cursor(), &mut key, &mut data, op) {
-                            ffi::MDBX_SUCCESS => {
-                                let key = match Key::decode_val::<K>(txn, &key) {
-                                    Ok(v) => v,
-                                    Err(e) => return Some(Err(e)),
-                                };
-                                let data = match Value::decode_val::<K>(txn, &data) {
-                                    Ok(v) => v,
-                                    Err(e) => return Some(Err(e)),
-                                };
-                                Some(Ok((key, data)))
-                            }
-                            // MDBX_NODATA can occur when the cursor was previously seeked to a non-existent value,
-                            // e.g. iter_from with a key greater than all values in the database.
-                            ffi::MDBX_NOTFOUND | ffi::MDBX_ENODATA => None,
-                            error => Some(Err(Error::from_err_code(error))),
-                        }
-                    })
+            } => cursor.estimate_len(*op, *next_op),
+            Iter::Next { cursor, op, .. } => cursor.estimate_len(*op, *op),
+            Iter::Err(err) => {
+                let n = err.is_some() as usize;
+                (n, Some(n))
+            }
+        }
+    }
+}
+
+/// An iterator over the key/value pairs in an MDBX database, returned by
+/// [Cursor::iter_batched()].
+pub struct IterBatched<'txn, 'cur, K, Key, Value>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    cursor: &'cur mut Cursor<'txn, K>,
+    /// The operation to read the next batch with, [None] once the end or an error was reached.
+    op: Option<ffi::MDBX_cursor_op>,
+    /// Whether the database is read one item per call, as it does not support batches.
+    single: bool,
+    /// Keys and values read by the last call.
+    pairs: Vec<ffi::MDBX_val>,
+    items: VecDeque<Result<(Key, Value)>>,
+}
+
+impl<'txn, 'cur, K, Key, Value> IterBatched<'txn, 'cur, K, Key, Value>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    fn new(cursor: &'cur mut Cursor<'txn, K>, batch: usize) -> Self {
+        // MDBX needs room for at least two pairs.
+        let batch = batch.max(2);
+        Self {
+            cursor,
+            op: Some(MDBX_NEXT),
+            single: false,
+            pairs: vec![unsafe { slice_to_val(None) }; batch * 2],
+            items: VecDeque::with_capacity(batch),
+        }
+    }
+
+    /// Reads and decodes the next batch of items.
+    fn fill(&mut self) {
+        let Some(op) = self.op else {
+            return;
+        };
+        let cursor = self.cursor.cursor();
+        let (pairs, items) = (&mut self.pairs, &mut self.items);
+        let (next_op, single) = txn_execute(&*self.cursor.txn, |txn| unsafe {
+            let mut op = op;
+            let mut count = 0;
+            let mut rc = ffi::MDBX_INCOMPATIBLE;
+            if !self.single {
+                rc = ffi::mdbx_cursor_get_batch(
+                    cursor,
+                    &mut count,
+                    pairs.as_mut_ptr(),
+                    pairs.len(),
+                    op,
+                );
+            }
+            let single = rc == ffi::MDBX_INCOMPATIBLE;
+            if single {
+                rc = ffi::MDBX_SUCCESS;
+                while count < pairs.len() {
+                    let (key, data) = pairs[count..].split_at_mut(1);
+                    rc = ffi::mdbx_cursor_get(cursor, &mut key[0], &mut data[0], op);
+                    if rc != ffi::MDBX_SUCCESS {
+                        break;
+                    }
+                    op = MDBX_NEXT;
+                    count += 2;
+                }
+                if rc == ffi::MDBX_SUCCESS {
+                    rc = ffi::MDBX_RESULT_TRUE;
                 }
             }
-            Iter::Err(err) => err.take().map(Err),
+            for pair in pairs[..count].chunks_exact(2) {
+                items.push_back(decode_pair::<K, _, _>(txn, cursor, &pair[0], &pair[1]));
+            }
+            let next_op = match rc {
+                // The rest of the page was read, NEXT moves to the next page.
+                ffi::MDBX_SUCCESS => Some(MDBX_NEXT),
+                // The batch was full, the cursor is on the next item, or the last one read if
+                // reading one item per call.
+                ffi::MDBX_RESULT_TRUE if single => Some(MDBX_NEXT),
+                ffi::MDBX_RESULT_TRUE => Some(MDBX_GET_CURRENT),
+                // MDBX_ENODATA can occur when the cursor was previously seeked to a non-existent
+                // value, as in Iter.
+                ffi::MDBX_NOTFOUND | ffi::MDBX_ENODATA => None,
+                error => {
+                    // Report the error once instead of retrying the failed operation forever.
+                    items.push_back(Err(Error::from_err_code(error)));
+                    None
+                }
+            };
+            (next_op, single)
+        });
+        self.op = next_op;
+        self.single = single;
+    }
+}
+
+impl<'txn, 'cur, K, Key, Value> fmt::Debug for IterBatched<'txn, 'cur, K, Key, Value>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        f.debug_struct("IterBatched").finish()
+    }
+}
+
+impl<'txn, 'cur, K, Key, Value> Iterator for IterBatched<'txn, 'cur, K, Key, Value>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    type Item = Result<(Key, Value)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.items.is_empty() && self.op.is_some() {
+            self.fill();
+        }
+        self.items.pop_front()
+    }
+
+    /// Estimated from the position of the cursor on every call, which takes a few calls into
+    /// MDBX, and the items read ahead. The upper bound is the number of entries in the database.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let read = self.items.len();
+        match self.op {
+            Some(op) => {
+                let (lower, upper) = self.cursor.estimate_len(op, MDBX_NEXT);
+                let lower = lower + read;
+                (upper.map_or(lower, |upper| lower.min(upper)), upper)
+            }
+            None => (read, Some(read)),
         }
     }
 }
@@ -810,20 +1746,25 @@ where
                 };
                 let op = mem::replace(op, ffi::MDBX_NEXT_NODUP as u32);
 
-                txn_execute(&*cursor.txn, |_| {
-                    let err_code =
-                        unsafe { ffi::mdbx_cursor_get(cursor.cursor(), &mut key, &mut data, op) };
-
-                    if err_code == ffi::MDBX_SUCCESS {
-                        Some(IntoIter::new(
-                            Cursor::new_at_position(&**cursor).unwrap(),
-                            ffi::MDBX_GET_CURRENT,
-                            ffi::MDBX_NEXT_DUP,
-                        ))
-                    } else {
-                        None
+                let res = txn_execute(&*cursor.txn, |_| {
+                    match unsafe { ffi::mdbx_cursor_get(cursor.cursor(), &mut key, &mut data, op) }
+                    {
+                        ffi::MDBX_SUCCESS => Cursor::new_at_position(&**cursor).map(Some),
+                        ffi::MDBX_NOTFOUND | ffi::MDBX_ENODATA => Ok(None),
+                        error => Err(Error::from_err_code(error)),
                     }
-                })
+                });
+
+                match res {
+                    Ok(cursor) => cursor.map(|cursor| {
+                        IntoIter::new(cursor, ffi::MDBX_GET_CURRENT, ffi::MDBX_NEXT_DUP)
+                    }),
+                    Err(error) => {
+                        // Report the error once instead of retrying the failed operation forever.
+                        *self = IterDup::Err(None);
+                        Some(IntoIter::Err(Some(error)))
+                    }
+                }
             }
             IterDup::Err(err) => err.take().map(|e| IntoIter::Err(Some(e))),
         }
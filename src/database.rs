@@ -1,11 +1,12 @@
 use crate::{
-    environment::EnvironmentKind,
-    error::{mdbx_result, Result},
+    environment::{Environment, EnvironmentKind},
+    error::{mdbx_result, Error, Result},
+    flags::{DatabaseFlags, DatabaseState},
     transaction::{txn_execute, TransactionKind},
-    Transaction,
+    Stat, Transaction,
 };
 use libc::c_uint;
-use std::{ffi::CString, marker::PhantomData, ptr};
+use std::{ffi::CString, marker::PhantomData, ptr, sync::Arc};
 
 /// A handle to an individual database in an environment.
 ///
@@ -13,6 +14,9 @@ use std::{ffi::CString, marker::PhantomData, ptr};
 #[derive(Debug)]
 pub struct Database<'txn> {
     dbi: ffi::MDBX_dbi,
+    /// Token of the handle in the environment, see [Transaction::drop_db()], holding the name of
+    /// the database.
+    handle: Option<Arc<Option<String>>>,
     _marker: PhantomData<&'txn ()>,
 }
 
@@ -26,6 +30,34 @@ impl<'txn> Database<'txn> {
         name: Option<&str>,
         flags: c_uint,
     ) -> Result<Self> {
+        Self::new_ex(txn, name, flags, None, None)
+    }
+
+    /// Opens a new database handle using custom comparators for its keys and duplicates, or
+    /// the default ones for [None].
+    pub(crate) fn new_ex<'env, K: TransactionKind, E: EnvironmentKind>(
+        txn: &'txn Transaction<'env, K, E>,
+        name: Option<&str>,
+        flags: c_uint,
+        keycmp: ffi::MDBX_cmp_func,
+        datacmp: ffi::MDBX_cmp_func,
+    ) -> Result<Self> {
+        let default_cmp = keycmp.is_none() && datacmp.is_none();
+        if let (Some(name), 0, true) = (name, flags, default_cmp) {
+            if let Some(dbi) = txn.env().cached_dbi(name) {
+                // The handle may have been closed since, e.g. by dropping the database through
+                // the raw API, in which case it is opened again below.
+                let mut flags: c_uint = 0;
+                let mut state: c_uint = 0;
+                match txn_execute(&*txn.txn_mutex(), |txn| unsafe {
+                    ffi::mdbx_dbi_flags_ex(txn, dbi, &mut flags, &mut state)
+                }) {
+                    ffi::MDBX_SUCCESS => return Ok(Self::tracked(txn.env(), dbi)),
+                    ffi::MDBX_BAD_DBI => txn.env().forget_dbi(dbi),
+                    err_code => return Err(Error::from_err_code(err_code)),
+                }
+            }
+        }
         let c_name = name.map(|n| CString::new(n).unwrap());
         let name_ptr = if let Some(c_name) = &c_name {
             c_name.as_ptr()
@@ -34,25 +66,87 @@ impl<'txn> Database<'txn> {
         };
         let mut dbi: ffi::MDBX_dbi = 0;
         mdbx_result(txn_execute(&*txn.txn_mutex(), |txn| unsafe {
-            ffi::mdbx_dbi_open(txn, name_ptr, flags, &mut dbi)
+            ffi::mdbx_dbi_open_ex(txn, name_ptr, flags, &mut dbi, keycmp, datacmp)
         }))?;
-        Ok(Self::new_from_ptr(dbi))
+        if let Some(name) = name {
+            txn.env().record_dbi_name(dbi, name);
+        }
+        if let (Some(name), 0, true) = (name, flags, default_cmp) {
+            // Handles of databases created by a write transaction are closed if it aborts.
+            if !txn
+                .db_state(&Self::new_from_ptr(dbi))?
+                .contains(DatabaseState::CREAT)
+            {
+                txn.env().cache_dbi(name, dbi);
+            }
+        }
+        Ok(Self::tracked(txn.env(), dbi))
     }
 
     pub(crate) fn new_from_ptr(dbi: ffi::MDBX_dbi) -> Self {
         Self {
             dbi,
+            handle: None,
             _marker: PhantomData,
         }
     }
 
-    pub(crate) fn freelist_db() -> Self {
-        Database {
-            dbi: 0,
+    /// Creates an instance counted among the users of the handle in `env`.
+    pub(crate) fn tracked<E: EnvironmentKind>(env: &Environment<E>, dbi: ffi::MDBX_dbi) -> Self {
+        Self {
+            dbi,
+            handle: Some(env.dbi_handle(dbi)),
             _marker: PhantomData,
         }
     }
 
+    pub(crate) fn freelist_db() -> Self {
+        Self::new_from_ptr(0)
+    }
+
+    pub(crate) fn handle(&self) -> Option<Arc<Option<String>>> {
+        self.handle.clone()
+    }
+
+    pub(crate) fn is_tracked(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    /// Returns the name of the database, or [None] for the main database.
+    pub fn name(&self) -> Option<&str> {
+        self.handle.as_deref()?.as_deref()
+    }
+
+    /// Gets the option flags of the database, see [Transaction::db_flags()].
+    pub fn flags<'a, K, E>(&self, txn: &'a Transaction<'_, K, E>) -> Result<DatabaseFlags>
+    where
+        'txn: 'a,
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        txn.db_flags(self)
+    }
+
+    /// Gets the state of the handle in a transaction, see [Transaction::db_state()].
+    pub fn state<'a, K, E>(&self, txn: &'a Transaction<'_, K, E>) -> Result<DatabaseState>
+    where
+        'txn: 'a,
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        txn.db_state(self)
+    }
+
+    /// Retrieves the statistics of the database, see [Transaction::db_stat()].
+    pub fn stat<'a, K, E>(&self, txn: &'a Transaction<'_, K, E>) -> Result<Stat>
+    where
+        'txn: 'a,
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        txn.db_stat(self)
+    }
+
     /// Returns the underlying MDBX database handle.
     ///
     /// The caller **must** ensure that the handle is not used after the lifetime of the
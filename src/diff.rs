@@ -0,0 +1,130 @@
+//! Differences between two snapshots of a database.
+//!
+//! [snapshot_diff()] compares a database as seen by two transactions, e.g. to find what changed
+//! between two points in time for replication, or to assert the effect of some code in tests.
+
+use crate::{Cursor, DatabaseFlags, EnvironmentKind, Result, Transaction, TransactionKind};
+use std::{borrow::Cow, cmp::Ordering, mem};
+
+type Item<'txn> = (Cow<'txn, [u8]>, Cow<'txn, [u8]>);
+
+/// A difference found by [snapshot_diff()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffEntry<'txn> {
+    /// An item only present in the second snapshot.
+    Added {
+        key: Cow<'txn, [u8]>,
+        value: Cow<'txn, [u8]>,
+    },
+    /// An item only present in the first snapshot.
+    Removed {
+        key: Cow<'txn, [u8]>,
+        value: Cow<'txn, [u8]>,
+    },
+    /// A key present in both snapshots with different values. Never returned for
+    /// [DatabaseFlags::DUP_SORT] databases, where a changed value is a removed and an added item.
+    Changed {
+        key: Cow<'txn, [u8]>,
+        old: Cow<'txn, [u8]>,
+        new: Cow<'txn, [u8]>,
+    },
+}
+
+/// Iterator over the differences between two snapshots, returned by [snapshot_diff()].
+pub struct SnapshotDiff<'txn, K1, K2>
+where
+    K1: TransactionKind,
+    K2: TransactionKind,
+{
+    a: Cursor<'txn, K1>,
+    b: Cursor<'txn, K2>,
+    item_a: Option<Item<'txn>>,
+    item_b: Option<Item<'txn>>,
+    dup_sort: bool,
+    started: bool,
+}
+
+/// Compares the database `name`, [None] for the main database, in the snapshots of `a` and `b`,
+/// yielding the items added, removed or changed from `a` to `b` in key order.
+///
+/// The transactions may belong to the same environment or to two environments, in which case the
+/// database must have the same flags in both. Both databases are read in a single pass, with
+/// the key order of the database in `a`.
+pub fn snapshot_diff<'txn, K1, E1, K2, E2>(
+    a: &'txn Transaction<'_, K1, E1>,
+    b: &'txn Transaction<'_, K2, E2>,
+    name: Option<&str>,
+) -> Result<SnapshotDiff<'txn, K1, K2>>
+where
+    K1: TransactionKind,
+    E1: EnvironmentKind,
+    K2: TransactionKind,
+    E2: EnvironmentKind,
+{
+    let db_a = a.open_db(name)?;
+    let db_b = b.open_db(name)?;
+    Ok(SnapshotDiff {
+        a: a.cursor(&db_a)?,
+        b: b.cursor(&db_b)?,
+        item_a: None,
+        item_b: None,
+        dup_sort: a.db_flags(&db_a)?.contains(DatabaseFlags::DUP_SORT),
+        started: false,
+    })
+}
+
+impl<'txn, K1, K2> SnapshotDiff<'txn, K1, K2>
+where
+    K1: TransactionKind,
+    K2: TransactionKind,
+{
+    fn advance(&mut self) -> Result<Option<DiffEntry<'txn>>> {
+        if !self.started {
+            self.item_a = self.a.first()?;
+            self.item_b = self.b.first()?;
+            self.started = true;
+        }
+        loop {
+            let order = match (&self.item_a, &self.item_b) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((key_a, value_a)), Some((key_b, value_b))) => {
+                    match self.a.cmp_keys(key_a, key_b) {
+                        Ordering::Equal if self.dup_sort => self.a.cmp_values(value_a, value_b),
+                        order => order,
+                    }
+                }
+            };
+            match order {
+                Ordering::Less => {
+                    let (key, value) = mem::replace(&mut self.item_a, self.a.next()?).unwrap();
+                    return Ok(Some(DiffEntry::Removed { key, value }));
+                }
+                Ordering::Greater => {
+                    let (key, value) = mem::replace(&mut self.item_b, self.b.next()?).unwrap();
+                    return Ok(Some(DiffEntry::Added { key, value }));
+                }
+                Ordering::Equal => {
+                    let (key, old) = mem::replace(&mut self.item_a, self.a.next()?).unwrap();
+                    let (_, new) = mem::replace(&mut self.item_b, self.b.next()?).unwrap();
+                    if old != new {
+                        return Ok(Some(DiffEntry::Changed { key, old, new }));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'txn, K1, K2> Iterator for SnapshotDiff<'txn, K1, K2>
+where
+    K1: TransactionKind,
+    K2: TransactionKind,
+{
+    type Item = Result<DiffEntry<'txn>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.advance().transpose()
+    }
+}
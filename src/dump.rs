@@ -0,0 +1,333 @@
+//! The text format of the `mdbx_dump` and `mdbx_load` tools, shared with LMDB and Berkeley DB.
+
+use crate::{
+    database::Database,
+    error::{io_error, mdbx_result},
+    transaction::txn_execute,
+    DatabaseFlags, Environment, EnvironmentKind, Error, Result, Transaction, TransactionKind,
+    WriteFlags, RW,
+};
+use std::{
+    borrow::Cow,
+    io::{self, BufRead, Write},
+};
+
+/// Encoding of keys and values in a dump.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// Every byte as two hex digits, `mdbx_dump` by default.
+    #[default]
+    Bytevalue,
+    /// Printable characters as they are, other bytes escaped as `\xx`, `mdbx_dump -p`.
+    Print,
+}
+
+/// Flags which are written to the header of a dump, in the order of `mdbx_dump`.
+const DUMP_FLAGS: [(DatabaseFlags, &str); 6] = [
+    (DatabaseFlags::REVERSE_KEY, "reversekey"),
+    (DatabaseFlags::DUP_SORT, "dupsort"),
+    (DatabaseFlags::INTEGER_KEY, "integerkey"),
+    (DatabaseFlags::DUP_FIXED, "dupfixed"),
+    (DatabaseFlags::INTEGER_DUP, "integerdup"),
+    (DatabaseFlags::REVERSE_DUP, "reversedup"),
+];
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+fn format_error(line: usize, message: impl std::fmt::Display) -> Error {
+    Error::DecodeError(format!("dump line {line}: {message}").into())
+}
+
+fn write_value(out: &mut impl Write, value: &[u8], format: DumpFormat) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(value.len() * 2 + 2);
+    buf.push(b' ');
+    for &b in value {
+        match format {
+            DumpFormat::Print if (0x20..0x7f).contains(&b) && b != b'\\' => buf.push(b),
+            DumpFormat::Print => buf.extend([b'\\', HEX[b as usize >> 4], HEX[b as usize & 0xf]]),
+            DumpFormat::Bytevalue => buf.extend([HEX[b as usize >> 4], HEX[b as usize & 0xf]]),
+        }
+    }
+    buf.push(b'\n');
+    out.write_all(&buf)
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+fn read_value(line: &[u8], format: DumpFormat) -> Option<Vec<u8>> {
+    let line = line.strip_prefix(b" ")?;
+    let mut value = Vec::with_capacity(line.len());
+    let mut bytes = line.iter().copied();
+    while let Some(c) = bytes.next() {
+        let (hi, lo) = match format {
+            DumpFormat::Print if c != b'\\' => {
+                value.push(c);
+                continue;
+            }
+            DumpFormat::Print => (bytes.next()?, bytes.next()?),
+            DumpFormat::Bytevalue => (c, bytes.next()?),
+        };
+        value.push(hex_digit(hi)? << 4 | hex_digit(lo)?);
+    }
+    Some(value)
+}
+
+impl<'env, K, E> Transaction<'env, K, E>
+where
+    K: TransactionKind,
+    E: EnvironmentKind,
+{
+    /// Writes the snapshot of this transaction in the text format of `mdbx_dump -a`.
+    ///
+    /// Every named database is dumped, or the main database if there are none, like
+    /// `mdbx_dump` does.
+    pub fn dump_to(&self, mut out: impl Write, format: DumpFormat) -> Result<()> {
+        let main = self.open_db(None)?;
+        let mut names = Vec::new();
+        for item in self.cursor(&main)?.iter_start::<Vec<u8>, ()>() {
+            let (name, ()) = item?;
+            let name = match String::from_utf8(name) {
+                Ok(name) if !name.contains('\0') => name,
+                _ => continue,
+            };
+            match self.open_db(Some(&name)) {
+                Ok(db) => names.push((name, db)),
+                Err(Error::Incompatible | Error::NotFound) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        if names.is_empty() {
+            return self.dump_db(&main, None, true, &mut out, format);
+        }
+        for (i, (name, db)) in names.iter().enumerate() {
+            self.dump_db(db, Some(name), i == 0, &mut out, format)?;
+        }
+        Ok(())
+    }
+
+    fn dump_db(
+        &self,
+        db: &Database<'_>,
+        name: Option<&str>,
+        global: bool,
+        out: &mut impl Write,
+        format: DumpFormat,
+    ) -> Result<()> {
+        let flags = self.db_flags(db)?;
+        let stat = self.db_stat(db)?;
+        let mut sequence = 0;
+        mdbx_result(txn_execute(&self.txn_mutex(), |txn| unsafe {
+            ffi::mdbx_dbi_sequence(txn, db.dbi(), &mut sequence, 0)
+        }))?;
+
+        let mut header = String::from("VERSION=3\n");
+        if global {
+            let info = unsafe { self.env().info_for(self.txn())? };
+            let geometry = info.geometry();
+            if geometry.max() != geometry.min() {
+                header += &format!(
+                    "geometry=l{},c{},u{},s{},g{}\n",
+                    geometry.min(),
+                    geometry.current(),
+                    geometry.max(),
+                    geometry.shrink(),
+                    geometry.grow()
+                );
+            }
+            header += &format!("mapsize={}\n", geometry.max());
+            header += &format!("maxreaders={}\n", info.max_readers());
+        }
+        header += match format {
+            DumpFormat::Bytevalue => "format=bytevalue\n",
+            DumpFormat::Print => "format=print\n",
+        };
+        if let Some(name) = name {
+            header += &format!("database={name}\n");
+        }
+        header += "type=btree\n";
+        header += &format!("db_pagesize={}\n", stat.page_size());
+        let duplicates = flags.intersects(
+            DatabaseFlags::DUP_SORT
+                | DatabaseFlags::DUP_FIXED
+                | DatabaseFlags::INTEGER_DUP
+                | DatabaseFlags::REVERSE_DUP,
+        );
+        header += &format!("duplicates={}\n", duplicates as u8);
+        for (flag, name) in DUMP_FLAGS {
+            if flags.contains(flag) {
+                header += &format!("{name}=1\n");
+            }
+        }
+        if sequence != 0 {
+            header += &format!("sequence={sequence}\n");
+        }
+        header += "HEADER=END\n";
+        out.write_all(header.as_bytes()).map_err(io_error)?;
+
+        for item in self
+            .cursor(db)?
+            .iter_start::<Cow<'_, [u8]>, Cow<'_, [u8]>>()
+        {
+            let (key, value) = item?;
+            write_value(out, &key, format).map_err(io_error)?;
+            write_value(out, &value, format).map_err(io_error)?;
+        }
+        out.write_all(b"DATA=END\n").map_err(io_error)
+    }
+}
+
+impl<'env, E> Transaction<'env, RW, E>
+where
+    E: EnvironmentKind,
+{
+    /// Loads a dump in the text format of `mdbx_dump`, creating the databases as needed.
+    ///
+    /// Settings of the environment in the dump, such as `mapsize` or `geometry`, are ignored as
+    /// the environment is already open. Returns the number of items loaded.
+    pub fn load_from(&self, input: impl BufRead) -> Result<usize> {
+        let mut lines = input.split(b'\n').enumerate();
+        let mut lineno = 0;
+        let mut loaded = 0;
+
+        loop {
+            let mut format = DumpFormat::Bytevalue;
+            let mut name = None;
+            let mut flags = DatabaseFlags::empty();
+            let mut sequence = 0;
+            let mut started = false;
+
+            for (i, line) in lines.by_ref() {
+                lineno = i + 1;
+                let line = line.map_err(io_error)?;
+                let line =
+                    String::from_utf8(line).map_err(|_| format_error(lineno, "invalid header"))?;
+                let (key, value) = line
+                    .split_once('=')
+                    .ok_or_else(|| format_error(lineno, "invalid header"))?;
+                started = true;
+                match key {
+                    "HEADER" if value == "END" => break,
+                    "VERSION" if value != "3" => {
+                        return Err(format_error(lineno, format!("unsupported VERSION {value}")))
+                    }
+                    "format" => {
+                        format = match value {
+                            "bytevalue" => DumpFormat::Bytevalue,
+                            "print" => DumpFormat::Print,
+                            _ => {
+                                return Err(format_error(
+                                    lineno,
+                                    format!("unsupported format {value}"),
+                                ))
+                            }
+                        }
+                    }
+                    "type" if value != "btree" => {
+                        return Err(format_error(lineno, format!("unsupported type {value}")))
+                    }
+                    "database" => name = Some(value.to_string()),
+                    "sequence" => {
+                        sequence = value
+                            .parse()
+                            .map_err(|_| format_error(lineno, "invalid sequence"))?
+                    }
+                    "VERSION" | "type" | "duplicates" | "db_pagesize" | "mapsize"
+                    | "maxreaders" | "geometry" | "canary" | "txnid" | "mapaddr" => {}
+                    _ => match DUMP_FLAGS.iter().find(|(_, flag)| *flag == key) {
+                        Some((flag, _)) if value == "1" => flags |= *flag,
+                        Some(_) => {}
+                        None => return Err(format_error(lineno, format!("unknown keyword {key}"))),
+                    },
+                }
+            }
+            if !started {
+                return Ok(loaded);
+            }
+
+            let db = self.create_db(name.as_deref(), flags)?;
+            mdbx_result(txn_execute(&self.txn_mutex(), |txn| unsafe {
+                let mut current = 0;
+                match ffi::mdbx_dbi_sequence(txn, db.dbi(), &mut current, 0) {
+                    ffi::MDBX_SUCCESS if sequence > current => {
+                        ffi::mdbx_dbi_sequence(txn, db.dbi(), &mut current, sequence - current)
+                    }
+                    rc => rc,
+                }
+            }))?;
+
+            let mut key = None;
+            loop {
+                let (i, line) = lines
+                    .next()
+                    .ok_or_else(|| format_error(lineno, "unexpected end of dump"))?;
+                lineno = i + 1;
+                let line = line.map_err(io_error)?;
+                if line == b"DATA=END" {
+                    break;
+                }
+                let value = read_value(&line, format)
+                    .ok_or_else(|| format_error(lineno, "invalid value"))?;
+                match key.take() {
+                    None => key = Some(value),
+                    Some(key) => {
+                        self.put(&db, key, value, WriteFlags::empty())?;
+                        loaded += 1;
+                    }
+                }
+            }
+            if key.is_some() {
+                return Err(format_error(lineno, "key without value"));
+            }
+        }
+    }
+}
+
+impl<E> Environment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Writes the environment in the text format of `mdbx_dump -a`, in a new read-only
+    /// transaction.
+    ///
+    /// See [Transaction::dump_to()].
+    pub fn dump_to(&self, out: impl Write, format: DumpFormat) -> Result<()> {
+        self.begin_ro_txn()?.dump_to(out, format)
+    }
+
+    /// Loads a dump in the text format of `mdbx_dump` in a new read-write transaction.
+    ///
+    /// See [Transaction::load_from()].
+    pub fn load_from(&self, input: impl BufRead) -> Result<usize> {
+        self.update(|txn| txn.load_from(input))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_values() {
+        for format in [DumpFormat::Bytevalue, DumpFormat::Print] {
+            let mut line = Vec::new();
+            write_value(&mut line, b"a\\b\x00\xff", format).unwrap();
+            assert_eq!(
+                line,
+                match format {
+                    DumpFormat::Bytevalue => &b" 615c6200ff\n"[..],
+                    DumpFormat::Print => &b" a\\5cb\\00\\ff\n"[..],
+                }
+            );
+            assert_eq!(
+                read_value(&line[..line.len() - 1], format).unwrap(),
+                b"a\\b\x00\xff"
+            );
+        }
+        assert_eq!(read_value(b" 6", DumpFormat::Bytevalue), None);
+        assert_eq!(read_value(b" \\zz", DumpFormat::Print), None);
+        assert_eq!(read_value(b"61", DumpFormat::Bytevalue), None);
+    }
+}
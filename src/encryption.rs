@@ -0,0 +1,172 @@
+//! Encryption of values at rest.
+//!
+//! The cipher and its key are provided by the application through the [Aead] trait, e.g. with
+//! the `chacha20poly1305` or `aes-gcm` crates. Every value is sealed with a fresh random nonce,
+//! which is stored in front of it, and authenticated together with its key, so that values
+//! cannot be moved to another key unnoticed. Keys themselves are stored in the clear.
+
+use crate::{
+    Cursor, Database, Encodable, EnvironmentKind, Error, Result, Transaction, TransactionKind,
+    WriteFlags, RW,
+};
+use std::borrow::Cow;
+use thiserror::Error;
+
+/// An authenticated cipher with associated data, implemented by the application.
+pub trait Aead {
+    /// Length of the nonces taken by the cipher, e.g. 12 bytes for ChaCha20-Poly1305 or AES-GCM.
+    fn nonce_len(&self) -> usize;
+
+    /// Encrypts `plaintext` and authenticates it along with `aad`, returning the ciphertext
+    /// followed by its tag.
+    fn seal(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> std::result::Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Checks and decrypts a ciphertext returned by [Aead::seal()].
+    fn open(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> std::result::Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+impl<A> Aead for &A
+where
+    A: Aead + ?Sized,
+{
+    fn nonce_len(&self) -> usize {
+        (**self).nonce_len()
+    }
+
+    fn seal(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> std::result::Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        (**self).seal(nonce, aad, plaintext)
+    }
+
+    fn open(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> std::result::Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        (**self).open(nonce, aad, ciphertext)
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+enum EncryptionError {
+    #[error("encrypted value shorter than its nonce")]
+    Truncated,
+}
+
+/// Encrypts the value of `key` with a random nonce, which is prepended to the result.
+pub fn encrypt(cipher: &impl Aead, key: &[u8], value: &[u8]) -> Result<Vec<u8>> {
+    let mut out = vec![0; cipher.nonce_len()];
+    getrandom::getrandom(&mut out).map_err(|e| Error::EncodeError(Box::new(e)))?;
+    let sealed = cipher.seal(&out, key, value).map_err(Error::EncodeError)?;
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Decrypts the value of `key` written by [encrypt()].
+///
+/// Returns [Error::DecodeError] if the value was not encrypted with this cipher and key, or was
+/// tampered with.
+pub fn decrypt(cipher: &impl Aead, key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < cipher.nonce_len() {
+        return Err(Error::DecodeError(Box::new(EncryptionError::Truncated)));
+    }
+    let (nonce, ciphertext) = data.split_at(cipher.nonce_len());
+    cipher
+        .open(nonce, key, ciphertext)
+        .map_err(Error::DecodeError)
+}
+
+/// A database whose values are encrypted with [encrypt()] on writes and decrypted on reads.
+#[derive(Debug)]
+pub struct SecureTable<'txn, A> {
+    db: Database<'txn>,
+    cipher: A,
+}
+
+impl<'txn, A> SecureTable<'txn, A>
+where
+    A: Aead,
+{
+    pub fn new(db: Database<'txn>, cipher: A) -> Self {
+        Self { db, cipher }
+    }
+
+    /// Returns the underlying database, whose values are encrypted.
+    pub fn db(&self) -> &Database<'txn> {
+        &self.db
+    }
+
+    /// Gets and decrypts the value of a key.
+    pub fn get<K, E>(
+        &self,
+        txn: &'txn Transaction<'_, K, E>,
+        key: impl Encodable,
+    ) -> Result<Option<Vec<u8>>>
+    where
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        let key = txn.encode_key(&self.db, key)?;
+        let key = key.as_ref();
+        txn.get::<Cow<'txn, [u8]>>(&self.db, key)?
+            .map(|data| decrypt(&self.cipher, key, &data))
+            .transpose()
+    }
+
+    /// Encrypts and stores a value, see [Transaction::put()].
+    pub fn put<E>(
+        &self,
+        txn: &'txn Transaction<'_, RW, E>,
+        key: impl Encodable,
+        value: impl AsRef<[u8]>,
+        flags: WriteFlags,
+    ) -> Result<()>
+    where
+        E: EnvironmentKind,
+    {
+        let key = txn.encode_key(&self.db, key)?;
+        let key = key.as_ref();
+        let data = encrypt(&self.cipher, key, value.as_ref())?;
+        txn.put(&self.db, key, data, flags)
+    }
+
+    /// Deletes the value of a key, see [Transaction::del()].
+    pub fn del<E>(&self, txn: &'txn Transaction<'_, RW, E>, key: impl Encodable) -> Result<bool>
+    where
+        E: EnvironmentKind,
+    {
+        txn.del(&self.db, key, None)
+    }
+
+    /// Iterates over the decrypted items in key order.
+    pub fn iter<K, E>(
+        &self,
+        txn: &'txn Transaction<'_, K, E>,
+    ) -> Result<impl Iterator<Item = Result<(Cow<'txn, [u8]>, Vec<u8>)>> + '_>
+    where
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        let cursor: Cursor<'txn, K> = txn.cursor(&self.db)?;
+        Ok(cursor.into_iter().map(move |item| {
+            let (key, data) = item?;
+            let value = decrypt(&self.cipher, &key, &data)?;
+            Ok((key, value))
+        }))
+    }
+}
@@ -1,28 +1,107 @@
 use crate::{
     database::Database,
-    error::{mdbx_result, Error, Result},
+    error::{io_error, mdbx_result, Error, Result},
     flags::EnvironmentFlags,
+    latency::{CommitLatency, LatencyRecorder, SlowCommit},
     transaction::{RO, RW},
     Mode, Transaction, TransactionKind,
 };
 use byteorder::{ByteOrder, NativeEndian};
-use libc::c_uint;
+use libc::{c_uint, c_void};
 use mem::size_of;
+use parking_lot::Mutex;
 use std::{
+    backtrace::Backtrace,
+    collections::HashMap,
     ffi::CString,
     fmt,
     fmt::Debug,
+    fs, io,
     marker::PhantomData,
     mem,
     ops::{Bound, RangeBounds},
-    os::unix::ffi::OsStrExt,
-    path::Path,
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+    path::{Path, PathBuf},
     ptr, result,
-    sync::mpsc::{sync_channel, SyncSender},
-    thread::sleep,
-    time::Duration,
+    sync::{
+        atomic::{self, AtomicU64},
+        mpsc::{channel, sync_channel, Receiver, Sender, SyncSender},
+        Arc, Once,
+    },
+    thread::{self, sleep, ThreadId},
+    time::{Duration, Instant},
 };
 
+/// Magic numbers of network file systems in `statfs::f_type`, see `statfs(2)`.
+#[cfg(target_os = "linux")]
+const REMOTE_FS_MAGICS: [u32; 6] = [
+    0x0000_6969, // NFS
+    0x0000_517b, // SMB
+    0xff53_4d42, // CIFS
+    0xfe53_4d42, // SMB2
+    0x00c3_6400, // Ceph
+    0x0102_1997, // 9P
+];
+
+/// Returns whether `path`, or its parent directory if it does not exist yet, is on a network
+/// file system.
+#[cfg(target_os = "linux")]
+fn is_remote_fs(path: &Path) -> bool {
+    let path = match path.exists() {
+        true => path,
+        false => path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new(".")),
+    };
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat = mem::MaybeUninit::<libc::statfs>::uninit();
+    if unsafe { libc::statfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return false;
+    }
+    is_remote_fs_magic(unsafe { stat.assume_init() }.f_type as u32)
+}
+
+/// Returns whether the `statfs::f_type` magic number is one of a network file system.
+#[cfg(target_os = "linux")]
+fn is_remote_fs_magic(f_type: u32) -> bool {
+    REMOTE_FS_MAGICS.contains(&f_type)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_remote_fs(_: &Path) -> bool {
+    false
+}
+
+/// Number of `fork()` calls made by the ancestors of the current process and the process itself,
+/// since the first environment was opened. Comparing it is much cheaper than `getpid()`, which
+/// is a system call.
+static FORK_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+extern "C" fn bump_fork_generation() {
+    FORK_GENERATION.fetch_add(1, atomic::Ordering::Relaxed);
+}
+
+/// Returns the current value of [FORK_GENERATION], starting to count forks on the first call.
+fn fork_generation() -> u64 {
+    static AT_FORK: Once = Once::new();
+    AT_FORK.call_once(|| unsafe {
+        libc::pthread_atfork(None, None, Some(bump_fork_generation));
+    });
+    FORK_GENERATION.load(atomic::Ordering::Relaxed)
+}
+
+/// Identity of the data file of an environment, see
+/// [EnvironmentBuilder::set_durability_check()].
+#[derive(Debug)]
+struct DataFile {
+    path: PathBuf,
+    dev: u64,
+    ino: u64,
+}
+
 mod private {
     use super::*;
 
@@ -32,13 +111,13 @@ mod private {
     impl Sealed for WriteMap {}
 }
 
-pub trait EnvironmentKind: private::Sealed + Debug + 'static {
+pub trait EnvironmentKind: private::Sealed + Clone + Debug + 'static {
     const EXTRA_FLAGS: ffi::MDBX_env_flags_t;
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct NoWriteMap;
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct WriteMap;
 
 impl EnvironmentKind for NoWriteMap {
@@ -70,17 +149,90 @@ pub(crate) enum TxnManagerMessage {
     },
     Commit {
         tx: TxnPtr,
+        nested: bool,
         sender: SyncSender<Result<bool>>,
     },
 }
 
+fn begin_txn(
+    env: *mut ffi::MDBX_env,
+    parent: *mut ffi::MDBX_txn,
+    flags: ffi::MDBX_txn_flags_t,
+) -> Result<TxnPtr> {
+    let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
+    mdbx_result(unsafe { ffi::mdbx_txn_begin_ex(env, parent, flags, &mut txn, ptr::null_mut()) })
+        .map(|_| TxnPtr(txn))
+}
+
+fn commit_txn(
+    txn: *mut ffi::MDBX_txn,
+    nested: bool,
+    subscribers: &Mutex<Vec<Sender<u64>>>,
+    latency_recorder: Option<&LatencyRecorder>,
+    slow_commit: &SlowCommit,
+) -> Result<bool> {
+    let txnid = unsafe { ffi::mdbx_txn_id(txn) };
+    #[cfg(feature = "metrics")]
+    let start = Instant::now();
+    let mut latency = unsafe { mem::zeroed() };
+    let res = mdbx_result(unsafe { ffi::mdbx_txn_commit_ex(txn, &mut latency) });
+    #[cfg(feature = "metrics")]
+    crate::telemetry::record_commit(start.elapsed());
+    if res.is_ok() && !nested {
+        let latency = CommitLatency::from_mdbx(&latency);
+        if let Some(recorder) = latency_recorder {
+            recorder.record(&latency);
+        }
+        slow_commit.check(txnid, &latency);
+    }
+    // Subscribers are notified before the committing thread is released, so the notification is
+    // visible once `commit()` returns.
+    if matches!(res, Ok(false)) && !nested {
+        subscribers
+            .lock()
+            .retain(|subscriber| subscriber.send(txnid).is_ok());
+    }
+    res
+}
+
 /// An environment supports multiple databases, all residing in the same shared-memory map.
+///
+/// # Fork safety
+///
+/// An environment and its transactions belong to the process which opened it. The memory maps
+/// and the transaction manager thread are not inherited by a child process created with
+/// `fork()`, so in the child starting or committing a transaction fails with [Error::Inherited],
+/// while dropping inherited transactions and the environment itself leaks them instead of
+/// touching the parent's lock table. A child process must open the environment again.
 pub struct Environment<E>
 where
     E: EnvironmentKind,
 {
     env: *mut ffi::MDBX_env,
-    pub(crate) txn_manager: Option<SyncSender<TxnManagerMessage>>,
+    txn_manager: Option<SyncSender<TxnManagerMessage>>,
+    direct_commit: bool,
+    op_context: bool,
+    /// Names of the databases opened in this environment, reachable through the user context of
+    /// the MDBX environment.
+    dbi_names: Box<Mutex<HashMap<ffi::MDBX_dbi, String>>>,
+    /// Handles of the named databases which were opened, to open them again without a call into
+    /// MDBX.
+    dbi_cache: Mutex<HashMap<String, ffi::MDBX_dbi>>,
+    /// Token shared by the [Database] and [Cursor](crate::Cursor) instances of each handle, to
+    /// tell whether a handle is still in use before closing it. Holds the name of the database.
+    dbi_handles: Mutex<HashMap<ffi::MDBX_dbi, Arc<Option<String>>>>,
+    commit_subscribers: Arc<Mutex<Vec<Sender<u64>>>>,
+    /// Read-write transactions which are not finished yet, so that leaked ones can be aborted
+    /// when the environment is closed.
+    live_txns: Mutex<HashMap<usize, LiveTxn>>,
+    txn_registry: Option<Mutex<HashMap<usize, TxnOrigin>>>,
+    latency_recorder: Option<Arc<LatencyRecorder>>,
+    slow_commit: SlowCommit,
+    auto_grow: Option<GrowthPolicy>,
+    stale_readers: usize,
+    data_file: Option<DataFile>,
+    /// Value of [FORK_GENERATION] when the environment was opened.
+    fork_generation: u64,
     _marker: PhantomData<E>,
 }
 
@@ -102,10 +254,81 @@ where
             spill_max_denominator: None,
             spill_min_denominator: None,
             geometry: None,
+            txn_tracking: false,
+            direct_commit: false,
+            reader_check: false,
+            durability_check: false,
+            op_context: false,
+            allow_remote_fs: false,
+            latency_recording: false,
+            slow_commit: SlowCommit::default(),
+            auto_grow: None,
             _marker: PhantomData,
         }
     }
 
+    pub(crate) fn record_dbi_name(&self, dbi: ffi::MDBX_dbi, name: &str) {
+        self.dbi_names.lock().insert(dbi, name.to_string());
+    }
+
+    pub(crate) fn cached_dbi(&self, name: &str) -> Option<ffi::MDBX_dbi> {
+        self.dbi_cache.lock().get(name).copied()
+    }
+
+    pub(crate) fn cache_dbi(&self, name: &str, dbi: ffi::MDBX_dbi) {
+        self.dbi_cache.lock().insert(name.to_string(), dbi);
+    }
+
+    /// Forgets a handle which is being closed, as MDBX may reuse it for another database.
+    pub(crate) fn forget_dbi(&self, dbi: ffi::MDBX_dbi) {
+        self.dbi_cache.lock().retain(|_, cached| *cached != dbi);
+    }
+
+    pub(crate) fn dbi_handle(&self, dbi: ffi::MDBX_dbi) -> Arc<Option<String>> {
+        self.dbi_handles
+            .lock()
+            .entry(dbi)
+            .or_insert_with(|| Arc::new(self.dbi_names.lock().get(&dbi).cloned()))
+            .clone()
+    }
+
+    /// Returns whether [Database] or [Cursor](crate::Cursor) instances other than `db` reference
+    /// its handle.
+    pub(crate) fn dbi_in_use(&self, db: &Database<'_>) -> bool {
+        let handles = self.dbi_handles.lock();
+        Self::other_handles(&handles, db)
+    }
+
+    fn other_handles(
+        handles: &HashMap<ffi::MDBX_dbi, Arc<Option<String>>>,
+        db: &Database<'_>,
+    ) -> bool {
+        // The map itself and `db` hold a reference.
+        let own = 1 + db.is_tracked() as usize;
+        handles
+            .get(&db.dbi())
+            .is_some_and(|handle| Arc::strong_count(handle) > own)
+    }
+
+    /// Closes the handle of `db` with `close`, unless other [Database] or
+    /// [Cursor](crate::Cursor) instances still reference it.
+    pub(crate) fn release_dbi(
+        &self,
+        db: Database<'_>,
+        close: impl FnOnce(ffi::MDBX_dbi) -> Result<()>,
+    ) -> Result<()> {
+        let mut handles = self.dbi_handles.lock();
+        if Self::other_handles(&handles, &db) {
+            return Err(Error::DatabaseBusy);
+        }
+        let dbi = db.dbi();
+        drop(db);
+        close(dbi)?;
+        handles.remove(&dbi);
+        self.forget_dbi(dbi);
+        Ok(())
+    }
+
     /// Returns a raw pointer to the underlying MDBX environment.
     ///
     /// The caller **must** ensure that the pointer is not dereferenced after the lifetime of the
@@ -122,17 +345,8 @@ where
     /// Create a read-write transaction for use with the environment. This method will block while
     /// there are any other read-write transactions open on the environment.
     pub fn begin_rw_txn(&self) -> Result<Transaction<'_, RW, E>> {
-        let sender = self.txn_manager.as_ref().ok_or(Error::Access)?;
         let txn = loop {
-            let (tx, rx) = sync_channel(0);
-            sender
-                .send(TxnManagerMessage::Begin {
-                    parent: TxnPtr(ptr::null_mut()),
-                    flags: RW::OPEN_FLAGS,
-                    sender: tx,
-                })
-                .unwrap();
-            let res = rx.recv().unwrap();
+            let res = self.begin_txn_ptr(ptr::null_mut(), RW::OPEN_FLAGS);
             if let Err(Error::Busy) = &res {
                 sleep(Duration::from_millis(250));
                 continue;
@@ -140,7 +354,412 @@ where
 
             break res;
         }?;
-        Ok(Transaction::new_from_ptr(self, txn.0))
+        Ok(self.new_rw_txn(txn))
+    }
+
+    /// Create a read-write transaction for use with the environment, giving up with
+    /// [Error::Busy] if another read-write transaction is still open after `timeout`.
+    ///
+    /// The transaction is started with [ffi::MDBX_TXN_TRY], which fails instead of waiting for
+    /// the write lock, and retried with a growing backoff until `timeout` passes. The thread
+    /// starting read-write transactions is never blocked past the deadline.
+    pub fn begin_rw_txn_timeout(&self, timeout: Duration) -> Result<Transaction<'_, RW, E>> {
+        let deadline = Instant::now() + timeout;
+        let mut backoff = Duration::from_millis(1);
+        let txn = loop {
+            let res = self.begin_txn_ptr(ptr::null_mut(), RW::OPEN_FLAGS | ffi::MDBX_TXN_TRY);
+            if let Err(Error::Busy) = &res {
+                let now = Instant::now();
+                if now < deadline {
+                    sleep(backoff.min(deadline - now));
+                    backoff = (backoff * 2).min(Duration::from_millis(50));
+                    continue;
+                }
+            }
+
+            break res;
+        }?;
+        Ok(self.new_rw_txn(txn))
+    }
+
+    fn new_rw_txn(&self, txn: TxnPtr) -> Transaction<'_, RW, E> {
+        let mut txn = Transaction::new_from_ptr(self, txn.0, ptr::null_mut());
+        if self.direct_commit {
+            txn.set_owner(thread::current().id());
+        }
+        txn
+    }
+
+    /// Whether read-write transactions are started and finished on the calling thread, see
+    /// [EnvironmentBuilder::set_direct_commit()].
+    pub fn is_direct_commit(&self) -> bool {
+        self.direct_commit
+    }
+
+    /// Whether failed operations carry their context, see [EnvironmentBuilder::set_op_context()].
+    pub(crate) fn has_op_context(&self) -> bool {
+        self.op_context
+    }
+
+    /// Whether the environment was opened by a parent of the current process, see
+    /// [Environment#fork-safety].
+    pub fn is_inherited(&self) -> bool {
+        FORK_GENERATION.load(atomic::Ordering::Relaxed) != self.fork_generation
+    }
+
+    /// Checks that a commit reached the storage, see
+    /// [EnvironmentBuilder::set_durability_check()].
+    pub(crate) fn check_durability(&self) -> Result<()> {
+        let Some(file) = &self.data_file else {
+            return Ok(());
+        };
+        self.sync(true)
+            .map_err(|error| Error::SyncFailed(error.to_err_code()))?;
+        match fs::metadata(&file.path) {
+            Ok(metadata) if (metadata.dev(), metadata.ino()) == (file.dev, file.ino) => Ok(()),
+            Ok(_) => Err(Error::StorageLost),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Err(Error::StorageLost),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    pub(crate) fn check_inherited(&self) -> Result<()> {
+        if self.is_inherited() {
+            return Err(Error::Inherited);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn begin_txn_ptr(
+        &self,
+        parent: *mut ffi::MDBX_txn,
+        flags: ffi::MDBX_txn_flags_t,
+    ) -> Result<TxnPtr> {
+        self.check_inherited()?;
+        match &self.txn_manager {
+            Some(sender) => {
+                let (tx, rx) = sync_channel(0);
+                sender
+                    .send(TxnManagerMessage::Begin {
+                        parent: TxnPtr(parent),
+                        flags,
+                        sender: tx,
+                    })
+                    .unwrap();
+                rx.recv().unwrap()
+            }
+            None if self.direct_commit => begin_txn(self.env, parent, flags),
+            None => Err(Error::Access),
+        }
+    }
+
+    pub(crate) fn commit_txn_ptr(&self, txn: *mut ffi::MDBX_txn, nested: bool) -> Result<bool> {
+        match &self.txn_manager {
+            Some(sender) => {
+                let (tx, rx) = sync_channel(0);
+                sender
+                    .send(TxnManagerMessage::Commit {
+                        tx: TxnPtr(txn),
+                        nested,
+                        sender: tx,
+                    })
+                    .unwrap();
+                rx.recv().unwrap()
+            }
+            None => commit_txn(
+                txn,
+                nested,
+                &self.commit_subscribers,
+                self.latency_recorder.as_deref(),
+                &self.slow_commit,
+            ),
+        }
+    }
+
+    pub(crate) fn abort_txn_ptr(&self, txn: *mut ffi::MDBX_txn) -> Result<bool> {
+        match &self.txn_manager {
+            Some(sender) => {
+                let (tx, rx) = sync_channel(0);
+                sender
+                    .send(TxnManagerMessage::Abort {
+                        tx: TxnPtr(txn),
+                        sender: tx,
+                    })
+                    .unwrap();
+                rx.recv().unwrap()
+            }
+            None => mdbx_result(unsafe { ffi::mdbx_txn_abort(txn) }),
+        }
+    }
+
+    /// Records a transaction which was started, `parent` being null unless it is nested.
+    ///
+    /// Only read-write transactions are recorded as live, unless transaction tracking is enabled.
+    pub(crate) fn track_txn(
+        &self,
+        txn: *mut ffi::MDBX_txn,
+        parent: *mut ffi::MDBX_txn,
+        read_write: bool,
+    ) {
+        if read_write {
+            let mut live = self.live_txns.lock();
+            let parent = (!parent.is_null()).then_some(parent as usize);
+            if let Some(parent) = parent.and_then(|parent| live.get_mut(&parent)) {
+                parent.children.push(txn as usize);
+            }
+            live.insert(
+                txn as usize,
+                LiveTxn {
+                    // The writer lock is taken by the thread which starts the transaction.
+                    owner: (parent.is_none() && self.direct_commit).then(|| thread::current().id()),
+                    parent,
+                    children: Vec::new(),
+                },
+            );
+        }
+        if let Some(registry) = &self.txn_registry {
+            let thread = thread::current();
+            registry.lock().insert(
+                txn as usize,
+                TxnOrigin {
+                    id: unsafe { ffi::mdbx_txn_id(txn) },
+                    read_write,
+                    thread: thread.id(),
+                    thread_name: thread.name().map(ToString::to_string),
+                    backtrace: Arc::new(Backtrace::force_capture()),
+                },
+            );
+        }
+    }
+
+    /// Forgets a transaction which is being finished, along with its nested transactions.
+    pub(crate) fn untrack_txn(&self, txn: *mut ffi::MDBX_txn, read_write: bool) {
+        if read_write {
+            let mut live = self.live_txns.lock();
+            let parent = live.get(&(txn as usize)).and_then(|t| t.parent);
+            if let Some(parent) = parent.and_then(|parent| live.get_mut(&parent)) {
+                parent.children.retain(|&child| child != txn as usize);
+            }
+            // Nested transactions are finished together with their parent.
+            let mut finished = vec![txn as usize];
+            while let Some(txn) = finished.pop() {
+                if let Some(t) = live.remove(&txn) {
+                    finished.extend(t.children);
+                }
+            }
+        }
+        if let Some(registry) = &self.txn_registry {
+            registry.lock().remove(&(txn as usize));
+        }
+    }
+
+    /// Aborts the read-write transactions which were leaked, e.g. with [mem::forget()] or when
+    /// dropped on another thread during a panic, and returns their number. Nested transactions
+    /// are aborted with their parent.
+    ///
+    /// In direct-commit mode, a transaction can only be aborted on the thread which started it,
+    /// as MDBX releases the writer lock there. Leaked transactions of other threads are skipped,
+    /// in which case [Error::ThreadMismatch] is returned once the others were aborted.
+    fn abort_leaked_txns(&self) -> Result<usize> {
+        let current = thread::current().id();
+        let (leaked, foreign): (Vec<_>, Vec<_>) = self
+            .live_txns
+            .lock()
+            .iter()
+            .filter(|(_, t)| t.parent.is_none())
+            .map(|(&txn, t)| (txn as *mut ffi::MDBX_txn, t.owner))
+            .partition(|(_, owner)| owner.is_none_or(|owner| owner == current));
+        for &(txn, _) in &leaked {
+            self.abort_txn_ptr(txn)?;
+            self.untrack_txn(txn, true);
+        }
+        if !foreign.is_empty() {
+            return Err(Error::ThreadMismatch(None));
+        }
+        Ok(leaked.len())
+    }
+
+    /// Closes the environment and reports errors, which dropping it ignores.
+    ///
+    /// Transactions borrow the environment, so none of them can be in use any more. The
+    /// read-write ones which were leaked instead of being finished, e.g. with [mem::forget()] or
+    /// when dropped on another thread during a panic, are aborted first and their number is
+    /// returned. Leaked read-only transactions only hold a reader slot, which is released along
+    /// with the environment.
+    ///
+    /// Returns a [CloseError] holding the environment if it is still open, so that closing it
+    /// can be retried: with [Error::Busy] if a write transaction started through
+    /// [Environment::env()] is still running on another thread, or with [Error::ThreadMismatch]
+    /// if, in direct-commit mode, a leaked write transaction was started by another thread and
+    /// cannot be aborted on this one.
+    pub fn close(mut self) -> result::Result<usize, CloseError<E>> {
+        // Inherited from the parent process, see [Environment#fork-safety].
+        if self.is_inherited() {
+            return Ok(0);
+        }
+        let aborted = match self.abort_leaked_txns() {
+            Ok(aborted) => aborted,
+            Err(error) => {
+                return Err(CloseError {
+                    error,
+                    env: Some(Box::new(self)),
+                })
+            }
+        };
+        match mdbx_result(unsafe { ffi::mdbx_env_close_ex(self.env, false) }) {
+            Err(Error::Busy) => Err(CloseError {
+                error: Error::Busy,
+                env: Some(Box::new(self)),
+            }),
+            res => {
+                self.env = ptr::null_mut();
+                res.map(|_| aborted)
+                    .map_err(|error| CloseError { error, env: None })
+            }
+        }
+    }
+
+    /// Returns where the currently open transactions were started.
+    ///
+    /// Always empty unless enabled with [EnvironmentBuilder::set_txn_tracking()].
+    pub fn open_transactions(&self) -> Vec<TxnOrigin> {
+        self.txn_registry
+            .as_ref()
+            .map(|registry| registry.lock().values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns where the open transaction with the given id was started.
+    ///
+    /// Useful to find the transaction reported by [Error::ThreadMismatch] or
+    /// [Error::TxnOverlapping]. Always [None] unless enabled with
+    /// [EnvironmentBuilder::set_txn_tracking()].
+    pub fn txn_origin(&self, txnid: u64) -> Option<TxnOrigin> {
+        self.open_transactions()
+            .into_iter()
+            .find(|origin| origin.id == txnid)
+    }
+
+    /// Subscribes to commits of read-write transactions.
+    ///
+    /// The returned channel receives the id of every top-level read-write transaction after it
+    /// has been committed. Nested transactions and commits of read-only environments are not
+    /// reported. The subscription is dropped together with the receiver.
+    pub fn subscribe(&self) -> Receiver<u64> {
+        let (tx, rx) = channel();
+        self.commit_subscribers.lock().push(tx);
+        rx
+    }
+
+    /// Runs `f` in a read-only transaction.
+    pub fn view<R, UErr>(
+        &self,
+        f: impl FnOnce(&Transaction<'_, RO, E>) -> result::Result<R, UErr>,
+    ) -> result::Result<R, UErr>
+    where
+        UErr: From<Error>,
+    {
+        let txn = self.begin_ro_txn()?;
+        f(&txn)
+    }
+
+    /// Runs `f` in a read-write transaction, which is committed if `f` returns [Ok].
+    ///
+    /// The transaction is aborted if `f` returns [Err] or panics.
+    pub fn update<R, UErr>(
+        &self,
+        f: impl FnOnce(&Transaction<'_, RW, E>) -> result::Result<R, UErr>,
+    ) -> result::Result<R, UErr>
+    where
+        UErr: From<Error>,
+    {
+        let txn = self.begin_rw_txn()?;
+        let res = f(&txn)?;
+        txn.commit()?;
+        Ok(res)
+    }
+
+    /// Runs `f` in a read-write transaction, which is committed if `f` returns [Ok].
+    ///
+    /// With a [GrowthPolicy] set by [EnvironmentBuilder::set_auto_grow()], a transaction failing
+    /// with [Error::MapFull] or [Error::UnableExtendMapsize] is aborted, the upper bound of the
+    /// map size is raised and `f` is run again in a new transaction. A transaction which ran out
+    /// of space cannot be continued, hence `f` may be called more than once.
+    ///
+    /// As the environment is opened with `MDBX_NOTLS`, MDBX cannot move the memory map while
+    /// growing it. If the address space right after the map is in use, growing fails and
+    /// [Error::UnableExtendMapsize] is returned; reserving a larger upper bound with
+    /// [EnvironmentBuilder::set_geometry()] up front avoids this.
+    pub fn update_retrying<R>(
+        &self,
+        mut f: impl FnMut(&Transaction<'_, RW, E>) -> Result<R>,
+    ) -> Result<R> {
+        let mut retries = 0;
+        loop {
+            let res = self.begin_rw_txn().and_then(|txn| {
+                let res = f(&txn)?;
+                txn.commit()?;
+                Ok(res)
+            });
+            match (res, &self.auto_grow) {
+                (Err(Error::MapFull | Error::UnableExtendMapsize), Some(policy))
+                    if retries < policy.max_retries && self.grow_map(policy)? =>
+                {
+                    retries += 1;
+                }
+                (res, _) => return res,
+            }
+        }
+    }
+
+    /// Raises the upper bound of the map size by one step of `policy`, returning `false` if it
+    /// is already at [GrowthPolicy::max_size].
+    fn grow_map(&self, policy: &GrowthPolicy) -> Result<bool> {
+        let upper = self.info()?.geometry().max() as usize;
+        if upper >= policy.max_size {
+            return Ok(false);
+        }
+        let upper = upper.saturating_add(policy.step).min(policy.max_size);
+        mdbx_result(unsafe {
+            ffi::mdbx_env_set_geometry(self.env(), -1, -1, upper as isize, -1, -1, -1)
+        })?;
+        Ok(true)
+    }
+
+    /// Grows the database file so that at least `bytes` more can be written without extending
+    /// it, and returns its new size.
+    ///
+    /// MDBX extends the file by [GeometryInfo::grow()] whenever it runs out of space, which
+    /// stalls the write transaction doing so. Growing it once ahead of a large import avoids
+    /// these stalls. The shrink threshold is left unchanged, so commits give the room back once
+    /// more than [GeometryInfo::shrink()] of it is unused; a higher threshold can be configured
+    /// with [EnvironmentBuilder::set_geometry()]. The upper bound of the map size is raised if
+    /// needed, which fails with [Error::UnableExtendMapsize] if the address space after the map
+    /// is in use. Does nothing if the file is large enough already. Blocks until the current
+    /// write transaction, if any, is finished, so it must not be called while holding one.
+    pub fn ensure_capacity(&self, bytes: usize) -> Result<usize> {
+        let info = self.info()?;
+        let page_size = self.stat()?.page_size() as usize;
+        let used = (info.last_pgno() + 1) * page_size;
+        let current = info.geometry().current() as usize;
+        let size = used.saturating_add(bytes);
+        if size <= current {
+            return Ok(current);
+        }
+        let upper = (info.geometry().max() as usize).max(size);
+        // The current threshold is passed explicitly, MDBX would reset it to its default.
+        mdbx_result(unsafe {
+            ffi::mdbx_env_set_geometry(
+                self.env(),
+                -1,
+                size as isize,
+                upper as isize,
+                -1,
+                info.geometry().shrink() as isize,
+                -1,
+            )
+        })?;
+        Ok(self.info()?.geometry().current() as usize)
     }
 
     /// Flush the environment data buffers to disk.
@@ -164,16 +783,19 @@ where
 
     /// Retrieves info about this environment.
     pub fn info(&self) -> Result<Info> {
-        unsafe {
-            let mut info = Info(mem::zeroed());
-            mdbx_result(ffi::mdbx_env_info_ex(
-                self.env(),
-                ptr::null(),
-                &mut info.0,
-                size_of::<Info>(),
-            ))?;
-            Ok(info)
-        }
+        unsafe { self.info_for(ptr::null()) }
+    }
+
+    /// Retrieves info about the snapshot of `txn`, or the latest one if it is null.
+    pub(crate) unsafe fn info_for(&self, txn: *const ffi::MDBX_txn) -> Result<Info> {
+        let mut info = Info(mem::zeroed());
+        mdbx_result(ffi::mdbx_env_info_ex(
+            self.env(),
+            txn,
+            &mut info.0,
+            size_of::<Info>(),
+        ))?;
+        Ok(info)
     }
 
     /// Retrieves the total number of pages on the freelist.
@@ -224,6 +846,28 @@ where
 
         Ok(freelist)
     }
+
+    /// Clears the reader slots left behind by processes which exited without closing their
+    /// read-only transactions, returning the number of slots cleared.
+    ///
+    /// Stale readers keep old pages from being reused, which makes the database grow.
+    pub fn reader_check(&self) -> Result<usize> {
+        let mut dead: libc::c_int = 0;
+        mdbx_result(unsafe { ffi::mdbx_reader_check(self.env(), &mut dead) })?;
+        Ok(dead as usize)
+    }
+
+    /// Returns the latencies of all read-write commits so far, if enabled with
+    /// [EnvironmentBuilder::set_latency_recording()].
+    pub fn latency_recorder(&self) -> Option<&LatencyRecorder> {
+        self.latency_recorder.as_deref()
+    }
+
+    /// Number of stale reader slots cleared when the environment was opened, see
+    /// [EnvironmentBuilder::set_reader_check()].
+    pub fn stale_readers_cleared(&self) -> usize {
+        self.stale_readers
+    }
 }
 
 /// Environment statistics.
@@ -289,6 +933,26 @@ impl GeometryInfo {
     pub fn min(&self) -> u64 {
         self.0.lower
     }
+
+    /// Upper bound of the size of the database file.
+    pub fn max(&self) -> u64 {
+        self.0.upper
+    }
+
+    /// Current size of the database file.
+    pub fn current(&self) -> u64 {
+        self.0.current
+    }
+
+    /// Threshold of unused space at the end of the file for shrinking it.
+    pub fn shrink(&self) -> u64 {
+        self.0.shrink
+    }
+
+    /// Step by which the database file grows.
+    pub fn grow(&self) -> u64 {
+        self.0.grow
+    }
 }
 
 /// Environment information.
@@ -320,6 +984,12 @@ impl Info {
         self.0.mi_recent_txnid as usize
     }
 
+    /// Oldest transaction ID still in use by a reader
+    #[inline]
+    pub fn latter_reader_txnid(&self) -> usize {
+        self.0.mi_latter_reader_txnid as usize
+    }
+
     /// Max reader slots in the environment
     #[inline]
     pub fn max_readers(&self) -> usize {
@@ -331,11 +1001,165 @@ impl Info {
     pub fn num_readers(&self) -> usize {
         self.0.mi_numreaders as usize
     }
+
+    /// The three meta pages, of which the one with the greatest txnid is current.
+    pub fn meta_pages(&self) -> [MetaInfo; 3] {
+        let bootid = &self.0.mi_bootid;
+        [
+            MetaInfo::new(self.0.mi_meta0_txnid, self.0.mi_meta0_sign, &bootid.meta0),
+            MetaInfo::new(self.0.mi_meta1_txnid, self.0.mi_meta1_sign, &bootid.meta1),
+            MetaInfo::new(self.0.mi_meta2_txnid, self.0.mi_meta2_sign, &bootid.meta2),
+        ]
+    }
+
+    /// Boot id of the running system, or zero if unavailable.
+    #[inline]
+    pub fn bootid(&self) -> u128 {
+        bootid(&self.0.mi_bootid.current)
+    }
+
+    /// Bytes written since the last sync to disk.
+    #[inline]
+    pub fn unsync_volume(&self) -> u64 {
+        self.0.mi_unsync_volume
+    }
+
+    /// Volume of unsynced writes which triggers a sync, or zero if disabled.
+    #[inline]
+    pub fn autosync_threshold(&self) -> u64 {
+        self.0.mi_autosync_threshold
+    }
+
+    /// Time elapsed since the last sync to disk.
+    #[inline]
+    pub fn since_sync(&self) -> Duration {
+        duration_16dot16(self.0.mi_since_sync_seconds16dot16)
+    }
+
+    /// Time after which unsynced writes trigger a sync, or zero if disabled.
+    #[inline]
+    pub fn autosync_period(&self) -> Duration {
+        duration_16dot16(self.0.mi_autosync_period_seconds16dot16)
+    }
+
+    /// Time elapsed since the reader table was last checked for stale readers.
+    #[inline]
+    pub fn since_reader_check(&self) -> Duration {
+        duration_16dot16(self.0.mi_since_reader_check_seconds16dot16)
+    }
+
+    /// Current mode of the environment, a combination of `MDBX_env_flags_t`.
+    #[inline]
+    pub fn mode(&self) -> u32 {
+        self.0.mi_mode
+    }
+}
+
+/// State of a meta page, as reported by [Info::meta_pages()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MetaInfo {
+    /// Id of the transaction which wrote the meta page.
+    pub txnid: u64,
+    /// Signature of the data, telling whether it was synced to disk.
+    pub sign: u64,
+    /// Boot id of the system which wrote the meta page.
+    pub bootid: u128,
+}
+
+impl MetaInfo {
+    fn new(txnid: u64, sign: u64, bootid: &ffi::MDBX_envinfo__bindgen_ty_2__bindgen_ty_1) -> Self {
+        Self {
+            txnid,
+            sign,
+            bootid: self::bootid(bootid),
+        }
+    }
+
+    /// Whether the data of the meta page was synced to disk.
+    #[inline]
+    pub fn is_steady(&self) -> bool {
+        // MDBX_DATASIGN_NONE and MDBX_DATASIGN_WEAK mark unsynced data.
+        self.sign > 1
+    }
+}
+
+fn bootid(id: &ffi::MDBX_envinfo__bindgen_ty_2__bindgen_ty_1) -> u128 {
+    (id.x as u128) << 64 | id.y as u128
+}
+
+/// Converts 16.16 fixed-point seconds, as reported by MDBX.
+pub(crate) fn duration_16dot16(v: u32) -> Duration {
+    Duration::from_nanos((v as u64 * 1_000_000_000) >> 16)
+}
+
+/// A read-write transaction recorded by [Environment::track_txn()].
+#[derive(Clone, Debug)]
+struct LiveTxn {
+    /// Thread holding the writer lock of a top-level transaction in direct-commit mode.
+    owner: Option<ThreadId>,
+    /// Parent of a nested transaction.
+    parent: Option<usize>,
+    /// Nested transactions, finished together with this one.
+    children: Vec<usize>,
+}
+
+/// Error returned by [Environment::close()].
+#[derive(Debug)]
+pub struct CloseError<E>
+where
+    E: EnvironmentKind,
+{
+    pub error: Error,
+    /// The environment, if it is still open.
+    pub env: Option<Box<Environment<E>>>,
+}
+
+impl<E> fmt::Display for CloseError<E>
+where
+    E: EnvironmentKind,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "failed to close environment: {}", self.error)
+    }
+}
+
+impl<E> std::error::Error for CloseError<E>
+where
+    E: EnvironmentKind,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
 }
 
 unsafe impl<E> Send for Environment<E> where E: EnvironmentKind {}
 unsafe impl<E> Sync for Environment<E> where E: EnvironmentKind {}
 
+/// Where a transaction was started, recorded when enabled with
+/// [EnvironmentBuilder::set_txn_tracking()].
+#[derive(Clone, Debug)]
+pub struct TxnOrigin {
+    /// Id of the transaction.
+    pub id: u64,
+    /// Whether this is a read-write transaction.
+    pub read_write: bool,
+    /// Thread which started the transaction.
+    pub thread: ThreadId,
+    /// Name of the thread which started the transaction.
+    pub thread_name: Option<String>,
+    /// Backtrace of the call which started the transaction.
+    pub backtrace: Arc<Backtrace>,
+}
+
+/// Looks up the name of a database opened in the environment of the given transaction.
+///
+/// Returns [None] for the main database and for environments not opened by this crate.
+pub(crate) unsafe fn dbi_name(txn: *const ffi::MDBX_txn, dbi: ffi::MDBX_dbi) -> Option<String> {
+    let names = ffi::mdbx_env_get_userctx(ffi::mdbx_txn_env(txn))
+        as *const Mutex<HashMap<ffi::MDBX_dbi, String>>;
+    names.as_ref()?.lock().get(&dbi).cloned()
+}
+
 impl<E> fmt::Debug for Environment<E>
 where
     E: EnvironmentKind,
@@ -350,6 +1174,12 @@ where
     E: EnvironmentKind,
 {
     fn drop(&mut self) {
+        // The memory maps of the environment are not inherited, so closing it in a child process
+        // could only do harm.
+        if self.env.is_null() || self.is_inherited() {
+            return;
+        }
+        let _ = self.abort_leaked_txns();
         unsafe {
             ffi::mdbx_env_close_ex(self.env, false);
         }
@@ -385,13 +1215,25 @@ impl<R> Default for Geometry<R> {
     }
 }
 
+/// How [Environment::update_retrying()] raises the upper bound of the map size when a
+/// transaction runs out of space, see [EnvironmentBuilder::set_auto_grow()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrowthPolicy {
+    /// Bytes added to the upper bound on each growth.
+    pub step: usize,
+    /// The upper bound is never raised beyond this size.
+    pub max_size: usize,
+    /// Maximum number of times a transaction is retried.
+    pub max_retries: usize,
+}
+
 /// Options for opening or creating an environment.
 #[derive(Debug, Clone)]
 pub struct EnvironmentBuilder<E>
 where
     E: EnvironmentKind,
 {
-    flags: EnvironmentFlags,
+    pub(crate) flags: EnvironmentFlags,
     max_readers: Option<c_uint>,
     max_dbs: Option<u64>,
     rp_augment_limit: Option<u64>,
@@ -401,6 +1243,15 @@ where
     spill_max_denominator: Option<u64>,
     spill_min_denominator: Option<u64>,
     geometry: Option<Geometry<(Option<usize>, Option<usize>)>>,
+    txn_tracking: bool,
+    direct_commit: bool,
+    reader_check: bool,
+    durability_check: bool,
+    op_context: bool,
+    allow_remote_fs: bool,
+    latency_recording: bool,
+    slow_commit: SlowCommit,
+    auto_grow: Option<GrowthPolicy>,
     _marker: PhantomData<E>,
 }
 
@@ -415,6 +1266,15 @@ where
         self.open_with_permissions(path, 0o644)
     }
 
+    /// Fails with [Error::RemoteFilesystem] for an environment on a network file system, unless
+    /// allowed with [EnvironmentBuilder::set_allow_remote_fs()].
+    fn check_remote_fs(&self, remote: bool) -> Result<()> {
+        if remote && !self.allow_remote_fs {
+            return Err(Error::RemoteFilesystem);
+        }
+        Ok(())
+    }
+
     /// Open an environment with the provided UNIX permissions.
     ///
     /// The path may not contain the null character.
@@ -423,6 +1283,7 @@ where
         path: &Path,
         mode: ffi::mdbx_mode_t,
     ) -> Result<Environment<E>> {
+        self.check_remote_fs(is_remote_fs(path))?;
         let mut env: *mut ffi::MDBX_env = ptr::null_mut();
         unsafe {
             mdbx_result(ffi::mdbx_env_create(&mut env))?;
@@ -497,55 +1358,85 @@ where
         let mut env = Environment {
             env,
             txn_manager: None,
+            direct_commit: false,
+            op_context: self.op_context,
+            dbi_names: Default::default(),
+            dbi_cache: Default::default(),
+            dbi_handles: Default::default(),
+            commit_subscribers: Default::default(),
+            live_txns: Default::default(),
+            txn_registry: self.txn_tracking.then(Default::default),
+            latency_recorder: self.latency_recording.then(Default::default),
+            slow_commit: self.slow_commit.clone(),
+            auto_grow: self.auto_grow.clone(),
+            stale_readers: 0,
+            data_file: None,
+            fork_generation: fork_generation(),
             _marker: PhantomData,
         };
+        unsafe {
+            ffi::mdbx_env_set_userctx(env.env, &*env.dbi_names as *const _ as *mut c_void);
+        }
+        if self.reader_check {
+            env.stale_readers = env.reader_check()?;
+        }
+        if self.durability_check {
+            let path = if self.flags.no_sub_dir {
+                path.to_path_buf()
+            } else {
+                path.join("mdbx.dat")
+            };
+            let metadata = fs::metadata(&path).map_err(io_error)?;
+            env.data_file = Some(DataFile {
+                path,
+                dev: metadata.dev(),
+                ino: metadata.ino(),
+            });
+        }
 
         if let Mode::ReadWrite { .. } = self.flags.mode {
-            let (tx, rx) = std::sync::mpsc::sync_channel(0);
-            let e = EnvPtr(env.env);
-            std::thread::spawn(move || loop {
-                match rx.recv() {
-                    Ok(msg) => match msg {
-                        TxnManagerMessage::Begin {
-                            parent,
-                            flags,
-                            sender,
-                        } => {
-                            let e = e;
-                            let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
-                            sender
-                                .send(
-                                    mdbx_result(unsafe {
-                                        ffi::mdbx_txn_begin_ex(
-                                            e.0,
-                                            parent.0,
-                                            flags,
-                                            &mut txn,
-                                            ptr::null_mut(),
-                                        )
-                                    })
-                                    .map(|_| TxnPtr(txn)),
-                                )
-                                .unwrap()
-                        }
-                        TxnManagerMessage::Abort { tx, sender } => {
-                            sender
-                                .send(mdbx_result(unsafe { ffi::mdbx_txn_abort(tx.0) }))
-                                .unwrap();
-                        }
-                        TxnManagerMessage::Commit { tx, sender } => {
-                            sender
-                                .send(mdbx_result(unsafe {
-                                    ffi::mdbx_txn_commit_ex(tx.0, ptr::null_mut())
-                                }))
-                                .unwrap();
-                        }
-                    },
-                    Err(_) => return,
-                }
-            });
+            if self.direct_commit {
+                env.direct_commit = true;
+            } else {
+                let (tx, rx) = std::sync::mpsc::sync_channel(0);
+                let e = EnvPtr(env.env);
+                let subscribers = env.commit_subscribers.clone();
+                let latency_recorder = env.latency_recorder.clone();
+                let slow_commit = env.slow_commit.clone();
+                std::thread::spawn(move || loop {
+                    match rx.recv() {
+                        Ok(msg) => match msg {
+                            TxnManagerMessage::Begin {
+                                parent,
+                                flags,
+                                sender,
+                            } => {
+                                let e = e;
+                                sender.send(begin_txn(e.0, parent.0, flags)).unwrap()
+                            }
+                            TxnManagerMessage::Abort { tx, sender } => {
+                                sender
+                                    .send(mdbx_result(unsafe { ffi::mdbx_txn_abort(tx.0) }))
+                                    .unwrap();
+                            }
+                            TxnManagerMessage::Commit { tx, nested, sender } => {
+                                sender
+                                    .send(commit_txn(
+                                        tx.0,
+                                        nested,
+                                        &subscribers,
+                                        latency_recorder.as_deref(),
+                                        &slow_commit,
+                                    ))
+                                    .unwrap();
+                            }
+                        },
+                        Err(_) => return,
+                    }
+                });
 
-            env.txn_manager = Some(tx);
+                env.txn_manager = Some(tx);
+            }
         }
 
         Ok(env)
@@ -611,6 +1502,105 @@ where
         self
     }
 
+    /// Records where each transaction was started, see [Environment::open_transactions()].
+    ///
+    /// This captures a backtrace for every transaction, so it is meant for debugging only.
+    pub fn set_txn_tracking(&mut self, enabled: bool) -> &mut Self {
+        self.txn_tracking = enabled;
+        self
+    }
+
+    /// Starts, commits and aborts read-write transactions on the calling thread instead of a
+    /// dedicated manager thread.
+    ///
+    /// MDBX releases its write lock on the thread which acquired it, so in this mode a top-level
+    /// read-write transaction must be committed or dropped on the thread which started it.
+    /// Otherwise committing it returns [Error::ThreadMismatch], and dropping it logs an error and
+    /// leaks it: the writer lock stays held, blocking every other write transaction, until the
+    /// environment is closed on the thread which started it, see [Environment::close()]. This is
+    /// only sound because the bundled MDBX is built with `MDBX_TXN_CHECKOWNER` disabled; with the
+    /// manager thread transactions may be moved freely.
+    pub fn set_direct_commit(&mut self, enabled: bool) -> &mut Self {
+        self.direct_commit = enabled;
+        self
+    }
+
+    /// Clears stale reader slots with [Environment::reader_check()] right after opening the
+    /// environment. The number of cleared slots is reported by
+    /// [Environment::stale_readers_cleared()].
+    pub fn set_reader_check(&mut self, enabled: bool) -> &mut Self {
+        self.reader_check = enabled;
+        self
+    }
+
+    /// Allows opening environments on network file systems such as NFS or SMB, which fails with
+    /// [Error::RemoteFilesystem] by default.
+    ///
+    /// MDBX relies on file locks and a coherent shared memory map, which these file systems do
+    /// not provide across hosts. Only enable this for environments opened by a single process,
+    /// with [EnvironmentFlags::exclusive], which MDBX requires on such file systems anyway.
+    pub fn set_allow_remote_fs(&mut self, enabled: bool) -> &mut Self {
+        self.allow_remote_fs = enabled;
+        self
+    }
+
+    /// Makes every top-level read-write commit check that it reached the storage: the
+    /// environment is synced with [Environment::sync()], forcibly, and the data file must still
+    /// be the one which was opened.
+    ///
+    /// A commit fails with [Error::SyncFailed] if the sync fails, or with [Error::StorageLost] if
+    /// the data file was deleted or replaced, e.g. along with its directory, in which case MDBX
+    /// keeps writing to the unlinked file and the data is lost once the environment is closed.
+    /// The transaction is committed anyway, so the environment should be closed and the data
+    /// restored. The check costs little with the default durable mode, which syncs on every
+    /// commit already.
+    pub fn set_durability_check(&mut self, enabled: bool) -> &mut Self {
+        self.durability_check = enabled;
+        self
+    }
+
+    /// Makes failed get, put, delete and cursor operations return an [Error::Op] naming the
+    /// operation, the database and the key, instead of the bare error.
+    ///
+    /// Missing items and decode errors, which carry a [DecodeErrorContext](crate::DecodeErrorContext)
+    /// already, are reported unchanged. With this enabled, errors should be matched through
+    /// [Error::kind()]; they still compare equal to the bare error.
+    pub fn set_op_context(&mut self, enabled: bool) -> &mut Self {
+        self.op_context = enabled;
+        self
+    }
+
+    /// Records the latency of every top-level read-write commit, see
+    /// [Environment::latency_recorder()].
+    pub fn set_latency_recording(&mut self, enabled: bool) -> &mut Self {
+        self.latency_recording = enabled;
+        self
+    }
+
+    /// Logs a warning with the breakdown of the [CommitLatency] of every top-level read-write
+    /// commit which takes longer than `threshold`, typically because of a stalled sync to disk.
+    pub fn set_slow_commit_threshold(&mut self, threshold: Duration) -> &mut Self {
+        self.slow_commit.threshold = Some(threshold);
+        self
+    }
+
+    /// Calls `callback` with the transaction id and latency of slow commits instead of logging
+    /// them, see [EnvironmentBuilder::set_slow_commit_threshold()].
+    pub fn set_slow_commit_callback(
+        &mut self,
+        callback: impl Fn(u64, &CommitLatency) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.slow_commit.callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Lets [Environment::update_retrying()] raise the upper bound of the map size and retry
+    /// transactions which run out of space.
+    pub fn set_auto_grow(&mut self, policy: GrowthPolicy) -> &mut Self {
+        self.auto_grow = Some(policy);
+        self
+    }
+
     /// Set all size-related parameters of environment, including page size and the min/max size of the memory map.
     pub fn set_geometry<R: RangeBounds<usize>>(&mut self, geometry: Geometry<R>) -> &mut Self {
         let convert_bound = |bound: Bound<&usize>| match bound {
@@ -631,3 +1621,32 @@ where
         self
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_remote_fs_magic() {
+        assert!(is_remote_fs_magic(0x0000_6969));
+        assert!(is_remote_fs_magic(0xfe53_4d42));
+        // ext4 and tmpfs
+        assert!(!is_remote_fs_magic(0x0000_ef53));
+        assert!(!is_remote_fs_magic(0x0102_1994));
+        // proc
+        assert!(!is_remote_fs(Path::new("/proc")));
+    }
+
+    #[test]
+    fn test_allow_remote_fs() {
+        let mut builder = Environment::<NoWriteMap>::new();
+        assert!(builder.check_remote_fs(false).is_ok());
+        assert!(matches!(
+            builder.check_remote_fs(true),
+            Err(Error::RemoteFilesystem)
+        ));
+        builder.set_allow_remote_fs(true);
+        assert!(builder.check_remote_fs(true).is_ok());
+    }
+}
@@ -1,7 +1,11 @@
-use libc::c_int;
-use std::{ffi::CStr, fmt, result, str};
+use crate::{Database, EnvironmentKind, Transaction, TransactionKind};
+use libc::{c_char, c_int, c_void};
+use std::{ffi::CStr, fmt, io, mem, result, str};
 
 /// An MDBX error kind.
+///
+/// Errors are compared by kind, and [Error::DecodeError] and [Error::EncodeError] by the message
+/// of their source. They are not [Clone] as the source is not.
 #[derive(Debug)]
 pub enum Error {
     KeyExist,
@@ -23,6 +27,16 @@ pub enum Error {
     BadRslot,
     BadTxn,
     BadValSize,
+    /// A key is longer than the database allows, checked before calling into MDBX.
+    KeyTooLarge {
+        len: usize,
+        max: usize,
+    },
+    /// A value is longer than the database allows, checked before calling into MDBX.
+    ValueTooLarge {
+        len: usize,
+        max: usize,
+    },
     BadDbi,
     Problem,
     Busy,
@@ -32,7 +46,46 @@ pub enum Error {
     InvalidValue,
     Access,
     TooLarge,
+    /// A handle such as a transaction or a cursor was used after being freed.
+    BadSign,
+    NoMemory,
+    /// The environment is on a read-only file system.
+    ReadOnly,
+    NotSupported,
+    Io,
+    NotPermitted,
+    Interrupted,
+    FileNotFound,
+    /// The environment is on a network file system. Such file systems do not provide the locking
+    /// and coherency MDBX relies on, so sharing an environment over them corrupts it. See
+    /// [EnvironmentBuilder::set_allow_remote_fs()](crate::EnvironmentBuilder::set_allow_remote_fs).
+    /// Only raised by the check made when opening an environment.
+    RemoteFilesystem,
+    /// A thread used a transaction it does not own, with the id of the transaction if known.
+    ThreadMismatch(Option<u64>),
+    /// A read transaction was started by a thread with an open write transaction, with the id of
+    /// the write transaction if known.
+    TxnOverlapping(Option<u64>),
+    /// A database handle could not be closed as other [Database] or [Cursor](crate::Cursor)
+    /// instances reference it.
+    DatabaseBusy,
+    /// The data file of the environment was deleted or replaced, see
+    /// [EnvironmentBuilder::set_durability_check()](crate::EnvironmentBuilder::set_durability_check).
+    StorageLost,
+    /// A commit could not be synced to the storage, with the error code of the sync, see
+    /// [EnvironmentBuilder::set_durability_check()](crate::EnvironmentBuilder::set_durability_check).
+    SyncFailed(c_int),
+    /// A transaction was committed after being poisoned, see
+    /// [Transaction::is_poisoned()](crate::Transaction::is_poisoned).
+    Poisoned,
+    /// The environment was opened by a parent of the current process, see
+    /// [Environment#fork-safety](crate::Environment#fork-safety).
+    Inherited,
+    /// An error together with the operation, database and key it occurred with, see
+    /// [EnvironmentBuilder::set_op_context()](crate::EnvironmentBuilder::set_op_context).
+    Op(Box<OpError>),
     DecodeError(Box<dyn std::error::Error + Send + Sync + 'static>),
+    EncodeError(Box<dyn std::error::Error + Send + Sync + 'static>),
     Other(c_int),
 }
 
@@ -68,15 +121,35 @@ impl Error {
             ffi::MDBX_EINVAL => Error::InvalidValue,
             ffi::MDBX_EACCESS => Error::Access,
             ffi::MDBX_TOO_LARGE => Error::TooLarge,
+            ffi::MDBX_EBADSIGN => Error::BadSign,
+            ffi::MDBX_ENOMEM => Error::NoMemory,
+            ffi::MDBX_EROFS => Error::ReadOnly,
+            ffi::MDBX_ENOSYS => Error::NotSupported,
+            ffi::MDBX_EIO => Error::Io,
+            ffi::MDBX_EPERM => Error::NotPermitted,
+            ffi::MDBX_EINTR => Error::Interrupted,
+            ffi::MDBX_ENOFILE => Error::FileNotFound,
+            ffi::MDBX_THREAD_MISMATCH => Error::ThreadMismatch(None),
+            ffi::MDBX_TXN_OVERLAPPING => Error::TxnOverlapping(None),
             other => Error::Other(other),
         }
     }
 
-    /// Converts an [Error] to the raw error code.
-    fn to_err_code(&self) -> c_int {
-        match self {
+    /// Converts an [Error] to the raw error code, or [ffi::MDBX_PROBLEM] for errors raised by
+    /// this crate.
+    pub(crate) fn to_err_code(&self) -> c_int {
+        self.code().unwrap_or(ffi::MDBX_PROBLEM)
+    }
+}
+
+impl Error {
+    /// Returns the raw MDBX error code, or [None] for errors raised by this crate, e.g. by
+    /// encoding or decoding.
+    pub fn code(&self) -> Option<c_int> {
+        Some(match self {
             Error::KeyExist => ffi::MDBX_KEYEXIST,
             Error::NotFound => ffi::MDBX_NOTFOUND,
+            Error::NoData => ffi::MDBX_ENODATA,
             Error::PageNotFound => ffi::MDBX_PAGE_NOTFOUND,
             Error::Corrupted => ffi::MDBX_CORRUPTED,
             Error::Panic => ffi::MDBX_PANIC,
@@ -92,7 +165,9 @@ impl Error {
             Error::Incompatible => ffi::MDBX_INCOMPATIBLE,
             Error::BadRslot => ffi::MDBX_BAD_RSLOT,
             Error::BadTxn => ffi::MDBX_BAD_TXN,
-            Error::BadValSize => ffi::MDBX_BAD_VALSIZE,
+            Error::BadValSize | Error::KeyTooLarge { .. } | Error::ValueTooLarge { .. } => {
+                ffi::MDBX_BAD_VALSIZE
+            }
             Error::BadDbi => ffi::MDBX_BAD_DBI,
             Error::Problem => ffi::MDBX_PROBLEM,
             Error::Busy => ffi::MDBX_BUSY,
@@ -102,21 +177,116 @@ impl Error {
             Error::InvalidValue => ffi::MDBX_EINVAL,
             Error::Access => ffi::MDBX_EACCESS,
             Error::TooLarge => ffi::MDBX_TOO_LARGE,
-            Error::Other(err_code) => *err_code,
-            _ => unreachable!(),
+            Error::BadSign => ffi::MDBX_EBADSIGN,
+            Error::NoMemory => ffi::MDBX_ENOMEM,
+            Error::ReadOnly => ffi::MDBX_EROFS,
+            Error::NotSupported => ffi::MDBX_ENOSYS,
+            Error::Io => ffi::MDBX_EIO,
+            Error::NotPermitted => ffi::MDBX_EPERM,
+            Error::Interrupted => ffi::MDBX_EINTR,
+            Error::FileNotFound => ffi::MDBX_ENOFILE,
+            Error::ThreadMismatch(_) => ffi::MDBX_THREAD_MISMATCH,
+            Error::TxnOverlapping(_) => ffi::MDBX_TXN_OVERLAPPING,
+            Error::Other(err_code) | Error::SyncFailed(err_code) => *err_code,
+            Error::Op(e) => return e.code(),
+            Error::DecodeError(_)
+            | Error::EncodeError(_)
+            | Error::DatabaseBusy
+            | Error::StorageLost
+            | Error::RemoteFilesystem
+            | Error::Poisoned
+            | Error::Inherited => return None,
+        })
+    }
+}
+
+impl Error {
+    /// Returns the error without the context of an [Error::Op], to match on its kind.
+    pub fn kind(&self) -> &Error {
+        match self {
+            Error::Op(e) => &e.source,
+            other => other,
+        }
+    }
+
+    /// Returns whether the item or database looked for does not exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.kind(), Error::NotFound | Error::NoData)
+    }
+
+    /// Returns whether the operation may succeed if its transaction is retried, possibly after
+    /// waiting for other transactions or growing the map.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind(),
+            Error::Busy | Error::MapFull | Error::UnableExtendMapsize | Error::ReadersFull
+        )
+    }
+
+    /// Returns whether the error reports damaged data, in which case the database should be
+    /// checked and restored from a backup.
+    pub fn is_corruption(&self) -> bool {
+        matches!(
+            self.kind(),
+            Error::Corrupted | Error::PageNotFound | Error::Panic | Error::WannaRecovery
+        )
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.kind(), other.kind()) {
+            (Error::DecodeError(a), Error::DecodeError(b))
+            | (Error::EncodeError(a), Error::EncodeError(b)) => a.to_string() == b.to_string(),
+            (Error::ThreadMismatch(a), Error::ThreadMismatch(b))
+            | (Error::TxnOverlapping(a), Error::TxnOverlapping(b)) => a == b,
+            (Error::Other(a), Error::Other(b)) | (Error::SyncFailed(a), Error::SyncFailed(b)) => {
+                a == b
+            }
+            (Error::KeyTooLarge { len: a, max: x }, Error::KeyTooLarge { len: b, max: y })
+            | (Error::ValueTooLarge { len: a, max: x }, Error::ValueTooLarge { len: b, max: y }) => {
+                (a, x) == (b, y)
+            }
+            (a, b) => mem::discriminant(a) == mem::discriminant(b),
         }
     }
 }
 
+impl Eq for Error {}
+
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::DecodeError(reason) => write!(fmt, "{}", reason),
+            Error::DecodeError(reason) | Error::EncodeError(reason) => write!(fmt, "{}", reason),
+            Error::Op(e) => write!(fmt, "{}", e),
+            Error::DatabaseBusy => write!(fmt, "database handle is still in use"),
+            Error::StorageLost => write!(fmt, "data file of the environment was removed"),
+            Error::RemoteFilesystem => write!(
+                fmt,
+                "environment is on a network file system (network file systems may corrupt the \
+                 database, move it to a local disk or open it in exclusive mode with \
+                 EnvironmentBuilder::set_allow_remote_fs)"
+            ),
+            Error::Poisoned => write!(fmt, "transaction was poisoned and has been aborted"),
+            Error::Inherited => write!(fmt, "environment was opened by a parent process"),
             other => {
                 write!(fmt, "{}", unsafe {
                     let err = ffi::mdbx_strerror(other.to_err_code());
                     str::from_utf8_unchecked(CStr::from_ptr(err).to_bytes())
-                })
+                })?;
+                match other {
+                    Error::ThreadMismatch(Some(txnid)) | Error::TxnOverlapping(Some(txnid)) => {
+                        write!(fmt, " (conflicting transaction {})", txnid)
+                    }
+                    Error::SyncFailed(_) => write!(fmt, " (commit was not synced to the storage)"),
+                    Error::KeyTooLarge { len, max } => {
+                        write!(fmt, " (key of {} bytes, at most {} allowed)", len, max)
+                    }
+                    Error::ValueTooLarge { len, max } => {
+                        write!(fmt, " (value of {} bytes, at most {} allowed)", len, max)
+                    }
+                    _ => Ok(()),
+                }
             }
         }
     }
@@ -124,9 +294,245 @@ impl fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
+/// Maximum number of key bytes kept in a [DecodeErrorContext] or an [OpError].
+pub const DECODE_ERROR_MAX_KEY_LEN: usize = 64;
+
+/// Location of a value that failed to decode, wrapping the original decode error.
+///
+/// Read errors returned from [Transaction](crate::Transaction) and [Cursor](crate::Cursor)
+/// carry this as the source of [Error::DecodeError].
+#[derive(Debug)]
+pub struct DecodeErrorContext {
+    /// Name of the database, or [None] for the main database.
+    pub table: Option<String>,
+    /// Key of the entry, truncated to [DECODE_ERROR_MAX_KEY_LEN] bytes.
+    pub key: Vec<u8>,
+    /// Length of the full key.
+    pub key_len: usize,
+    /// Length of the value.
+    pub value_len: usize,
+    /// The original decode error.
+    pub source: Box<dyn std::error::Error + Send + Sync + 'static>,
+}
+
+impl DecodeErrorContext {
+    /// Renders the key as text if it is printable ASCII, or as hexadecimal between angle
+    /// brackets otherwise, as done by `mdbx_dump_val`.
+    pub fn key_dump(&self) -> String {
+        dump_key(&self.key)
+    }
+}
+
+fn dump_key(key: &[u8]) -> String {
+    let key = &key[..key.len().min(DECODE_ERROR_MAX_KEY_LEN)];
+    let val = ffi::MDBX_val {
+        iov_len: key.len(),
+        iov_base: key.as_ptr() as *mut c_void,
+    };
+    let mut buf = [0 as c_char; DECODE_ERROR_MAX_KEY_LEN * 2 + 4];
+    unsafe {
+        let dump = ffi::mdbx_dump_val(&val, buf.as_mut_ptr(), buf.len());
+        CStr::from_ptr(dump).to_string_lossy().into_owned()
+    }
+}
+
+impl fmt::Display for DecodeErrorContext {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "failed to decode entry in {} (key {}",
+            self.table.as_deref().unwrap_or("main database"),
+            self.key_dump()
+        )?;
+        if self.key.len() < self.key_len {
+            write!(fmt, "...")?;
+        }
+        write!(
+            fmt,
+            " of {} bytes, value of {} bytes): {}",
+            self.key_len, self.value_len, self.source
+        )
+    }
+}
+
+impl std::error::Error for DecodeErrorContext {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+impl Error {
+    /// Attaches the location of the entry to a [Error::DecodeError]. Other errors are returned
+    /// unchanged.
+    pub(crate) unsafe fn with_decode_context(
+        self,
+        txn: *const ffi::MDBX_txn,
+        dbi: ffi::MDBX_dbi,
+        key: &ffi::MDBX_val,
+        value: &ffi::MDBX_val,
+    ) -> Error {
+        match self {
+            Error::DecodeError(source) if !source.is::<DecodeErrorContext>() => {
+                let key = if key.iov_base.is_null() {
+                    &[][..]
+                } else {
+                    std::slice::from_raw_parts(key.iov_base as *const u8, key.iov_len)
+                };
+                Error::DecodeError(Box::new(DecodeErrorContext {
+                    table: crate::environment::dbi_name(txn, dbi),
+                    key: key[..key.len().min(DECODE_ERROR_MAX_KEY_LEN)].to_vec(),
+                    key_len: key.len(),
+                    value_len: value.iov_len,
+                    source,
+                }))
+            }
+            other => other,
+        }
+    }
+
+    /// Wraps the error into an [Error::Op]. Errors reporting a missing item, which are expected
+    /// by many operations, and decode errors, which carry a [DecodeErrorContext] already, are
+    /// returned unchanged.
+    pub(crate) unsafe fn with_op_context(
+        self,
+        txn: *const ffi::MDBX_txn,
+        dbi: ffi::MDBX_dbi,
+        operation: Operation,
+        key: &[u8],
+    ) -> Error {
+        match self {
+            Error::NotFound | Error::NoData | Error::DecodeError(_) | Error::Op(_) => self,
+            source => Error::Op(Box::new(OpError::new(txn, dbi, operation, key, source))),
+        }
+    }
+}
+
 /// An MDBX result.
 pub type Result<T> = result::Result<T, Error>;
 
+/// An operation which failed with an [OpError].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Get,
+    Put,
+    Del,
+    /// A cursor operation, e.g. positioning or iterating.
+    Cursor,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(match self {
+            Operation::Get => "get",
+            Operation::Put => "put",
+            Operation::Del => "del",
+            Operation::Cursor => "cursor operation",
+        })
+    }
+}
+
+/// An [Error] together with the operation, database and key it occurred with.
+///
+/// Attached to results with [ResultExt::op_context()], or to every failed operation as an
+/// [Error::Op] with
+/// [EnvironmentBuilder::set_op_context()](crate::EnvironmentBuilder::set_op_context).
+#[derive(Debug)]
+pub struct OpError {
+    pub operation: Operation,
+    /// Name of the database, or [None] for the main database.
+    pub table: Option<String>,
+    /// Key of the operation, truncated to [DECODE_ERROR_MAX_KEY_LEN] bytes.
+    pub key: Vec<u8>,
+    /// Length of the full key.
+    pub key_len: usize,
+    pub source: Error,
+}
+
+impl OpError {
+    pub(crate) unsafe fn new(
+        txn: *const ffi::MDBX_txn,
+        dbi: ffi::MDBX_dbi,
+        operation: Operation,
+        key: &[u8],
+        source: Error,
+    ) -> Self {
+        Self {
+            operation,
+            table: crate::environment::dbi_name(txn, dbi),
+            key: key[..key.len().min(DECODE_ERROR_MAX_KEY_LEN)].to_vec(),
+            key_len: key.len(),
+            source,
+        }
+    }
+
+    /// Returns the raw MDBX error code, see [Error::code()].
+    pub fn code(&self) -> Option<c_int> {
+        self.source.code()
+    }
+}
+
+impl fmt::Display for OpError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{} in {} failed (key {}",
+            self.operation,
+            self.table.as_deref().unwrap_or("main database"),
+            dump_key(&self.key)
+        )?;
+        if self.key.len() < self.key_len {
+            write!(fmt, "...")?;
+        }
+        write!(fmt, "): {}", self.source)
+    }
+}
+
+impl std::error::Error for OpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<OpError> for Error {
+    fn from(e: OpError) -> Self {
+        e.source
+    }
+}
+
+/// Attaches an [OpError] context to results of operations on a database.
+pub trait ResultExt<T> {
+    /// Wraps an error into an [OpError] naming the operation, the database and the key.
+    fn op_context<K, E>(
+        self,
+        txn: &Transaction<'_, K, E>,
+        db: &Database<'_>,
+        operation: Operation,
+        key: impl AsRef<[u8]>,
+    ) -> result::Result<T, OpError>
+    where
+        K: TransactionKind,
+        E: EnvironmentKind;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn op_context<K, E>(
+        self,
+        txn: &Transaction<'_, K, E>,
+        db: &Database<'_>,
+        operation: Operation,
+        key: impl AsRef<[u8]>,
+    ) -> result::Result<T, OpError>
+    where
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        self.map_err(|source| match source {
+            Error::Op(e) => *e,
+            source => unsafe { OpError::new(txn.txn(), db.dbi(), operation, key.as_ref(), source) },
+        })
+    }
+}
+
 pub fn mdbx_result(err_code: c_int) -> Result<bool> {
     match err_code {
         ffi::MDBX_SUCCESS => Ok(false),
@@ -135,6 +541,12 @@ pub fn mdbx_result(err_code: c_int) -> Result<bool> {
     }
 }
 
+/// Maps an I/O error to the error of its OS code, or to `EIO`.
+pub(crate) fn io_error(e: io::Error) -> Error {
+    Error::from_err_code(e.raw_os_error().unwrap_or(ffi::MDBX_EIO))
+}
+
+/// Maps a miss ([Error::NotFound] or [Error::NoData]) to `Ok(None)`, propagating every other error.
 #[macro_export]
 macro_rules! mdbx_try_optional {
     ($expr:expr) => {{
@@ -158,5 +570,127 @@ mod test {
             "MDBX_INVALID: File is not an MDBX file",
             Error::Invalid.to_string()
         );
+        assert!(matches!(
+            Error::from_err_code(ffi::MDBX_TXN_OVERLAPPING),
+            Error::TxnOverlapping(None)
+        ));
+        assert!(Error::TxnOverlapping(Some(42))
+            .to_string()
+            .ends_with("(conflicting transaction 42)"));
+
+        assert_eq!(Error::KeyExist.code(), Some(ffi::MDBX_KEYEXIST));
+        assert_eq!(Error::DecodeError("bad".into()).code(), None);
+
+        for code in [
+            ffi::MDBX_EBADSIGN,
+            ffi::MDBX_EIO,
+            ffi::MDBX_ENOFILE,
+            ffi::MDBX_ENODATA,
+        ] {
+            let error = Error::from_err_code(code);
+            assert!(!matches!(error, Error::Other(_)));
+            assert_eq!(error.code(), Some(code));
+        }
+        // Only raised by the check when opening an environment, MDBX_EREMOTE is ENOTBLK.
+        assert!(matches!(
+            Error::from_err_code(ffi::MDBX_EREMOTE),
+            Error::Other(_)
+        ));
+        assert_eq!(Error::RemoteFilesystem.code(), None);
+        assert!(Error::RemoteFilesystem
+            .to_string()
+            .contains("exclusive mode with EnvironmentBuilder::set_allow_remote_fs"));
+    }
+
+    #[test]
+    fn test_code() {
+        for code in [
+            ffi::MDBX_KEYEXIST,
+            ffi::MDBX_NOTFOUND,
+            ffi::MDBX_ENODATA,
+            ffi::MDBX_PAGE_NOTFOUND,
+            ffi::MDBX_CORRUPTED,
+            ffi::MDBX_PANIC,
+            ffi::MDBX_VERSION_MISMATCH,
+            ffi::MDBX_INVALID,
+            ffi::MDBX_MAP_FULL,
+            ffi::MDBX_DBS_FULL,
+            ffi::MDBX_READERS_FULL,
+            ffi::MDBX_TXN_FULL,
+            ffi::MDBX_CURSOR_FULL,
+            ffi::MDBX_PAGE_FULL,
+            ffi::MDBX_UNABLE_EXTEND_MAPSIZE,
+            ffi::MDBX_INCOMPATIBLE,
+            ffi::MDBX_BAD_RSLOT,
+            ffi::MDBX_BAD_TXN,
+            ffi::MDBX_BAD_VALSIZE,
+            ffi::MDBX_BAD_DBI,
+            ffi::MDBX_PROBLEM,
+            ffi::MDBX_BUSY,
+            ffi::MDBX_EMULTIVAL,
+            ffi::MDBX_WANNA_RECOVERY,
+            ffi::MDBX_EKEYMISMATCH,
+            ffi::MDBX_EINVAL,
+            ffi::MDBX_EACCESS,
+            ffi::MDBX_TOO_LARGE,
+            ffi::MDBX_EBADSIGN,
+            ffi::MDBX_ENOMEM,
+            ffi::MDBX_EROFS,
+            ffi::MDBX_ENOSYS,
+            ffi::MDBX_EIO,
+            ffi::MDBX_EPERM,
+            ffi::MDBX_EINTR,
+            ffi::MDBX_ENOFILE,
+            ffi::MDBX_THREAD_MISMATCH,
+            ffi::MDBX_TXN_OVERLAPPING,
+            // Not an MDBX error code.
+            12345,
+        ] {
+            let error = Error::from_err_code(code);
+            assert_eq!(error.code(), Some(code), "{error:?}");
+            let error = Error::Op(Box::new(OpError {
+                operation: Operation::Get,
+                table: None,
+                key: Vec::new(),
+                key_len: 0,
+                source: error,
+            }));
+            assert_eq!(error.code(), Some(code), "{error:?}");
+            assert_eq!(error.kind().code(), Some(code), "{error:?}");
+        }
+
+        for error in [
+            Error::DecodeError("bad".into()),
+            Error::EncodeError("bad".into()),
+            Error::DatabaseBusy,
+            Error::StorageLost,
+            Error::RemoteFilesystem,
+            Error::Poisoned,
+            Error::Inherited,
+        ] {
+            assert_eq!(error.code(), None, "{error:?}");
+            assert_eq!(error.to_err_code(), ffi::MDBX_PROBLEM);
+        }
+        assert_eq!(Error::SyncFailed(libc::ENOSPC).code(), Some(libc::ENOSPC));
+    }
+
+    #[test]
+    fn test_eq() {
+        assert_eq!(Error::from_err_code(ffi::MDBX_NOTFOUND), Error::NotFound);
+        assert_ne!(Error::NotFound, Error::NoData);
+        assert_ne!(Error::Other(1), Error::Other(2));
+        assert_ne!(Error::TxnOverlapping(Some(1)), Error::TxnOverlapping(None));
+        assert_eq!(
+            Error::DecodeError("bad".into()),
+            Error::DecodeError("bad".into())
+        );
+        assert_ne!(
+            Error::DecodeError("bad".into()),
+            Error::EncodeError("bad".into())
+        );
+
+        assert!(Error::NoData.is_not_found());
+        assert!(Error::Busy.is_retryable() && !Error::Busy.is_corruption());
+        assert!(Error::Corrupted.is_corruption() && !Error::Corrupted.is_retryable());
     }
 }
@@ -1,6 +1,6 @@
 use bitflags::bitflags;
 use ffi::*;
-use libc::c_uint;
+use libc::{c_int, c_uint};
 
 /// MDBX sync mode
 #[derive(Clone, Copy, Debug)]
@@ -176,6 +176,21 @@ bitflags! {
     }
 }
 
+bitflags! {
+    #[doc="State of a database handle in a transaction, see [Transaction::db_state()](crate::Transaction::db_state)."]
+    #[derive(Default)]
+    pub struct DatabaseState: c_uint {
+        /// The database was modified by the transaction.
+        const DIRTY = MDBX_DBI_DIRTY as u32;
+        /// The record of the database may be out of date, and is reloaded on use.
+        const STALE = MDBX_DBI_STALE as u32;
+        /// The handle was opened by the transaction, so it was not used by it before.
+        const FRESH = MDBX_DBI_FRESH as u32;
+        /// The database was created by the transaction.
+        const CREAT = MDBX_DBI_CREAT as u32;
+    }
+}
+
 bitflags! {
     #[doc="Write options."]
     #[derive(Default)]
@@ -191,3 +206,25 @@ bitflags! {
         const MULTIPLE = MDBX_MULTIPLE as u32;
     }
 }
+
+bitflags! {
+    #[doc="Runtime debug options of MDBX, see [setup_debug()](crate::setup_debug)."]
+    #[derive(Default)]
+    pub struct DebugFlags: c_int {
+        /// Enable assertion checks. Requires a build of MDBX with `MDBX_DEBUG`.
+        const ASSERT = MDBX_DBG_ASSERT;
+        /// Audit the page accounting on every commit. Requires a build of MDBX with `MDBX_DEBUG`.
+        const AUDIT = MDBX_DBG_AUDIT;
+        /// Yield the CPU at random points to shake out races. Requires a build of MDBX with
+        /// `MDBX_DEBUG`.
+        const JITTER = MDBX_DBG_JITTER;
+        /// Include the content of the database in core dumps.
+        const DUMP = MDBX_DBG_DUMP;
+        /// Allow opening the same environment more than once in a process.
+        const LEGACY_MULTIOPEN = MDBX_DBG_LEGACY_MULTIOPEN;
+        /// Allow read and write transactions of the same thread to overlap.
+        const LEGACY_OVERLAP = MDBX_DBG_LEGACY_OVERLAP;
+        /// Do not upgrade the format of a database opened in read-write mode.
+        const DONT_UPGRADE = MDBX_DBG_DONT_UPGRADE;
+    }
+}
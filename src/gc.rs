@@ -0,0 +1,123 @@
+use crate::{
+    database::Database, Environment, EnvironmentKind, Error, Result, Transaction, TransactionKind,
+};
+use byteorder::{ByteOrder, NativeEndian};
+use std::{mem::size_of, ops::Range};
+
+/// A record of the garbage collector: the pages freed by one transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GcRecord {
+    /// Id of the transaction which freed the pages.
+    pub txnid: u64,
+    /// Number of pages freed.
+    pub pages: u64,
+    /// Runs of consecutive page numbers, in ascending order.
+    pub ranges: Vec<Range<u64>>,
+}
+
+/// Content of the garbage collector, as returned by [Transaction::gc_info()].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GcInfo {
+    /// Records in ascending order of transaction id.
+    pub records: Vec<GcRecord>,
+    /// Id of the oldest snapshot still in use by a reader. Pages freed by this or a later
+    /// transaction cannot be reused yet.
+    pub oldest_reader_txnid: u64,
+}
+
+impl GcInfo {
+    /// Total number of free pages.
+    pub fn pages(&self) -> u64 {
+        self.records.iter().map(|record| record.pages).sum()
+    }
+
+    /// Records whose pages are kept from reuse by readers of an old snapshot.
+    pub fn retained(&self) -> impl Iterator<Item = &GcRecord> {
+        self.records
+            .iter()
+            .filter(move |record| record.txnid >= self.oldest_reader_txnid)
+    }
+
+    /// Number of free pages kept from reuse by readers of an old snapshot.
+    pub fn retained_pages(&self) -> u64 {
+        self.retained().map(|record| record.pages).sum()
+    }
+}
+
+/// Parses a record of the garbage collector: a count followed by as many page numbers.
+pub(crate) fn parse_pnl(record: &[u8]) -> Option<impl Iterator<Item = u64> + '_> {
+    const PGNO: usize = size_of::<u32>();
+    let count = NativeEndian::read_u32(record.get(..PGNO)?) as usize;
+    if record.len() != (count + 1) * PGNO {
+        return None;
+    }
+    Some(
+        record[PGNO..]
+            .chunks_exact(PGNO)
+            .map(|pgno| NativeEndian::read_u32(pgno) as u64),
+    )
+}
+
+fn into_ranges(mut pgnos: Vec<u64>) -> Vec<Range<u64>> {
+    pgnos.sort_unstable();
+    let mut ranges: Vec<Range<u64>> = Vec::new();
+    for pgno in pgnos {
+        match ranges.last_mut() {
+            Some(range) if range.end == pgno => range.end += 1,
+            _ => ranges.push(pgno..pgno + 1),
+        }
+    }
+    ranges
+}
+
+impl<'env, K, E> Transaction<'env, K, E>
+where
+    K: TransactionKind,
+    E: EnvironmentKind,
+{
+    /// Lists the records of the garbage collector in the snapshot of this transaction.
+    pub fn gc_info(&self) -> Result<GcInfo> {
+        let info = unsafe { self.env().info_for(self.txn())? };
+        let gc = Database::freelist_db();
+        let mut records = Vec::new();
+        for item in self.cursor(&gc)?.iter_start::<[u8; 8], Vec<u8>>() {
+            let (txnid, record) = item?;
+            let pgnos: Vec<u64> = parse_pnl(&record).ok_or(Error::Corrupted)?.collect();
+            records.push(GcRecord {
+                txnid: u64::from_ne_bytes(txnid),
+                pages: pgnos.len() as u64,
+                ranges: into_ranges(pgnos),
+            });
+        }
+        Ok(GcInfo {
+            records,
+            oldest_reader_txnid: info.latter_reader_txnid() as u64,
+        })
+    }
+}
+
+impl<E> Environment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Lists the records of the garbage collector in a new read-only transaction.
+    ///
+    /// See [Transaction::gc_info()].
+    pub fn gc_info(&self) -> Result<GcInfo> {
+        self.begin_ro_txn()?.gc_info()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_into_ranges() {
+        assert_eq!(into_ranges(vec![]), vec![]);
+        assert_eq!(
+            into_ranges(vec![9, 3, 4, 8, 5, 12]),
+            vec![3..6, 8..10, 12..13]
+        );
+    }
+}
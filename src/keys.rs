@@ -0,0 +1,659 @@
+//! Encoding of numeric keys into byte strings which sort in numeric order.
+//!
+//! The `to_key()` methods return unsigned integers to be stored in native byte order in
+//! databases opened with [DatabaseFlags::INTEGER_KEY](crate::DatabaseFlags::INTEGER_KEY).
+//! For databases with the default lexicographic comparator use the big-endian encodings instead.
+//! [IntegerTable] wraps such a database so that only keys of the right type can be used.
+//!
+//! Keys made of several fields can be built with [KeyBuilder] and decoded with [KeyReader].
+//! Tuples of [Encodable] and [TableObject] types use the same encoding: every field but the
+//! last one is written as [KeyBuilder::bytes()], and the last one as-is.
+
+use crate::{
+    Cursor, Database, DatabaseFlags, Encodable, EnvironmentKind, Error, SmallKey, TableObject,
+    Transaction, TransactionKind, WriteFlags, RW,
+};
+use libc::c_void;
+use std::{borrow::Cow, marker::PhantomData};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Error)]
+#[error("invalid key size: expected {expected} bytes, got {got}")]
+struct InvalidKeySize {
+    expected: usize,
+    got: usize,
+}
+
+fn fixed<const LEN: usize>(key: &[u8]) -> Result<[u8; LEN], Error> {
+    key.try_into().map_err(|_| {
+        Error::DecodeError(Box::new(InvalidKeySize {
+            expected: LEN,
+            got: key.len(),
+        }))
+    })
+}
+
+fn key_val(key: &[u8]) -> ffi::MDBX_val {
+    ffi::MDBX_val {
+        iov_len: key.len(),
+        iov_base: key.as_ptr() as *mut c_void,
+    }
+}
+
+/// A [f64] key ordered by its numeric value, with negative numbers sorting before positive ones.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct OrderedF64(pub f64);
+
+impl OrderedF64 {
+    /// Returns the key as an unsigned integer with the same ordering.
+    pub fn to_key(self) -> u64 {
+        unsafe { ffi::mdbx_key_from_double(self.0) }
+    }
+
+    /// Restores the value from a key created by [OrderedF64::to_key()].
+    pub fn from_key(key: u64) -> Self {
+        Self(unsafe { ffi::mdbx_double_from_key(key_val(&key.to_ne_bytes())) })
+    }
+
+    /// Encodes the key for a lexicographically sorted database.
+    pub fn to_be_bytes(self) -> [u8; 8] {
+        self.to_key().to_be_bytes()
+    }
+
+    /// Decodes a key created by [OrderedF64::to_be_bytes()].
+    pub fn from_be_bytes(key: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_key(u64::from_be_bytes(fixed(key)?)))
+    }
+}
+
+/// Decodes keys of databases opened with [DatabaseFlags::INTEGER_KEY](crate::DatabaseFlags::INTEGER_KEY).
+impl<'tx> TableObject<'tx> for OrderedF64 {
+    fn decode(data_val: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_key(u64::from_ne_bytes(fixed(data_val)?)))
+    }
+}
+
+/// A [f32] key ordered by its numeric value, with negative numbers sorting before positive ones.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct OrderedF32(pub f32);
+
+impl OrderedF32 {
+    /// Returns the key as an unsigned integer with the same ordering.
+    pub fn to_key(self) -> u32 {
+        unsafe { ffi::mdbx_key_from_float(self.0) }
+    }
+
+    /// Restores the value from a key created by [OrderedF32::to_key()].
+    pub fn from_key(key: u32) -> Self {
+        Self(unsafe { ffi::mdbx_float_from_key(key_val(&key.to_ne_bytes())) })
+    }
+
+    /// Encodes the key for a lexicographically sorted database.
+    pub fn to_be_bytes(self) -> [u8; 4] {
+        self.to_key().to_be_bytes()
+    }
+
+    /// Decodes a key created by [OrderedF32::to_be_bytes()].
+    pub fn from_be_bytes(key: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_key(u32::from_be_bytes(fixed(key)?)))
+    }
+}
+
+/// Decodes keys of databases opened with [DatabaseFlags::INTEGER_KEY](crate::DatabaseFlags::INTEGER_KEY).
+impl<'tx> TableObject<'tx> for OrderedF32 {
+    fn decode(data_val: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_key(u32::from_ne_bytes(fixed(data_val)?)))
+    }
+}
+
+/// A JSON integer key (a signed integer which is exactly representable as a [f64]), ordered
+/// consistently with [OrderedF64].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JsonInt(pub i64);
+
+impl JsonInt {
+    /// Returns the key as an unsigned integer with the same ordering.
+    pub fn to_key(self) -> u64 {
+        unsafe { ffi::mdbx_key_from_jsonInteger(self.0) }
+    }
+
+    /// Restores the value from a key created by [JsonInt::to_key()].
+    pub fn from_key(key: u64) -> Self {
+        // libmdbx decodes the key of zero as `i64::MAX`.
+        if key == Self(0).to_key() {
+            return Self(0);
+        }
+        Self(unsafe { ffi::mdbx_jsonInteger_from_key(key_val(&key.to_ne_bytes())) })
+    }
+
+    /// Encodes the key for a lexicographically sorted database.
+    pub fn to_be_bytes(self) -> [u8; 8] {
+        self.to_key().to_be_bytes()
+    }
+
+    /// Decodes a key created by [JsonInt::to_be_bytes()].
+    pub fn from_be_bytes(key: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_key(u64::from_be_bytes(fixed(key)?)))
+    }
+}
+
+/// Decodes keys of databases opened with [DatabaseFlags::INTEGER_KEY](crate::DatabaseFlags::INTEGER_KEY).
+impl<'tx> TableObject<'tx> for JsonInt {
+    fn decode(data_val: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_key(u64::from_ne_bytes(fixed(data_val)?)))
+    }
+}
+
+/// Encodes a [u32] so that lexicographic order matches [DatabaseFlags::INTEGER_KEY](crate::DatabaseFlags::INTEGER_KEY) order.
+pub fn u32_to_be_key(value: u32) -> [u8; 4] {
+    value.to_be_bytes()
+}
+
+/// Decodes a key created by [u32_to_be_key()].
+pub fn u32_from_be_key(key: &[u8]) -> Result<u32, Error> {
+    Ok(u32::from_be_bytes(fixed(key)?))
+}
+
+/// Encodes a [u64] so that lexicographic order matches [DatabaseFlags::INTEGER_KEY](crate::DatabaseFlags::INTEGER_KEY) order.
+pub fn u64_to_be_key(value: u64) -> [u8; 8] {
+    value.to_be_bytes()
+}
+
+/// Decodes a key created by [u64_to_be_key()].
+pub fn u64_from_be_key(key: &[u8]) -> Result<u64, Error> {
+    Ok(u64::from_be_bytes(fixed(key)?))
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A key of a [DatabaseFlags::INTEGER_KEY] database, which MDBX requires to be a 4 or 8 byte
+/// unsigned integer in native byte order.
+///
+/// This trait is sealed, and implemented for [u32], [u64] and the ordered keys of this module.
+pub trait IntKey: Copy + private::Sealed {
+    type Bytes: AsRef<[u8]>;
+
+    fn to_ne_key(self) -> Self::Bytes;
+
+    fn from_ne_key(key: &[u8]) -> Result<Self, Error>;
+}
+
+macro_rules! impl_int_key {
+    ($t:ty, $len:literal, $to:expr, $from:expr) => {
+        impl private::Sealed for $t {}
+
+        impl IntKey for $t {
+            type Bytes = [u8; $len];
+
+            fn to_ne_key(self) -> Self::Bytes {
+                ($to)(self).to_ne_bytes()
+            }
+
+            fn from_ne_key(key: &[u8]) -> Result<Self, Error> {
+                Ok(($from)(fixed(key)?))
+            }
+        }
+    };
+}
+
+impl_int_key!(u32, 4, |v| v, u32::from_ne_bytes);
+impl_int_key!(u64, 8, |v| v, u64::from_ne_bytes);
+impl_int_key!(OrderedF32, 4, OrderedF32::to_key, |key| {
+    OrderedF32::from_key(u32::from_ne_bytes(key))
+});
+impl_int_key!(OrderedF64, 8, OrderedF64::to_key, |key| {
+    OrderedF64::from_key(u64::from_ne_bytes(key))
+});
+impl_int_key!(JsonInt, 8, JsonInt::to_key, |key| {
+    JsonInt::from_key(u64::from_ne_bytes(key))
+});
+
+/// A [DatabaseFlags::INTEGER_KEY] database whose keys are typed, so that keys of the wrong width
+/// or byte order are rejected at compile time rather than by MDBX with
+/// [Error::BadValSize](crate::Error::BadValSize), or silently sorted in the wrong order.
+#[derive(Debug)]
+pub struct IntegerTable<'txn, Key> {
+    db: Database<'txn>,
+    _marker: PhantomData<fn(Key) -> Key>,
+}
+
+impl<'txn, Key> IntegerTable<'txn, Key>
+where
+    Key: IntKey,
+{
+    /// Opens the database `name`, creating it with [DatabaseFlags::INTEGER_KEY] added to `flags`
+    /// if necessary, see [Transaction::create_db()].
+    pub fn create<E>(
+        txn: &'txn Transaction<'_, RW, E>,
+        name: Option<&str>,
+        flags: DatabaseFlags,
+    ) -> Result<Self, Error>
+    where
+        E: EnvironmentKind,
+    {
+        Self::new(
+            txn,
+            txn.create_db(name, flags | DatabaseFlags::INTEGER_KEY)?,
+        )
+    }
+
+    /// Opens the database `name`, see [Transaction::open_db()].
+    pub fn open<K, E>(txn: &'txn Transaction<'_, K, E>, name: Option<&str>) -> Result<Self, Error>
+    where
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        Self::new(txn, txn.open_db(name)?)
+    }
+
+    /// Wraps a database handle, returning [Error::Incompatible] if the database was not created
+    /// with [DatabaseFlags::INTEGER_KEY].
+    pub fn new<K, E>(txn: &'txn Transaction<'_, K, E>, db: Database<'txn>) -> Result<Self, Error>
+    where
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        if !txn.db_flags(&db)?.contains(DatabaseFlags::INTEGER_KEY) {
+            return Err(Error::Incompatible);
+        }
+        Ok(Self {
+            db,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn db(&self) -> &Database<'txn> {
+        &self.db
+    }
+
+    /// Gets the value of a key, see [Transaction::get()].
+    pub fn get<Value, K, E>(
+        &self,
+        txn: &'txn Transaction<'_, K, E>,
+        key: Key,
+    ) -> Result<Option<Value>, Error>
+    where
+        Value: TableObject<'txn>,
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        txn.get(&self.db, key.to_ne_key().as_ref())
+    }
+
+    /// Stores an item, see [Transaction::put()].
+    pub fn put<E>(
+        &self,
+        txn: &'txn Transaction<'_, RW, E>,
+        key: Key,
+        data: impl AsRef<[u8]>,
+        flags: WriteFlags,
+    ) -> Result<(), Error>
+    where
+        E: EnvironmentKind,
+    {
+        txn.put(&self.db, key.to_ne_key().as_ref(), data, flags)
+    }
+
+    /// Deletes an item, see [Transaction::del()].
+    pub fn del<E>(
+        &self,
+        txn: &'txn Transaction<'_, RW, E>,
+        key: Key,
+        data: Option<&[u8]>,
+    ) -> Result<bool, Error>
+    where
+        E: EnvironmentKind,
+    {
+        txn.del(&self.db, key.to_ne_key().as_ref(), data)
+    }
+
+    /// Iterates over the items in ascending key order.
+    pub fn iter<K, E>(
+        &self,
+        txn: &'txn Transaction<'_, K, E>,
+    ) -> Result<impl Iterator<Item = Result<(Key, Cow<'txn, [u8]>), Error>> + 'txn, Error>
+    where
+        K: TransactionKind,
+        E: EnvironmentKind,
+    {
+        let cursor: Cursor<'txn, K> = txn.cursor(&self.db)?;
+        Ok(cursor.into_iter().map(|item| {
+            let (key, value) = item?;
+            Ok((Key::from_ne_key(&key)?, value))
+        }))
+    }
+}
+
+/// Builds a composite key from several fields, so that keys sort field by field.
+///
+/// Each field is encoded in an order-preserving way: fixed-size integers in big-endian, varints
+/// with a length prefix byte, and byte strings with escaping and a terminator. Decode keys with
+/// [KeyReader], reading the fields in the same order.
+#[derive(Clone, Debug, Default)]
+pub struct KeyBuilder {
+    buf: SmallKey,
+}
+
+impl KeyBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a big-endian [u32].
+    pub fn u32(mut self, value: u32) -> Self {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// Appends a big-endian [u64].
+    pub fn u64(mut self, value: u64) -> Self {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// Appends a [u64] as a variable-length integer: one byte with the number of significant
+    /// bytes, followed by these bytes in big-endian. Small values take less space, while the
+    /// order is still numeric.
+    pub fn varint(mut self, value: u64) -> Self {
+        let len = 8 - value.leading_zeros() as usize / 8;
+        self.buf.push(len as u8);
+        self.buf.extend_from_slice(&value.to_be_bytes()[8 - len..]);
+        self
+    }
+
+    /// Appends a byte string of any length, which sorts lexicographically regardless of the
+    /// fields that follow it.
+    ///
+    /// Zero bytes are escaped as `00 ff` and the field is terminated by `00 01`.
+    pub fn bytes(mut self, value: &[u8]) -> Self {
+        for &b in value {
+            self.buf.push(b);
+            if b == 0 {
+                self.buf.push(0xff);
+            }
+        }
+        self.buf.extend_from_slice(&[0, 1]);
+        self
+    }
+
+    /// Appends a byte string prefixed by its length as a [varint](KeyBuilder::varint).
+    ///
+    /// Such fields sort by length first and by contents second, which is cheaper to decode than
+    /// [KeyBuilder::bytes()] but only lexicographic for strings of equal length.
+    pub fn len_prefixed(self, value: &[u8]) -> Self {
+        let mut this = self.varint(value.len() as u64);
+        this.buf.extend_from_slice(value);
+        this
+    }
+
+    /// Appends bytes as-is. Only suitable for fixed-size fields or the last field of a key.
+    pub fn raw(mut self, value: &[u8]) -> Self {
+        self.buf.extend_from_slice(value);
+        self
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf.into_vec()
+    }
+
+    /// Returns the key without allocating if it is at most 32 bytes long.
+    pub fn finish_small(self) -> SmallKey {
+        self.buf
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+#[error("malformed key field")]
+struct MalformedKey;
+
+fn malformed() -> Error {
+    Error::DecodeError(Box::new(MalformedKey))
+}
+
+/// Reads the fields of a key built by [KeyBuilder].
+#[derive(Clone, Debug)]
+pub struct KeyReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> KeyReader<'a> {
+    pub fn new(key: &'a [u8]) -> Self {
+        Self { data: key }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < len {
+            return Err(malformed());
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        u32_from_be_key(self.take(4)?)
+    }
+
+    pub fn u64(&mut self) -> Result<u64, Error> {
+        u64_from_be_key(self.take(8)?)
+    }
+
+    pub fn varint(&mut self) -> Result<u64, Error> {
+        let len = self.take(1)?[0] as usize;
+        if len > 8 {
+            return Err(malformed());
+        }
+        let mut buf = [0; 8];
+        buf[8 - len..].copy_from_slice(self.take(len)?);
+        Ok(u64::from_be_bytes(buf))
+    }
+
+    pub fn bytes(&mut self) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        loop {
+            match *self.take(1)? {
+                [0] => match *self.take(1)? {
+                    [0xff] => out.push(0),
+                    [1] => return Ok(out),
+                    _ => return Err(malformed()),
+                },
+                [b] => out.push(b),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    pub fn len_prefixed(&mut self) -> Result<&'a [u8], Error> {
+        let len = usize::try_from(self.varint()?).map_err(|_| malformed())?;
+        self.take(len)
+    }
+
+    /// Returns the remaining bytes.
+    pub fn rest(self) -> &'a [u8] {
+        self.data
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($field:ident . $idx:tt),+ ; $last:ident . $last_idx:tt) => {
+        impl<$($field,)+ $last> Encodable for ($($field,)+ $last)
+        where
+            $($field: Encodable,)+
+            $last: Encodable,
+        {
+            type Encoded = SmallKey;
+
+            fn encode(self) -> Self::Encoded {
+                KeyBuilder::new()
+                    $(.bytes(self.$idx.encode().as_ref()))+
+                    .raw(self.$last_idx.encode().as_ref())
+                    .finish_small()
+            }
+        }
+
+        /// Fields but the last one are decoded from a copy, so only the last one may borrow from
+        /// the database.
+        impl<'tx, $($field,)+ $last> TableObject<'tx> for ($($field,)+ $last)
+        where
+            $($field: TableObject<'tx>,)+
+            $last: TableObject<'tx>,
+        {
+            fn decode(data_val: &[u8]) -> Result<Self, Error> {
+                let mut reader = KeyReader::new(data_val);
+                Ok((
+                    $($field::decode(&reader.bytes()?)?,)+
+                    $last::decode(reader.rest())?,
+                ))
+            }
+
+            #[doc(hidden)]
+            unsafe fn decode_val<K: TransactionKind>(
+                txn: *const ffi::MDBX_txn,
+                data_val: &ffi::MDBX_val,
+            ) -> Result<Self, Error> {
+                let data = std::slice::from_raw_parts(data_val.iov_base as *const u8, data_val.iov_len);
+                let mut reader = KeyReader::new(data);
+                $(
+                    #[allow(non_snake_case)]
+                    let $field = $field::decode(&reader.bytes()?)?;
+                )+
+                let rest = reader.rest();
+                let rest = ffi::MDBX_val {
+                    iov_len: rest.len(),
+                    iov_base: rest.as_ptr() as *mut c_void,
+                };
+                Ok(($($field,)+ $last::decode_val::<K>(txn, &rest)?))
+            }
+        }
+    };
+}
+
+impl_tuple!(A.0; B.1);
+impl_tuple!(A.0, B.1; C.2);
+impl_tuple!(A.0, B.1, C.2; D.3);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ordered_f64() {
+        let values = [
+            f64::NEG_INFINITY,
+            -1e10,
+            -1.5,
+            -0.0,
+            0.5,
+            1.0,
+            1e10,
+            f64::INFINITY,
+        ];
+        for pair in values.windows(2) {
+            assert!(OrderedF64(pair[0]).to_be_bytes() < OrderedF64(pair[1]).to_be_bytes());
+            assert!(OrderedF64(pair[0]).to_key() < OrderedF64(pair[1]).to_key());
+        }
+        for value in values {
+            let key = OrderedF64(value).to_be_bytes();
+            assert_eq!(OrderedF64::from_be_bytes(&key).unwrap(), OrderedF64(value));
+        }
+    }
+
+    #[test]
+    fn test_ordered_f32() {
+        let values = [f32::NEG_INFINITY, -1e10, -1.5, 0.5, 1.0, f32::INFINITY];
+        for pair in values.windows(2) {
+            assert!(OrderedF32(pair[0]).to_be_bytes() < OrderedF32(pair[1]).to_be_bytes());
+        }
+        for value in values {
+            let key = OrderedF32(value).to_be_bytes();
+            assert_eq!(OrderedF32::from_be_bytes(&key).unwrap(), OrderedF32(value));
+        }
+    }
+
+    #[test]
+    fn test_json_int() {
+        let values = [-(1 << 53), -100, -1, 0, 1, 100, 1 << 53];
+        for pair in values.windows(2) {
+            assert!(JsonInt(pair[0]).to_be_bytes() < JsonInt(pair[1]).to_be_bytes());
+            assert_eq!(
+                JsonInt(pair[0]).to_key() < OrderedF64(1.5).to_key(),
+                pair[0] < 2
+            );
+        }
+        for value in values {
+            let key = JsonInt(value).to_be_bytes();
+            assert_eq!(JsonInt::from_be_bytes(&key).unwrap(), JsonInt(value));
+        }
+        assert!(JsonInt::from_be_bytes(&[0; 4]).is_err());
+    }
+
+    #[test]
+    fn test_composite_key() {
+        let key = |account: &[u8], block: u64| {
+            KeyBuilder::new()
+                .bytes(account)
+                .varint(block)
+                .u32(7)
+                .len_prefixed(b"tail")
+                .finish()
+        };
+
+        let keys = [
+            key(b"", 5),
+            key(b"\x00", 0),
+            key(b"\x00\x00", 1),
+            key(b"\x00\x01", 0),
+            key(b"a", 0),
+            key(b"a", 255),
+            key(b"a", 256),
+            key(b"a", u64::MAX),
+            key(b"a\x00", 0),
+            key(b"ab", 0),
+        ];
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+
+        let mut reader = KeyReader::new(&keys[7]);
+        assert_eq!(reader.bytes().unwrap(), b"a");
+        assert_eq!(reader.varint().unwrap(), u64::MAX);
+        assert_eq!(reader.u32().unwrap(), 7);
+        assert_eq!(reader.len_prefixed().unwrap(), b"tail");
+        assert!(reader.rest().is_empty());
+
+        let mut reader = KeyReader::new(&keys[2]);
+        assert_eq!(reader.bytes().unwrap(), b"\x00\x00");
+        assert_eq!(reader.varint().unwrap(), 1);
+
+        assert!(KeyReader::new(&[9]).varint().is_err());
+        assert!(KeyReader::new(b"a\x00").bytes().is_err());
+    }
+
+    #[test]
+    fn test_tuples() {
+        let keys = [
+            ("", 5u64).encode(),
+            ("\0", 0u64).encode(),
+            ("a", 1u64).encode(),
+            ("a", 256u64).encode(),
+            ("ab", 0u64).encode(),
+        ];
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+        assert_eq!(
+            <(String, u64)>::decode(&keys[3]).unwrap(),
+            ("a".to_string(), 256)
+        );
+
+        let key = (1u32, "name", 2u16).encode();
+        assert!(!key.spilled());
+        assert_eq!(
+            <(u32, String, u16)>::decode(&key).unwrap(),
+            (1, "name".to_string(), 2)
+        );
+        assert!(<(u32, u16)>::decode(&key).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! A key-value storage interface implemented by transactions and by an in-memory mock.
+//!
+//! Code written against [Kv] can be unit tested with [MemoryKv], which keeps its data in a
+//! [BTreeMap] and never calls into MDBX nor touches the file system, e.g. to run under Miri.
+//! Databases are addressed by name, [None] being the main database, and treated as plain maps
+//! ordered by the bytes of their keys.
+
+use crate::{DatabaseFlags, EnvironmentKind, Error, Result, Transaction, WriteFlags, RW};
+use parking_lot::Mutex;
+use std::{borrow::Cow, collections::BTreeMap, ops::Bound};
+
+type Items = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Basic operations of a read-write transaction.
+///
+/// Missing databases are read as empty, and created by the first write to them.
+pub trait Kv {
+    /// Gets the value of a key in database `db`.
+    fn get(&self, db: Option<&str>, key: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Stores an item into database `db`, replacing the value of the key if it exists.
+    fn put(&self, db: Option<&str>, key: &[u8], value: &[u8]) -> Result<()>;
+
+    /// Deletes a key from database `db`, returning whether it was present.
+    fn del(&self, db: Option<&str>, key: &[u8]) -> Result<bool>;
+
+    /// Returns the items of database `db` with keys inside `range`, in key order.
+    fn scan(&self, db: Option<&str>, range: (Bound<&[u8]>, Bound<&[u8]>)) -> Result<Items>;
+}
+
+impl<'env, E> Kv for Transaction<'env, RW, E>
+where
+    E: EnvironmentKind,
+{
+    fn get(&self, db: Option<&str>, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        match self.open_db(db) {
+            Ok(db) => self.get(&db, key),
+            Err(Error::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn put(&self, db: Option<&str>, key: &[u8], value: &[u8]) -> Result<()> {
+        let db = self.create_db(db, DatabaseFlags::empty())?;
+        self.put(&db, key, value, WriteFlags::empty())
+    }
+
+    fn del(&self, db: Option<&str>, key: &[u8]) -> Result<bool> {
+        match self.open_db(db) {
+            Ok(db) => self.del(&db, key, None),
+            Err(Error::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn scan(&self, db: Option<&str>, range: (Bound<&[u8]>, Bound<&[u8]>)) -> Result<Items> {
+        let db = match self.open_db(db) {
+            Ok(db) => db,
+            Err(Error::NotFound) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut cursor = self.cursor(&db)?;
+        let mut items = Vec::new();
+        let mut item = cursor.seek_range_start::<Cow<'_, [u8]>>(range.0)?;
+        while let Some((key, value)) = item {
+            if !cursor.before_range_end(&key, range.1) {
+                break;
+            }
+            items.push((key.into_owned(), value.into_owned()));
+            item = cursor.next()?;
+        }
+        Ok(items)
+    }
+}
+
+/// A [Kv] keeping its databases in memory, for tests.
+///
+/// Writes are visible immediately, as if made by a single transaction which is never committed.
+/// Unlike in MDBX, the main database does not list the named databases.
+#[derive(Debug, Default)]
+pub struct MemoryKv {
+    dbs: Mutex<BTreeMap<Option<String>, BTreeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl MemoryKv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Kv for MemoryKv {
+    fn get(&self, db: Option<&str>, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let dbs = self.dbs.lock();
+        Ok(dbs
+            .get(&db.map(ToString::to_string))
+            .and_then(|items| items.get(key))
+            .cloned())
+    }
+
+    fn put(&self, db: Option<&str>, key: &[u8], value: &[u8]) -> Result<()> {
+        self.dbs
+            .lock()
+            .entry(db.map(ToString::to_string))
+            .or_default()
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn del(&self, db: Option<&str>, key: &[u8]) -> Result<bool> {
+        let mut dbs = self.dbs.lock();
+        Ok(dbs
+            .get_mut(&db.map(ToString::to_string))
+            .is_some_and(|items| items.remove(key).is_some()))
+    }
+
+    fn scan(&self, db: Option<&str>, range: (Bound<&[u8]>, Bound<&[u8]>)) -> Result<Items> {
+        let dbs = self.dbs.lock();
+        let Some(items) = dbs.get(&db.map(ToString::to_string)) else {
+            return Ok(Vec::new());
+        };
+        Ok(items
+            .iter()
+            .skip_while(|(key, _)| before_range_start(key, range.0))
+            .take_while(|(key, _)| before_range_end(key, range.1))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+/// Returns whether `key` comes before the given start bound.
+fn before_range_start(key: &[u8], start: Bound<&[u8]>) -> bool {
+    match start {
+        Bound::Included(start) => key < start,
+        Bound::Excluded(start) => key <= start,
+        Bound::Unbounded => false,
+    }
+}
+
+/// Returns whether `key` satisfies the given end bound.
+fn before_range_end(key: &[u8], end: Bound<&[u8]>) -> bool {
+    match end {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
@@ -0,0 +1,232 @@
+use crate::environment::duration_16dot16;
+use parking_lot::Mutex;
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Number of buckets of a [LatencyHistogram]. Bucket `i` counts samples below `2^i`
+/// microseconds, the last one all longer samples.
+pub const LATENCY_BUCKETS: usize = 32;
+
+/// Durations of the phases of a commit, as measured by MDBX.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CommitLatency {
+    /// Preparation of the commit.
+    pub preparation: Duration,
+    /// Updating the garbage collector.
+    pub gc: Duration,
+    /// Internal audit, if enabled.
+    pub audit: Duration,
+    /// Writing dirty pages.
+    pub write: Duration,
+    /// Syncing to disk.
+    pub sync: Duration,
+    /// Releasing resources.
+    pub ending: Duration,
+    /// The whole commit.
+    pub whole: Duration,
+}
+
+impl CommitLatency {
+    pub(crate) fn from_mdbx(latency: &ffi::MDBX_commit_latency) -> Self {
+        Self {
+            preparation: duration_16dot16(latency.preparation),
+            gc: duration_16dot16(latency.gc),
+            audit: duration_16dot16(latency.audit),
+            write: duration_16dot16(latency.write),
+            sync: duration_16dot16(latency.sync),
+            ending: duration_16dot16(latency.ending),
+            whole: duration_16dot16(latency.whole),
+        }
+    }
+}
+
+impl fmt::Display for CommitLatency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} (preparation {:?}, gc {:?}, audit {:?}, write {:?}, sync {:?}, ending {:?})",
+            self.whole, self.preparation, self.gc, self.audit, self.write, self.sync, self.ending
+        )
+    }
+}
+
+/// Reports commits which take longer than a threshold, see
+/// [EnvironmentBuilder::set_slow_commit_threshold()](crate::EnvironmentBuilder::set_slow_commit_threshold).
+#[derive(Clone, Default)]
+pub(crate) struct SlowCommit {
+    pub(crate) threshold: Option<Duration>,
+    pub(crate) callback: Option<Arc<dyn Fn(u64, &CommitLatency) + Send + Sync>>,
+}
+
+impl SlowCommit {
+    pub(crate) fn check(&self, txnid: u64, latency: &CommitLatency) {
+        match self.threshold {
+            Some(threshold) if latency.whole > threshold => match &self.callback {
+                Some(callback) => callback(txnid, latency),
+                None => log::warn!(
+                    target: "libmdbx",
+                    "slow commit of transaction {txnid}: {latency}"
+                ),
+            },
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Debug for SlowCommit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowCommit")
+            .field("threshold", &self.threshold)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+/// Distribution of the samples of one phase of commits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Number of samples.
+    pub count: u64,
+    /// Sum of all samples.
+    pub sum: Duration,
+    /// Longest sample.
+    pub max: Duration,
+    /// Number of samples per bucket, see [LATENCY_BUCKETS].
+    pub buckets: [u64; LATENCY_BUCKETS],
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: Duration::ZERO,
+            max: Duration::ZERO,
+            buckets: [0; LATENCY_BUCKETS],
+        }
+    }
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, sample: Duration) {
+        let micros = sample.as_micros() as u64;
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+        self.buckets[bucket.min(LATENCY_BUCKETS - 1)] += 1;
+        self.count += 1;
+        self.sum += sample;
+        self.max = self.max.max(sample);
+    }
+
+    /// Average of all samples.
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos((self.sum.as_nanos() / self.count as u128) as u64)
+    }
+
+    /// Upper bound of the `q`-quantile of the samples, for `q` between 0 and 1.
+    ///
+    /// This is the upper bound of the bucket containing the quantile, capped by the longest
+    /// sample.
+    pub fn quantile(&self, q: f64) -> Duration {
+        let rank = (q.clamp(0.0, 1.0) * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Duration::from_micros(1 << i).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+/// Histograms of the phases of all commits recorded by a [LatencyRecorder].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LatencySnapshot {
+    pub preparation: LatencyHistogram,
+    pub gc: LatencyHistogram,
+    pub write: LatencyHistogram,
+    pub sync: LatencyHistogram,
+    pub whole: LatencyHistogram,
+}
+
+/// Accumulates the [CommitLatency] of every top-level read-write commit of an environment.
+///
+/// Enabled with
+/// [EnvironmentBuilder::set_latency_recording()](crate::EnvironmentBuilder::set_latency_recording)
+/// and reachable through
+/// [Environment::latency_recorder()](crate::Environment::latency_recorder).
+#[derive(Debug, Default)]
+pub struct LatencyRecorder {
+    histograms: Mutex<LatencySnapshot>,
+}
+
+impl LatencyRecorder {
+    pub(crate) fn record(&self, latency: &CommitLatency) {
+        let mut histograms = self.histograms.lock();
+        histograms.preparation.record(latency.preparation);
+        histograms.gc.record(latency.gc);
+        histograms.write.record(latency.write);
+        histograms.sync.record(latency.sync);
+        histograms.whole.record(latency.whole);
+    }
+
+    /// Returns the histograms of all commits recorded so far.
+    pub fn snapshot(&self) -> LatencySnapshot {
+        self.histograms.lock().clone()
+    }
+
+    /// Returns the histograms of all commits recorded so far and starts over.
+    pub fn reset(&self) -> LatencySnapshot {
+        std::mem::take(&mut *self.histograms.lock())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.quantile(0.5), Duration::ZERO);
+
+        for micros in [0, 3, 3, 100, 5000] {
+            histogram.record(Duration::from_micros(micros));
+        }
+        assert_eq!(histogram.count, 5);
+        assert_eq!(histogram.max, Duration::from_micros(5000));
+        assert_eq!(histogram.mean(), Duration::from_nanos(1_021_200));
+        assert_eq!(histogram.buckets[0], 1);
+        assert_eq!(histogram.buckets[2], 2);
+        assert_eq!(histogram.quantile(0.0), Duration::from_micros(1));
+        assert_eq!(histogram.quantile(0.5), Duration::from_micros(4));
+        assert_eq!(histogram.quantile(0.8), Duration::from_micros(128));
+        assert_eq!(histogram.quantile(1.0), Duration::from_micros(5000));
+
+        // More samples than fit into a u32.
+        histogram.count = 1 << 32;
+        histogram.sum = Duration::from_secs(3 << 32);
+        assert_eq!(histogram.mean(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_from_mdbx() {
+        let latency = CommitLatency::from_mdbx(&ffi::MDBX_commit_latency {
+            preparation: 0,
+            gc: 1 << 16,
+            audit: 0,
+            write: 1 << 15,
+            sync: 0,
+            ending: 0,
+            whole: 3 << 15,
+        });
+        assert_eq!(latency.gc, Duration::from_secs(1));
+        assert_eq!(latency.write, Duration::from_millis(500));
+        assert_eq!(latency.whole, Duration::from_millis(1500));
+        assert_eq!(
+            latency.to_string(),
+            "1.5s (preparation 0ns, gc 1s, audit 0ns, write 500ms, sync 0ns, ending 0ns)"
+        );
+    }
+}
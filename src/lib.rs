@@ -1,26 +1,73 @@
 #![allow(clippy::type_complexity)]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "async")]
+pub use crate::async_environment::{AsyncEnvironment, CursorStream};
+#[cfg(feature = "encryption")]
+pub use crate::encryption::{decrypt, encrypt, Aead, SecureTable};
 pub use crate::{
+    batch::{BatchReceipt, BatchWriter},
+    bulk::{BulkLoader, LoadStats},
+    check::{CheckOptions, CheckReport, Problem, ProblemKind},
     codec::*,
-    cursor::{Cursor, Iter, IterDup},
+    compression::{compress, decompress, Compression, Decompressed, COMPRESSION_MAGIC},
+    copy::CompactProgress,
+    cursor::{Cursor, DupCursor, Iter, IterBatched, IterDup, RetainStats},
     database::Database,
+    dump::DumpFormat,
     environment::{
-        Environment, EnvironmentBuilder, EnvironmentKind, Geometry, Info, NoWriteMap, Stat,
-        WriteMap,
+        CloseError, Environment, EnvironmentBuilder, EnvironmentKind, Geometry, GrowthPolicy, Info,
+        MetaInfo, NoWriteMap, Stat, TxnOrigin, WriteMap,
+    },
+    error::{
+        DecodeErrorContext, Error, OpError, Operation, Result, ResultExt, DECODE_ERROR_MAX_KEY_LEN,
     },
-    error::{Error, Result},
     flags::*,
-    transaction::{Transaction, TransactionKind, RO, RW},
+    gc::{GcInfo, GcRecord},
+    kv::{Kv, MemoryKv},
+    latency::{CommitLatency, LatencyHistogram, LatencyRecorder, LatencySnapshot, LATENCY_BUCKETS},
+    logging::{set_log_level, setup_debug, LogLevel},
+    readers::{ReaderInfo, ReaderMonitorOptions},
+    report::{DatabaseReport, SpaceReport, TableReport, TableSpace},
+    sample::{KeyDistribution, KeySample},
+    shard::{MergedIter, ShardRouting, ShardedEnvironment, ShardedTransaction},
+    transaction::{ArcRoTransaction, Transaction, TransactionKind, ValueGuard, RO, RW},
+    walk::{PageOwner, PageType, PageVisit},
 };
 
+#[cfg(feature = "async")]
+mod async_environment;
+mod batch;
+mod bulk;
+mod check;
 mod codec;
+mod compression;
+mod copy;
 mod cursor;
 mod database;
+pub mod diff;
+mod dump;
+#[cfg(feature = "encryption")]
+mod encryption;
 mod environment;
 mod error;
 mod flags;
+mod gc;
+pub mod keys;
+mod kv;
+mod latency;
+mod logging;
+mod readers;
+mod report;
+mod sample;
+mod shard;
+#[cfg(feature = "metrics")]
+mod telemetry;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 mod transaction;
+pub mod ttl;
+mod walk;
 
 #[cfg(test)]
 mod test_utils {
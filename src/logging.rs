@@ -0,0 +1,112 @@
+use crate::DebugFlags;
+use libc::{c_char, c_int};
+use std::ffi::CStr;
+
+/// Size of the buffer messages of MDBX are formatted into. Longer messages are truncated.
+const MESSAGE_BUFFER: usize = 1024;
+
+// How a `va_list` parameter is passed, which differs between platforms.
+#[cfg(all(target_arch = "x86_64", not(windows)))]
+type VaList = *mut ffi::__va_list_tag;
+#[cfg(not(all(target_arch = "x86_64", not(windows))))]
+type VaList = ffi::va_list;
+
+extern "C" {
+    fn vsnprintf(buf: *mut c_char, size: usize, fmt: *const c_char, args: VaList) -> c_int;
+}
+
+/// Verbosity of the diagnostics of MDBX, from least to most verbose.
+///
+/// Levels above the one MDBX was built with are not available, as these messages are compiled
+/// out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Fatal,
+    Error,
+    Warn,
+    Notice,
+    Verbose,
+    Debug,
+    Trace,
+    Extra,
+}
+
+impl LogLevel {
+    fn to_mdbx(self) -> ffi::MDBX_log_level_t {
+        match self {
+            LogLevel::Fatal => ffi::MDBX_LOG_FATAL,
+            LogLevel::Error => ffi::MDBX_LOG_ERROR,
+            LogLevel::Warn => ffi::MDBX_LOG_WARN,
+            LogLevel::Notice => ffi::MDBX_LOG_NOTICE,
+            LogLevel::Verbose => ffi::MDBX_LOG_VERBOSE,
+            LogLevel::Debug => ffi::MDBX_LOG_DEBUG,
+            LogLevel::Trace => ffi::MDBX_LOG_TRACE,
+            LogLevel::Extra => ffi::MDBX_LOG_EXTRA,
+        }
+    }
+}
+
+fn to_log_level(level: ffi::MDBX_log_level_t) -> log::Level {
+    match level {
+        ffi::MDBX_LOG_FATAL | ffi::MDBX_LOG_ERROR => log::Level::Error,
+        ffi::MDBX_LOG_WARN => log::Level::Warn,
+        ffi::MDBX_LOG_NOTICE => log::Level::Info,
+        ffi::MDBX_LOG_VERBOSE | ffi::MDBX_LOG_DEBUG => log::Level::Debug,
+        _ => log::Level::Trace,
+    }
+}
+
+unsafe extern "C" fn logger(
+    level: ffi::MDBX_log_level_t,
+    function: *const c_char,
+    _line: c_int,
+    fmt: *const c_char,
+    args: VaList,
+) {
+    let level = to_log_level(level);
+    if !log::log_enabled!(target: "libmdbx", level) {
+        return;
+    }
+
+    let mut buf = [0 as c_char; MESSAGE_BUFFER];
+    if vsnprintf(buf.as_mut_ptr(), buf.len(), fmt, args) < 0 {
+        return;
+    }
+    let message = CStr::from_ptr(buf.as_ptr()).to_string_lossy();
+    let message = message.trim_end();
+    if function.is_null() {
+        log::log!(target: "libmdbx", level, "{message}");
+    } else {
+        let function = CStr::from_ptr(function).to_string_lossy();
+        log::log!(target: "libmdbx", level, "{function}: {message}");
+    }
+}
+
+/// Forwards the diagnostics of MDBX up to `level` to the [log] crate, with `libmdbx` as the
+/// target.
+///
+/// This is global for the process. Messages are mapped to the closest [log::Level], e.g.
+/// [LogLevel::Notice] to [log::Level::Info].
+pub fn set_log_level(level: LogLevel) {
+    unsafe {
+        ffi::mdbx_setup_debug(level.to_mdbx(), ffi::MDBX_DBG_DONTCHANGE, Some(logger));
+    }
+}
+
+/// Sets the log level as [set_log_level()] does, along with the runtime debug options of MDBX,
+/// returning the options in effect afterwards.
+///
+/// This is global for the process. Options which the bundled MDBX was not built to support,
+/// e.g. [DebugFlags::AUDIT] without `MDBX_DEBUG`, are ignored and absent from the result.
+pub fn setup_debug(level: LogLevel, flags: DebugFlags) -> DebugFlags {
+    unsafe {
+        ffi::mdbx_setup_debug(level.to_mdbx(), flags.bits(), Some(logger));
+        // The previous flags are returned in the low byte, next to the log level.
+        let current = ffi::mdbx_setup_debug(
+            ffi::MDBX_LOG_DONTCHANGE,
+            ffi::MDBX_DBG_DONTCHANGE,
+            Some(logger),
+        );
+        DebugFlags::from_bits_truncate(current & 0xff)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{error::mdbx_result, Environment, EnvironmentKind, Result};
+use libc::{c_int, c_void};
+use std::{
+    sync::{Arc, Weak},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// A reader slot in use, as listed by [Environment::readers()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReaderInfo {
+    /// Index of the slot in the lock table.
+    pub slot: i32,
+    /// Process which owns the slot.
+    pub pid: i32,
+    /// Thread which owns the slot.
+    pub thread: u64,
+    /// Id of the snapshot being read, or zero if the slot is idle.
+    pub txnid: u64,
+    /// Number of transactions committed since the snapshot was taken.
+    pub lag: u64,
+    /// Bytes of the snapshot in use.
+    pub bytes_used: usize,
+    /// Bytes of freed pages kept from reuse by this reader.
+    pub bytes_retained: usize,
+}
+
+unsafe extern "C" fn list_reader(
+    ctx: *mut c_void,
+    _num: c_int,
+    slot: c_int,
+    pid: ffi::mdbx_pid_t,
+    thread: ffi::mdbx_tid_t,
+    txnid: u64,
+    lag: u64,
+    bytes_used: usize,
+    bytes_retained: usize,
+) -> c_int {
+    let readers = &mut *(ctx as *mut Vec<ReaderInfo>);
+    readers.push(ReaderInfo {
+        slot,
+        pid,
+        thread: thread as u64,
+        txnid,
+        lag,
+        bytes_used,
+        bytes_retained,
+    });
+    ffi::MDBX_SUCCESS
+}
+
+/// Options of [Environment::spawn_reader_monitor()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReaderMonitorOptions {
+    /// Readers lagging behind by more than this number of transactions are reported.
+    pub max_lag: u64,
+    /// Time between two inspections of the reader slots.
+    pub interval: Duration,
+    /// Clear the slots of readers from processes which exited with
+    /// [Environment::reader_check()] before each inspection.
+    pub clear_stale: bool,
+}
+
+impl Default for ReaderMonitorOptions {
+    fn default() -> Self {
+        Self {
+            max_lag: 1000,
+            interval: Duration::from_secs(10),
+            clear_stale: true,
+        }
+    }
+}
+
+impl<E> Environment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Lists the reader slots in use, by all processes.
+    pub fn readers(&self) -> Result<Vec<ReaderInfo>> {
+        let mut readers = Vec::new();
+        mdbx_result(unsafe {
+            ffi::mdbx_reader_list(
+                self.env(),
+                Some(list_reader),
+                &mut readers as *mut Vec<ReaderInfo> as *mut c_void,
+            )
+        })?;
+        Ok(readers)
+    }
+
+    /// Inspects the reader slots on a background thread every [ReaderMonitorOptions::interval],
+    /// calling `callback` for each reader lagging behind by more than
+    /// [ReaderMonitorOptions::max_lag] transactions, until the environment is dropped.
+    ///
+    /// Long-lived readers keep the pages freed after their snapshot from being reused, which
+    /// makes the database grow.
+    pub fn spawn_reader_monitor(
+        self: &Arc<Self>,
+        options: ReaderMonitorOptions,
+        mut callback: impl FnMut(&ReaderInfo) + Send + 'static,
+    ) -> JoinHandle<()> {
+        let env: Weak<Self> = Arc::downgrade(self);
+        thread::Builder::new()
+            .name("mdbx-reader-monitor".to_string())
+            .spawn(move || loop {
+                match env.upgrade() {
+                    Some(env) => {
+                        if options.clear_stale {
+                            let _ = env.reader_check();
+                        }
+                        for reader in env.readers().unwrap_or_default() {
+                            if reader.txnid != 0 && reader.lag > options.max_lag {
+                                callback(&reader);
+                            }
+                        }
+                    }
+                    None => return,
+                }
+                thread::sleep(options.interval);
+            })
+            .expect("failed to spawn reader monitor thread")
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    Environment, EnvironmentKind, Error, PageOwner, PageType, Result, Stat, Transaction,
+    TransactionKind,
+};
+use indexmap::IndexMap;
+use std::{cmp::Reverse, ops::ControlFlow};
+
+/// Space usage of a single database.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableReport {
+    /// Name of the database, or [None] for the main database.
+    pub name: Option<String>,
+    /// Number of data items.
+    pub entries: usize,
+    /// Depth (height) of the B-tree.
+    pub depth: u32,
+    /// Number of internal (non-leaf) pages.
+    pub branch_pages: usize,
+    /// Number of leaf pages.
+    pub leaf_pages: usize,
+    /// Number of overflow pages.
+    pub overflow_pages: usize,
+    /// Total size of all pages in bytes.
+    pub bytes: u64,
+}
+
+impl TableReport {
+    fn new(name: Option<String>, stat: &Stat) -> Self {
+        let pages = stat.branch_pages() + stat.leaf_pages() + stat.overflow_pages();
+        Self {
+            name,
+            entries: stat.entries(),
+            depth: stat.depth(),
+            branch_pages: stat.branch_pages(),
+            leaf_pages: stat.leaf_pages(),
+            overflow_pages: stat.overflow_pages(),
+            bytes: pages as u64 * stat.page_size() as u64,
+        }
+    }
+
+    /// Total number of pages used by the database.
+    pub fn pages(&self) -> usize {
+        self.branch_pages + self.leaf_pages + self.overflow_pages
+    }
+}
+
+/// Per-database space usage of an environment, as returned by
+/// [Transaction::db_report()].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DatabaseReport {
+    /// Reports of the main database followed by every named database.
+    pub tables: Vec<TableReport>,
+}
+
+impl DatabaseReport {
+    /// Total size of all databases in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.tables.iter().map(|table| table.bytes).sum()
+    }
+
+    /// Share of the given database in [DatabaseReport::total_bytes()], in percent.
+    pub fn percentage(&self, table: &TableReport) -> f64 {
+        match self.total_bytes() {
+            0 => 0.0,
+            total => table.bytes as f64 * 100.0 / total as f64,
+        }
+    }
+
+    /// Sorts the databases by size, largest first.
+    pub fn sort_by_size(&mut self) {
+        self.tables.sort_by_key(|table| Reverse(table.bytes));
+    }
+
+    /// Sorts the databases by number of entries, largest first.
+    pub fn sort_by_entries(&mut self) {
+        self.tables.sort_by_key(|table| Reverse(table.entries));
+    }
+
+    /// Sorts the databases by name, with the main database first.
+    pub fn sort_by_name(&mut self) {
+        self.tables.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+/// Pages of one owner and how their bytes are used, as found by walking them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableSpace {
+    pub owner: PageOwner<'static>,
+    /// Number of internal (non-leaf) pages.
+    pub branch_pages: usize,
+    /// Number of leaf pages.
+    pub leaf_pages: usize,
+    /// Number of overflow pages.
+    pub overflow_pages: usize,
+    /// Bytes used by keys and values.
+    pub payload_bytes: u64,
+    /// Bytes used by page headers and item pointers.
+    pub header_bytes: u64,
+    /// Bytes left free in partially filled pages.
+    pub unused_bytes: u64,
+}
+
+impl TableSpace {
+    fn new(owner: PageOwner<'static>) -> Self {
+        Self {
+            owner,
+            branch_pages: 0,
+            leaf_pages: 0,
+            overflow_pages: 0,
+            payload_bytes: 0,
+            header_bytes: 0,
+            unused_bytes: 0,
+        }
+    }
+
+    /// Total size of all pages in bytes.
+    pub fn bytes(&self) -> u64 {
+        self.payload_bytes + self.header_bytes + self.unused_bytes
+    }
+
+    /// Share of [TableSpace::bytes()] left unused, between 0 and 1. A high ratio means the pages
+    /// are sparsely filled and compacting the environment would reclaim space.
+    pub fn unused_ratio(&self) -> f64 {
+        match self.bytes() {
+            0 => 0.0,
+            total => self.unused_bytes as f64 / total as f64,
+        }
+    }
+}
+
+/// Space usage per owner of pages, as returned by [Transaction::space_report()].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpaceReport {
+    /// The meta pages, the garbage collector, the main database and every named database, in
+    /// the order they were found.
+    pub tables: Vec<TableSpace>,
+}
+
+impl SpaceReport {
+    /// Total size of all pages in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.tables.iter().map(TableSpace::bytes).sum()
+    }
+
+    /// Total number of unused bytes.
+    pub fn unused_bytes(&self) -> u64 {
+        self.tables.iter().map(|table| table.unused_bytes).sum()
+    }
+
+    /// Returns the space used by the given owner.
+    pub fn table(&self, owner: &PageOwner<'_>) -> Option<&TableSpace> {
+        self.tables.iter().find(|table| table.owner == *owner)
+    }
+
+    /// Sorts the owners by unused bytes, largest first.
+    pub fn sort_by_unused(&mut self) {
+        self.tables.sort_by_key(|table| Reverse(table.unused_bytes));
+    }
+}
+
+impl<'env, K, E> Transaction<'env, K, E>
+where
+    K: TransactionKind,
+    E: EnvironmentKind,
+{
+    /// Walks all pages to break down the space used by every owner of pages.
+    ///
+    /// Meta pages only contribute bytes, not page counts. Values stored inline in leaf pages are
+    /// accounted to the payload of these pages.
+    pub fn space_report(&self) -> Result<SpaceReport> {
+        let mut tables = IndexMap::<PageOwner<'static>, TableSpace>::new();
+        self.walk_pages(false, |visit| {
+            if visit.page_type.is_subpage() || visit.page_type == PageType::Broken {
+                return ControlFlow::Continue(());
+            }
+            let owner = visit.owner.into_owned();
+            let table = tables
+                .entry(owner.clone())
+                .or_insert_with(|| TableSpace::new(owner));
+            let pages = visit.pages as usize;
+            match visit.page_type {
+                PageType::Branch => table.branch_pages += pages,
+                PageType::Leaf | PageType::DupFixedLeaf => table.leaf_pages += pages,
+                PageType::Large => table.overflow_pages += pages,
+                _ => {}
+            }
+            table.payload_bytes += visit.payload_bytes as u64;
+            table.header_bytes += visit.header_bytes as u64;
+            table.unused_bytes += visit.unused_bytes as u64;
+            ControlFlow::Continue(())
+        })?;
+        Ok(SpaceReport {
+            tables: tables.into_values().collect(),
+        })
+    }
+
+    /// Collects space usage of the main database and of every named database in it.
+    ///
+    /// Named databases are found by scanning the keys of the main database, so the environment
+    /// must allow enough named databases through
+    /// [EnvironmentBuilder::set_max_dbs()](crate::EnvironmentBuilder::set_max_dbs) to open all
+    /// of them. Keys of the main database which are not database names are skipped.
+    pub fn db_report(&self) -> Result<DatabaseReport> {
+        let main = self.open_db(None)?;
+        let mut tables = vec![TableReport::new(None, &self.db_stat(&main)?)];
+
+        let mut cursor = self.cursor(&main)?;
+        for item in cursor.iter_start::<Vec<u8>, ()>() {
+            let (name, ()) = item?;
+            let name = match String::from_utf8(name) {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            let db = match self.open_db(Some(&name)) {
+                Ok(db) => db,
+                Err(Error::Incompatible | Error::NotFound) => continue,
+                Err(e) => return Err(e),
+            };
+            tables.push(TableReport::new(Some(name), &self.db_stat(&db)?));
+        }
+
+        Ok(DatabaseReport { tables })
+    }
+}
+
+impl<E> Environment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Breaks down the space used by every owner of pages in a new read-only transaction.
+    ///
+    /// See [Transaction::space_report()].
+    pub fn space_report(&self) -> Result<SpaceReport> {
+        self.begin_ro_txn()?.space_report()
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{
+    database::Database, error::mdbx_result, transaction::txn_execute, Cursor, DatabaseFlags,
+    EnvironmentKind, Result, Transaction, TransactionKind,
+};
+use std::borrow::Cow;
+
+/// Bisection steps spent on each sample of [Transaction::sample_keys()].
+const MAX_STEPS: usize = 64;
+
+/// A key found by [Transaction::sample_keys()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeySample {
+    pub key: Vec<u8>,
+    /// Estimated number of items before the key.
+    pub rank: u64,
+    /// Length of the first value of the key.
+    pub value_len: usize,
+}
+
+/// Approximate distribution of the keys of a database, as returned by
+/// [Transaction::sample_keys()].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyDistribution {
+    /// Number of items in the database.
+    pub entries: u64,
+    /// Distinct keys spread evenly over the items, in ascending order.
+    pub samples: Vec<KeySample>,
+}
+
+impl KeyDistribution {
+    /// Returns the sampled key closest to the given fraction of the items, between 0 and 1.
+    ///
+    /// Quantiles make good boundaries to split the database into shards of equal size.
+    pub fn quantile(&self, q: f64) -> Option<&[u8]> {
+        let target = q.clamp(0.0, 1.0) * self.entries.saturating_sub(1) as f64;
+        self.samples
+            .iter()
+            .min_by(|a, b| {
+                (a.rank as f64 - target)
+                    .abs()
+                    .total_cmp(&(b.rank as f64 - target).abs())
+            })
+            .map(|sample| sample.key.as_slice())
+    }
+
+    /// Returns the given quantile, between 0 and 1, of the value lengths of the samples.
+    pub fn value_len_quantile(&self, q: f64) -> Option<usize> {
+        let mut lens: Vec<usize> = self.samples.iter().map(|s| s.value_len).collect();
+        lens.sort_unstable();
+        let last = lens.len().checked_sub(1)?;
+        Some(lens[(q.clamp(0.0, 1.0) * last as f64).round() as usize])
+    }
+}
+
+/// Returns a key between `lo` and `hi` in the order of a database with the given flags, if there
+/// is one short enough to be found.
+fn midpoint(lo: &[u8], hi: &[u8], flags: DatabaseFlags) -> Option<Vec<u8>> {
+    if flags.contains(DatabaseFlags::INTEGER_KEY) {
+        let read = |key: &[u8]| -> Option<u64> {
+            Some(match key.len() {
+                4 => u32::from_ne_bytes(key.try_into().ok()?) as u64,
+                8 => u64::from_ne_bytes(key.try_into().ok()?),
+                _ => return None,
+            })
+        };
+        let (a, b) = (read(lo)?, read(hi)?);
+        if b <= a || b - a <= 1 {
+            return None;
+        }
+        let mid = a + (b - a) / 2;
+        return Some(match lo.len() {
+            4 => (mid as u32).to_ne_bytes().to_vec(),
+            _ => mid.to_ne_bytes().to_vec(),
+        });
+    }
+
+    let reverse = flags.contains(DatabaseFlags::REVERSE_KEY);
+    let ordered = |key: &[u8]| -> Vec<u8> {
+        match reverse {
+            true => key.iter().rev().copied().collect(),
+            false => key.to_vec(),
+        }
+    };
+    let (lo, hi) = (ordered(lo), ordered(hi));
+    let prefix = lo.iter().zip(&hi).take_while(|(a, b)| a == b).count();
+    let read = |key: &[u8]| {
+        let mut buf = [0; 8];
+        let tail = &key[prefix.min(key.len())..];
+        let len = tail.len().min(8);
+        buf[..len].copy_from_slice(&tail[..len]);
+        u64::from_be_bytes(buf)
+    };
+    let (a, b) = (read(&lo), read(&hi));
+    if b <= a || b - a <= 1 {
+        return None;
+    }
+    let mut mid = lo[..prefix].to_vec();
+    mid.extend_from_slice(&(a + (b - a) / 2).to_be_bytes());
+    Some(ordered(&mid))
+}
+
+impl<'env, K, E> Transaction<'env, K, E>
+where
+    K: TransactionKind,
+    E: EnvironmentKind,
+{
+    /// Samples up to `n` keys spread evenly over the items of a database, to estimate the
+    /// distribution of its keys and value lengths without reading all of it.
+    ///
+    /// Each sample is found by bisecting the key space with cursor seeks, using the estimated
+    /// rank of the keys found, so this takes `O(n log(entries))` seeks. Ranks are estimated from
+    /// the shape of the B-tree as if all pages held as many items, so they are less precise when
+    /// the size of the items varies along the keys.
+    pub fn sample_keys<'txn>(&'txn self, db: &Database<'txn>, n: usize) -> Result<KeyDistribution> {
+        let flags = self.db_flags(db)?;
+        let entries = self.db_stat(db)?.entries() as u64;
+        let mut first = self.cursor(db)?;
+        let mut cursor = self.cursor(db)?;
+        let sample = |(key, value): (Vec<u8>, Cow<'txn, [u8]>), rank| KeySample {
+            key,
+            rank,
+            value_len: value.len(),
+        };
+        let (Some(start), Some(end)) = (first.first()?, cursor.last()?) else {
+            return Ok(KeyDistribution::default());
+        };
+        let rank = |cursor: &Cursor<'txn, K>| -> Result<u64> {
+            let mut distance = 0;
+            mdbx_result(txn_execute(&self.txn_mutex(), |_| unsafe {
+                ffi::mdbx_estimate_distance(first.cursor(), cursor.cursor(), &mut distance)
+            }))?;
+            Ok(distance.max(0) as u64)
+        };
+        let start = sample(start, 0);
+        let end = sample(end, rank(&cursor)?);
+
+        let mut samples: Vec<KeySample> = Vec::with_capacity(n);
+        for i in 0..n as u64 {
+            let target = match n {
+                1 => 0,
+                _ => end.rank * i / (n as u64 - 1),
+            };
+            let (mut lo, mut hi) = (start.clone(), end.clone());
+            for _ in 0..MAX_STEPS {
+                let Some(mid) = midpoint(&lo.key, &hi.key, flags) else {
+                    break;
+                };
+                let Some(found) = cursor.set_range(&mid)? else {
+                    break;
+                };
+                let found = sample(found, rank(&cursor)?);
+                let side = if found.rank <= target {
+                    &mut lo
+                } else {
+                    &mut hi
+                };
+                if side.key == found.key {
+                    break;
+                }
+                *side = found;
+            }
+            let sample = match target - lo.rank <= hi.rank.saturating_sub(target) {
+                true => lo,
+                false => hi,
+            };
+            if samples.last().is_none_or(|last| last.key != sample.key) {
+                samples.push(sample);
+            }
+        }
+        Ok(KeyDistribution { entries, samples })
+    }
+}
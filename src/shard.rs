@@ -0,0 +1,244 @@
+use crate::{
+    cursor::IntoIter, Encodable, Environment, EnvironmentBuilder, EnvironmentKind, Error, Result,
+    TableObject, Transaction, TransactionKind, WriteFlags, RO, RW,
+};
+use std::{borrow::Cow, cell::OnceCell, cmp::Ordering, mem, path::Path};
+
+/// How keys are assigned to the shards of a [ShardedEnvironment].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShardRouting {
+    /// By a stable hash of the key, which spreads keys evenly.
+    Hash,
+    /// By key range: shard `i` holds the keys below `boundaries[i]` and from `boundaries[i - 1]`,
+    /// in lexicographic order. There must be one boundary less than shards, in ascending order.
+    Range(Vec<Vec<u8>>),
+}
+
+/// 64-bit FNV-1a, which unlike the hashers of the standard library is stable across releases.
+fn fnv1a(key: &[u8]) -> u64 {
+    key.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A keyspace split over several environments, each with its own writer.
+///
+/// MDBX allows a single write transaction per environment at a time. Splitting the keys over
+/// environments, ideally on separate disks, lets writes to different shards proceed in
+/// parallel.
+#[derive(Debug)]
+pub struct ShardedEnvironment<E>
+where
+    E: EnvironmentKind,
+{
+    shards: Vec<Environment<E>>,
+    routing: ShardRouting,
+}
+
+impl<E> ShardedEnvironment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Opens one environment per path with the options of `builder`.
+    ///
+    /// Returns [Error::Invalid] if there are no paths, or if the boundaries of
+    /// [ShardRouting::Range] do not match them.
+    ///
+    /// The paths and the routing must stay the same across openings, as keys are looked up in
+    /// the shard they are routed to.
+    pub fn open<P>(
+        builder: &EnvironmentBuilder<E>,
+        paths: impl IntoIterator<Item = P>,
+        routing: ShardRouting,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let paths = paths.into_iter().collect::<Vec<_>>();
+        let valid = match &routing {
+            ShardRouting::Hash => !paths.is_empty(),
+            ShardRouting::Range(boundaries) => {
+                boundaries.len() + 1 == paths.len() && boundaries.windows(2).all(|w| w[0] < w[1])
+            }
+        };
+        if !valid {
+            return Err(Error::Invalid);
+        }
+        let shards = paths
+            .iter()
+            .map(|path| builder.open(path.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { shards, routing })
+    }
+
+    pub fn shards(&self) -> &[Environment<E>] {
+        &self.shards
+    }
+
+    /// Returns the index of the shard holding `key`.
+    pub fn shard_of(&self, key: &[u8]) -> usize {
+        match &self.routing {
+            ShardRouting::Hash => (fnv1a(key) % self.shards.len() as u64) as usize,
+            ShardRouting::Range(boundaries) => {
+                boundaries.partition_point(|boundary| boundary.as_slice() <= key)
+            }
+        }
+    }
+
+    /// Creates a read-only transaction over all shards.
+    ///
+    /// The transactions of the shards are started as they are first used, so they may see
+    /// snapshots from slightly different points in time.
+    pub fn begin_ro_txn(&self) -> ShardedTransaction<'_, RO, E> {
+        ShardedTransaction::new(self, Environment::begin_ro_txn)
+    }
+
+    /// Creates a read-write transaction over all shards.
+    ///
+    /// The write transactions of the shards are started as they are first used, so other
+    /// writers are only blocked on the shards actually written to.
+    pub fn begin_rw_txn(&self) -> ShardedTransaction<'_, RW, E> {
+        ShardedTransaction::new(self, Environment::begin_rw_txn)
+    }
+}
+
+/// A transaction over the shards of a [ShardedEnvironment], made of one transaction per shard.
+pub struct ShardedTransaction<'env, K, E>
+where
+    K: TransactionKind,
+    E: EnvironmentKind,
+{
+    env: &'env ShardedEnvironment<E>,
+    begin: fn(&'env Environment<E>) -> Result<Transaction<'env, K, E>>,
+    txns: Vec<OnceCell<Transaction<'env, K, E>>>,
+}
+
+impl<'env, K, E> ShardedTransaction<'env, K, E>
+where
+    K: TransactionKind,
+    E: EnvironmentKind,
+{
+    fn new(
+        env: &'env ShardedEnvironment<E>,
+        begin: fn(&'env Environment<E>) -> Result<Transaction<'env, K, E>>,
+    ) -> Self {
+        Self {
+            env,
+            begin,
+            txns: env.shards.iter().map(|_| OnceCell::new()).collect(),
+        }
+    }
+
+    /// Returns the transaction of a shard, starting it if needed.
+    pub fn shard(&self, index: usize) -> Result<&Transaction<'env, K, E>> {
+        let cell = &self.txns[index];
+        if let Some(txn) = cell.get() {
+            return Ok(txn);
+        }
+        let txn = (self.begin)(&self.env.shards[index])?;
+        Ok(cell.get_or_init(|| txn))
+    }
+
+    /// Returns the transaction of the shard holding `key`, starting it if needed.
+    pub fn shard_for(&self, key: &[u8]) -> Result<&Transaction<'env, K, E>> {
+        self.shard(self.env.shard_of(key))
+    }
+
+    /// Gets an item from database `db` of the shard holding `key`, [None] for the main database.
+    pub fn get<'txn, Value>(
+        &'txn self,
+        db: Option<&str>,
+        key: impl Encodable,
+    ) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        let key = key.encode();
+        let txn = self.shard_for(key.as_ref())?;
+        txn.get(&txn.open_db(db)?, key.as_ref())
+    }
+
+    /// Iterates over the items of database `db` of all shards, merged in lexicographic key
+    /// order.
+    pub fn iter<'txn>(&'txn self, db: Option<&str>) -> Result<MergedIter<'txn, K>> {
+        let mut iters = Vec::with_capacity(self.txns.len());
+        for index in 0..self.txns.len() {
+            let txn = self.shard(index)?;
+            iters.push(txn.cursor(&txn.open_db(db)?)?.into_iter());
+        }
+        let heads = iters.iter_mut().map(Iterator::next).collect();
+        Ok(MergedIter { iters, heads })
+    }
+}
+
+impl<'env, E> ShardedTransaction<'env, RW, E>
+where
+    E: EnvironmentKind,
+{
+    /// Stores an item into database `db` of the shard holding `key`, [None] for the main
+    /// database.
+    pub fn put(
+        &self,
+        db: Option<&str>,
+        key: impl Encodable,
+        data: impl AsRef<[u8]>,
+        flags: WriteFlags,
+    ) -> Result<()> {
+        let key = key.encode();
+        let txn = self.shard_for(key.as_ref())?;
+        txn.put(&txn.open_db(db)?, key.as_ref(), data, flags)
+    }
+
+    /// Deletes an item from database `db` of the shard holding `key`, see [Transaction::del()].
+    pub fn del(&self, db: Option<&str>, key: impl Encodable, data: Option<&[u8]>) -> Result<bool> {
+        let key = key.encode();
+        let txn = self.shard_for(key.as_ref())?;
+        txn.del(&txn.open_db(db)?, key.as_ref(), data)
+    }
+
+    /// Commits the transactions of the shards which were started, one after the other.
+    ///
+    /// The commit is atomic per shard only: if it fails for a shard, the shards before it stay
+    /// committed and the ones after it are aborted.
+    pub fn commit(self) -> Result<()> {
+        for txn in self.txns.into_iter().filter_map(OnceCell::into_inner) {
+            txn.commit()?;
+        }
+        Ok(())
+    }
+}
+
+type Item<'txn> = (Cow<'txn, [u8]>, Cow<'txn, [u8]>);
+
+/// Iterator over the items of all shards, returned by [ShardedTransaction::iter()].
+pub struct MergedIter<'txn, K>
+where
+    K: TransactionKind,
+{
+    iters: Vec<IntoIter<'txn, K, Cow<'txn, [u8]>, Cow<'txn, [u8]>>>,
+    /// Next item of each shard.
+    heads: Vec<Option<Result<Item<'txn>>>>,
+}
+
+impl<'txn, K> Iterator for MergedIter<'txn, K>
+where
+    K: TransactionKind,
+{
+    type Item = Result<Item<'txn>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Errors come first, so that they are not hidden behind smaller keys.
+        let (i, _) = self
+            .heads
+            .iter()
+            .enumerate()
+            .filter_map(|(i, head)| Some((i, head.as_ref()?)))
+            .min_by(|(_, a), (_, b)| match (a, b) {
+                (Ok((a, _)), Ok((b, _))) => a.cmp(b),
+                (Err(_), _) => Ordering::Less,
+                (_, Err(_)) => Ordering::Greater,
+            })?;
+        let next = self.iters[i].next();
+        mem::replace(&mut self.heads[i], next)
+    }
+}
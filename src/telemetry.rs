@@ -0,0 +1,61 @@
+//! Export of environment health through the [metrics] facade.
+
+use crate::{Environment, EnvironmentKind, Result};
+use std::{
+    sync::{Arc, Weak},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+pub(crate) fn record_commit(duration: Duration) {
+    metrics::histogram!("libmdbx_commit_duration_seconds").record(duration.as_secs_f64());
+}
+
+impl<E> Environment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Records the current statistics of the environment as gauges.
+    ///
+    /// The durations of read-write commits are recorded as they happen into the
+    /// `libmdbx_commit_duration_seconds` histogram, from which the recorder derives quantiles.
+    pub fn export_metrics(&self) -> Result<()> {
+        let stat = self.stat()?;
+        let info = self.info()?;
+        let freelist = self.freelist()?;
+
+        metrics::gauge!("libmdbx_page_size_bytes").set(stat.page_size());
+        metrics::gauge!("libmdbx_depth").set(stat.depth());
+        metrics::gauge!("libmdbx_branch_pages").set(stat.branch_pages() as f64);
+        metrics::gauge!("libmdbx_leaf_pages").set(stat.leaf_pages() as f64);
+        metrics::gauge!("libmdbx_overflow_pages").set(stat.overflow_pages() as f64);
+        metrics::gauge!("libmdbx_entries").set(stat.entries() as f64);
+        metrics::gauge!("libmdbx_map_size_bytes").set(info.map_size() as f64);
+        metrics::gauge!("libmdbx_last_pgno").set(info.last_pgno() as f64);
+        metrics::gauge!("libmdbx_last_txnid").set(info.last_txnid() as f64);
+        metrics::gauge!("libmdbx_freelist_pages").set(freelist as f64);
+        metrics::gauge!("libmdbx_readers").set(info.num_readers() as f64);
+        metrics::gauge!("libmdbx_max_readers").set(info.max_readers() as f64);
+        metrics::gauge!("libmdbx_reader_lag_txns")
+            .set(info.last_txnid().saturating_sub(info.latter_reader_txnid()) as f64);
+        Ok(())
+    }
+
+    /// Calls [Environment::export_metrics()] every `interval` on a background thread, until the
+    /// environment is dropped.
+    pub fn export_metrics_every(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let env: Weak<Self> = Arc::downgrade(self);
+        thread::Builder::new()
+            .name("mdbx-metrics".to_string())
+            .spawn(move || loop {
+                match env.upgrade() {
+                    Some(env) => {
+                        let _ = env.export_metrics();
+                    }
+                    None => return,
+                }
+                thread::sleep(interval);
+            })
+            .expect("failed to spawn metrics thread")
+    }
+}
@@ -0,0 +1,89 @@
+//! Helpers for tests of crates built on top of this one.
+
+use crate::{
+    error::io_error, DatabaseFlags, Encodable, Environment, EnvironmentBuilder, EnvironmentKind,
+    NoWriteMap, Result, WriteFlags,
+};
+use std::{ops::Deref, path::Path};
+use tempfile::TempDir;
+
+/// An environment in a temporary directory, which is removed when the environment is dropped.
+#[derive(Debug)]
+pub struct TempEnvironment<E = NoWriteMap>
+where
+    E: EnvironmentKind,
+{
+    // Declared before `dir` so the environment is closed before the directory is removed.
+    env: Environment<E>,
+    dir: TempDir,
+}
+
+impl<E> TempEnvironment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Maximum number of named databases in environments opened by [TempEnvironment::new()].
+    pub const MAX_DBS: usize = 16;
+
+    /// Opens an environment with default options in a new temporary directory, allowing up to
+    /// [TempEnvironment::MAX_DBS] named databases.
+    pub fn new() -> Result<Self> {
+        let mut builder = Environment::new();
+        builder.set_max_dbs(Self::MAX_DBS);
+        Self::with_builder(&builder)
+    }
+
+    /// Opens an environment with the given options in a new temporary directory.
+    pub fn with_builder(builder: &EnvironmentBuilder<E>) -> Result<Self> {
+        let dir = tempfile::tempdir().map_err(io_error)?;
+        let env = builder.open(dir.path())?;
+        Ok(Self { env, dir })
+    }
+
+    /// Path of the temporary directory.
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Stores the given items into a database, creating it if needed, and commits them.
+    ///
+    /// `name` is [None] for the main database.
+    pub fn put_all<Key, Value>(
+        &self,
+        name: Option<&str>,
+        items: impl IntoIterator<Item = (Key, Value)>,
+    ) -> Result<()>
+    where
+        Key: Encodable,
+        Value: AsRef<[u8]>,
+    {
+        let txn = self.env.begin_rw_txn()?;
+        let db = txn.create_db(name, DatabaseFlags::empty())?;
+        for (key, value) in items {
+            txn.put(&db, key, value, WriteFlags::empty())?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Stores a single item into a database, creating it if needed, and commits it.
+    pub fn put(
+        &self,
+        name: Option<&str>,
+        key: impl Encodable,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        self.put_all(name, [(key, value)])
+    }
+}
+
+impl<E> Deref for TempEnvironment<E>
+where
+    E: EnvironmentKind,
+{
+    type Target = Environment<E>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.env
+    }
+}
@@ -1,21 +1,29 @@
 use crate::{
+    compress,
     database::Database,
-    environment::{Environment, EnvironmentKind, NoWriteMap, TxnManagerMessage, TxnPtr},
-    error::{mdbx_result, Result},
-    flags::{DatabaseFlags, WriteFlags},
-    Cursor, Error, Stat, TableObject,
+    environment::{Environment, EnvironmentKind, NoWriteMap},
+    error::{mdbx_result, Operation, Result},
+    flags::{DatabaseFlags, DatabaseState, WriteFlags},
+    Compression, Cursor, DupCursor, Encodable, Error, Stat, TableObject,
 };
+#[cfg(feature = "serde")]
+use crate::{Serde, SerdeFormat};
 use ffi::{MDBX_txn_flags_t, MDBX_TXN_RDONLY, MDBX_TXN_READWRITE};
 use indexmap::IndexSet;
-use libc::{c_uint, c_void};
-use parking_lot::Mutex;
+use libc::{c_int, c_uint, c_void};
+use parking_lot::{Mutex, MutexGuard};
 use std::{
+    borrow::Cow,
+    cmp::Ordering,
     fmt,
     fmt::Debug,
+    io::IoSlice,
     marker::PhantomData,
     mem::size_of,
+    ops::Deref,
     ptr, result, slice,
-    sync::{mpsc::sync_channel, Arc},
+    sync::Arc,
+    thread::{self, ThreadId},
 };
 
 mod private {
@@ -52,6 +60,11 @@ impl TransactionKind for RW {
 /// An MDBX transaction.
 ///
 /// All database operations require a transaction.
+///
+/// Environments are opened with `MDBX_NOTLS`, so transactions are not bound to the thread which
+/// started them and are both [Send] and [Sync]. Calls on a transaction and its cursors are
+/// serialized by a lock, so a read-only transaction can be shared between threads to read one
+/// snapshot in parallel, e.g. through [ArcRoTransaction].
 pub struct Transaction<'env, K, E>
 where
     K: TransactionKind,
@@ -60,6 +73,10 @@ where
     txn: Arc<Mutex<*mut ffi::MDBX_txn>>,
     primed_dbis: Mutex<IndexSet<ffi::MDBX_dbi>>,
     committed: bool,
+    nested: bool,
+    /// Thread which has to finish the transaction, see
+    /// [EnvironmentBuilder::set_direct_commit()](crate::EnvironmentBuilder::set_direct_commit).
+    owner: Option<ThreadId>,
     env: &'env Environment<E>,
     _marker: PhantomData<fn(K)>,
 }
@@ -70,6 +87,7 @@ where
     E: EnvironmentKind,
 {
     pub(crate) fn new(env: &'env Environment<E>) -> Result<Self> {
+        env.check_inherited()?;
         let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
         unsafe {
             mdbx_result(ffi::mdbx_txn_begin_ex(
@@ -78,21 +96,46 @@ where
                 K::OPEN_FLAGS,
                 &mut txn,
                 ptr::null_mut(),
-            ))?;
-            Ok(Self::new_from_ptr(env, txn))
+            ))
+            .map_err(|e| match e {
+                Error::TxnOverlapping(None) => Error::TxnOverlapping(
+                    env.open_transactions()
+                        .into_iter()
+                        .find(|origin| origin.read_write)
+                        .map(|origin| origin.id),
+                ),
+                other => other,
+            })?;
+            Ok(Self::new_from_ptr(env, txn, ptr::null_mut()))
         }
     }
 
-    pub(crate) fn new_from_ptr(env: &'env Environment<E>, txn: *mut ffi::MDBX_txn) -> Self {
+    pub(crate) fn new_from_ptr(
+        env: &'env Environment<E>,
+        txn: *mut ffi::MDBX_txn,
+        parent: *mut ffi::MDBX_txn,
+    ) -> Self {
+        env.track_txn(txn, parent, !K::ONLY_CLEAN);
         Self {
             txn: Arc::new(Mutex::new(txn)),
             primed_dbis: Mutex::new(IndexSet::new()),
             committed: false,
+            nested: false,
+            owner: None,
             env,
             _marker: PhantomData,
         }
     }
 
+    pub(crate) fn set_owner(&mut self, owner: ThreadId) {
+        self.owner = Some(owner);
+    }
+
+    fn on_owner_thread(&self) -> bool {
+        self.owner
+            .is_none_or(|owner| owner == thread::current().id())
+    }
+
     /// Returns a raw pointer to the underlying MDBX transaction.
     ///
     /// The caller **must** ensure that the pointer is not used after the
@@ -110,6 +153,15 @@ where
         self.env
     }
 
+    /// Attaches the operation, database and key to the error of a failed operation, if enabled
+    /// with [EnvironmentBuilder::set_op_context()](crate::EnvironmentBuilder::set_op_context).
+    fn op_error(&self, error: Error, db: &Database<'_>, operation: Operation, key: &[u8]) -> Error {
+        if !self.env.has_op_context() {
+            return error;
+        }
+        unsafe { error.with_op_context(self.txn(), db.dbi(), operation, key) }
+    }
+
     /// Returns the transaction id.
     pub fn id(&self) -> u64 {
         txn_execute(&self.txn, |txn| unsafe { ffi::mdbx_txn_id(txn) })
@@ -123,10 +175,16 @@ where
     /// returned. Retrieval of other items requires the use of
     /// [Cursor]. If the item is not in the database, then
     /// [None] will be returned.
-    pub fn get<'txn, Key>(&'txn self, db: &Database<'txn>, key: &[u8]) -> Result<Option<Key>>
+    pub fn get<'txn, Key>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+    ) -> Result<Option<Key>>
     where
         Key: TableObject<'txn>,
     {
+        let key = self.encode_key(db, key)?;
+        let key = key.as_ref();
         let key_val: ffi::MDBX_val = ffi::MDBX_val {
             iov_len: key.len(),
             iov_base: key.as_ptr() as *mut c_void,
@@ -138,42 +196,174 @@ where
 
         txn_execute(&self.txn, |txn| unsafe {
             match ffi::mdbx_get(txn, db.dbi(), &key_val, &mut data_val) {
-                ffi::MDBX_SUCCESS => Key::decode_val::<K>(txn, &data_val).map(Some),
+                ffi::MDBX_SUCCESS => Key::decode_val::<K>(txn, &data_val)
+                    .map(Some)
+                    .map_err(|e| e.with_decode_context(txn, db.dbi(), &key_val, &data_val)),
                 ffi::MDBX_NOTFOUND => Ok(None),
                 err_code => Err(Error::from_err_code(err_code)),
             }
         })
+        .map_err(|e| self.op_error(e, db, Operation::Get, key))
+    }
+
+    /// Gets an item from a database without copying it.
+    ///
+    /// Unlike [Transaction::get()] with [Cow], this never copies values which were modified in
+    /// the current read-write transaction. The returned guard holds the lock of the transaction,
+    /// so the value cannot be changed or freed by writes while it is alive. Every other operation
+    /// on the transaction and its cursors blocks until the guard is dropped, also on the calling
+    /// thread, so it must be dropped before the transaction is used again.
+    pub fn get_ref<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+    ) -> Result<Option<ValueGuard<'txn>>> {
+        let key = self.encode_key(db, key)?;
+        let key = key.as_ref();
+        let key_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: key.len(),
+            iov_base: key.as_ptr() as *mut c_void,
+        };
+        let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: 0,
+            iov_base: ptr::null_mut(),
+        };
+
+        let lock = self.txn.lock();
+        let err_code = match unsafe { ffi::mdbx_get(*lock, db.dbi(), &key_val, &mut data_val) } {
+            ffi::MDBX_SUCCESS => {
+                let data = match data_val.iov_len {
+                    0 => &[][..],
+                    len => unsafe { slice::from_raw_parts(data_val.iov_base as *const u8, len) },
+                };
+                return Ok(Some(ValueGuard { _lock: lock, data }));
+            }
+            ffi::MDBX_NOTFOUND => return Ok(None),
+            err_code => err_code,
+        };
+        drop(lock);
+        Err(self.op_error(Error::from_err_code(err_code), db, Operation::Get, key))
+    }
+
+    /// Gets multiple items from a database, returning the values in the order of `keys`.
+    ///
+    /// The keys are sorted and looked up with a single cursor, so neighbouring keys are found
+    /// without descending the B-tree from the root again. This is considerably faster than
+    /// separate [Transaction::get()] calls for large batches of random keys.
+    pub fn get_many<'txn, Value, Key>(
+        &'txn self,
+        db: &Database<'txn>,
+        keys: impl IntoIterator<Item = Key>,
+    ) -> Result<Vec<Option<Value>>>
+    where
+        Value: TableObject<'txn>,
+        Key: Encodable,
+    {
+        self.check_key_encoding(db, Key::BIG_ENDIAN_INT)?;
+        let keys = keys.into_iter().map(Encodable::encode).collect::<Vec<_>>();
+        let mut cursor = self.cursor(db)?;
+        let mut order = (0..keys.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| cursor.cmp_keys(keys[a].as_ref(), keys[b].as_ref()));
+
+        let mut values = (0..keys.len()).map(|_| None).collect::<Vec<_>>();
+        for i in order {
+            values[i] = cursor.set(keys[i].as_ref())?;
+        }
+        Ok(values)
+    }
+
+    /// Gets multiple items from a database, like [Transaction::get_many()], for keys which are
+    /// already sorted in the order of the database.
+    ///
+    /// See [Cursor::get_sorted()].
+    pub fn get_sorted<'txn, Value, Key>(
+        &'txn self,
+        db: &Database<'txn>,
+        keys: impl IntoIterator<Item = Key>,
+    ) -> Result<Vec<Option<Value>>>
+    where
+        Value: TableObject<'txn>,
+        Key: Encodable,
+    {
+        self.check_key_encoding(db, Key::BIG_ENDIAN_INT)?;
+        self.cursor(db)?
+            .get_sorted(keys.into_iter().map(Encodable::encode))
+    }
+
+    /// Gets an item from a database and deserializes it with the format `F`.
+    ///
+    /// See [Transaction::get()].
+    #[cfg(feature = "serde")]
+    pub fn get_de<'txn, F, T>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+    ) -> Result<Option<T>>
+    where
+        F: SerdeFormat,
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(self.get::<Serde<F, T>>(db, key)?.map(Serde::into_inner))
     }
 
     /// Commits the transaction.
     ///
-    /// Any pending operations will be saved.
+    /// Any pending operations will be saved. A poisoned transaction is aborted instead, returning
+    /// [Error::Poisoned], see [Transaction::is_poisoned()].
+    ///
+    /// In direct-commit mode, committing a top-level read-write transaction on a thread other than
+    /// the one which started it returns [Error::ThreadMismatch] and leaks the transaction, see
+    /// [EnvironmentBuilder::set_direct_commit()](crate::EnvironmentBuilder::set_direct_commit).
     pub fn commit(self) -> Result<bool> {
         self.commit_and_rebind_open_dbs().map(|v| v.0)
     }
 
+    /// Returns whether the transaction is poisoned, in which case it can only be aborted.
+    ///
+    /// A transaction is poisoned by a panic in a closure called with it, e.g. by
+    /// [Transaction::guarded()] or [Transaction::merge()], or by a failed write which MDBX cannot
+    /// recover from, e.g. [Error::MapFull]. Committing it returns [Error::Poisoned] and aborts
+    /// it instead, so that a caught panic never commits half of the changes.
+    pub fn is_poisoned(&self) -> bool {
+        let flags = txn_execute(&self.txn, |txn| unsafe { ffi::mdbx_txn_flags(txn) });
+        flags < 0 || flags as ffi::MDBX_txn_flags_t & ffi::MDBX_TXN_ERROR != 0
+    }
+
+    /// Runs `f` with the transaction, poisoning it if `f` panics.
+    ///
+    /// See [Transaction::is_poisoned()].
+    pub fn guarded<R>(&self, f: impl FnOnce(&Self) -> R) -> R {
+        let _guard = PoisonGuard(&self.txn);
+        f(self)
+    }
+
     pub fn prime_for_permaopen(&self, db: Database<'_>) {
         self.primed_dbis.lock().insert(db.dbi());
     }
 
     /// Commits the transaction and returns table handles permanently open for the lifetime of `Environment`.
     pub fn commit_and_rebind_open_dbs(mut self) -> Result<(bool, Vec<Database<'env>>)> {
+        if !self.on_owner_thread() {
+            // Leaked when dropped.
+            return Err(Error::ThreadMismatch(Some(self.id())));
+        }
+        if self.is_poisoned() {
+            return Err(Error::Poisoned);
+        }
         let txnlck = self.txn.lock();
         let txn = *txnlck;
-        let result = if K::ONLY_CLEAN {
+        self.env.untrack_txn(txn, !K::ONLY_CLEAN);
+        let result = if let Err(e) = self.env.check_inherited() {
+            Err(e)
+        } else if K::ONLY_CLEAN {
             mdbx_result(unsafe { ffi::mdbx_txn_commit_ex(txn, ptr::null_mut()) })
         } else {
-            let (sender, rx) = sync_channel(0);
             self.env
-                .txn_manager
-                .as_ref()
-                .unwrap()
-                .send(TxnManagerMessage::Commit {
-                    tx: TxnPtr(txn),
-                    sender,
+                .commit_txn_ptr(txn, self.nested)
+                .and_then(|v| match self.nested {
+                    false => self.env.check_durability().map(|()| v),
+                    true => Ok(v),
                 })
-                .unwrap();
-            rx.recv().unwrap()
         };
         self.committed = true;
         result.map(|v| {
@@ -182,7 +372,7 @@ where
                 self.primed_dbis
                     .lock()
                     .iter()
-                    .map(|&dbi| Database::new_from_ptr(dbi))
+                    .map(|&dbi| Database::tracked(self.env, dbi))
                     .collect(),
             )
         })
@@ -203,17 +393,57 @@ where
         Database::new(self, name, 0)
     }
 
-    /// Gets the option flags for the given database in the transaction.
-    pub fn db_flags<'txn>(&'txn self, db: &Database<'txn>) -> Result<DatabaseFlags> {
+    fn db_flags_ex<'txn>(&'txn self, db: &Database<'txn>) -> Result<(c_uint, c_uint)> {
         let mut flags: c_uint = 0;
+        let mut state: c_uint = 0;
         unsafe {
             mdbx_result(txn_execute(&self.txn, |txn| {
-                ffi::mdbx_dbi_flags_ex(txn, db.dbi(), &mut flags, ptr::null_mut())
+                ffi::mdbx_dbi_flags_ex(txn, db.dbi(), &mut flags, &mut state)
             }))?;
         }
+        Ok((flags, state))
+    }
+
+    /// Gets the option flags for the given database in the transaction.
+    pub fn db_flags<'txn>(&'txn self, db: &Database<'txn>) -> Result<DatabaseFlags> {
+        let (flags, _) = self.db_flags_ex(db)?;
         Ok(DatabaseFlags::from_bits_truncate(flags))
     }
 
+    /// Gets the state of the given database handle in the transaction, e.g. whether the
+    /// database was created or modified by it.
+    pub fn db_state<'txn>(&'txn self, db: &Database<'txn>) -> Result<DatabaseState> {
+        let (_, state) = self.db_flags_ex(db)?;
+        Ok(DatabaseState::from_bits_truncate(state))
+    }
+
+    /// Encodes a key for the given database, refusing big-endian integer keys for databases
+    /// with [DatabaseFlags::INTEGER_KEY], see [Encodable::BIG_ENDIAN_INT].
+    pub(crate) fn encode_key<'txn, Key>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: Key,
+    ) -> Result<Key::Encoded>
+    where
+        Key: Encodable,
+    {
+        self.check_key_encoding(db, Key::BIG_ENDIAN_INT)?;
+        Ok(key.encode())
+    }
+
+    /// Returns [Error::Incompatible] if `big_endian_int` keys are used with a database with
+    /// [DatabaseFlags::INTEGER_KEY]. The flags are only looked up for such keys.
+    pub(crate) fn check_key_encoding<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        big_endian_int: bool,
+    ) -> Result<()> {
+        if big_endian_int && self.db_flags(db)?.contains(DatabaseFlags::INTEGER_KEY) {
+            return Err(Error::Incompatible);
+        }
+        Ok(())
+    }
+
     /// Retrieves database statistics.
     pub fn db_stat<'txn>(&'txn self, db: &Database<'txn>) -> Result<Stat> {
         unsafe {
@@ -225,10 +455,70 @@ where
         }
     }
 
+    /// Checks that the items of a database are in the order of its comparators, e.g. after
+    /// opening it with [Transaction::create_db_with_comparators()].
+    ///
+    /// Returns the first key which does not sort after the previous one, or whose duplicate does
+    /// not sort after the previous duplicate, if any. This reads the whole database.
+    pub fn check_order<'txn>(&'txn self, db: &Database<'txn>) -> Result<Option<Vec<u8>>> {
+        let dup_sort = self.db_flags(db)?.contains(DatabaseFlags::DUP_SORT);
+        let mut cursor = self.cursor(db)?;
+        let Some((mut prev_key, mut prev_value)) = cursor.first::<Cow<_>, Cow<_>>()? else {
+            return Ok(None);
+        };
+        while let Some((key, value)) = cursor.next::<Cow<_>, Cow<_>>()? {
+            let ordered = match cursor.cmp_keys(&prev_key, &key) {
+                Ordering::Less => true,
+                Ordering::Equal => dup_sort && cursor.cmp_values(&prev_value, &value).is_lt(),
+                Ordering::Greater => false,
+            };
+            if !ordered {
+                return Ok(Some(key.into_owned()));
+            }
+            (prev_key, prev_value) = (key, value);
+        }
+        Ok(None)
+    }
+
     /// Open a new cursor on the given database.
     pub fn cursor<'txn>(&'txn self, db: &Database<'txn>) -> Result<Cursor<'txn, K>> {
         Cursor::new(self, db)
     }
+
+    /// Open a new cursor on the given database, which must have been opened with
+    /// [DatabaseFlags::DUP_SORT].
+    ///
+    /// Returns [Error::Incompatible] for databases without duplicate data items.
+    pub fn dup_cursor<'txn>(&'txn self, db: &Database<'txn>) -> Result<DupCursor<'txn, K>> {
+        DupCursor::new(Cursor::new(self, db)?)
+    }
+}
+
+/// A value borrowed from a database by [Transaction::get_ref()], holding the lock of its
+/// transaction.
+pub struct ValueGuard<'txn> {
+    _lock: MutexGuard<'txn, *mut ffi::MDBX_txn>,
+    data: &'txn [u8],
+}
+
+impl<'txn> fmt::Debug for ValueGuard<'txn> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ValueGuard").field(&self.data).finish()
+    }
+}
+
+impl<'txn> Deref for ValueGuard<'txn> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<'txn> AsRef<[u8]> for ValueGuard<'txn> {
+    fn as_ref(&self) -> &[u8] {
+        self.data
+    }
 }
 
 pub(crate) fn txn_execute<F: FnOnce(*mut ffi::MDBX_txn) -> T, T>(
@@ -239,6 +529,76 @@ pub(crate) fn txn_execute<F: FnOnce(*mut ffi::MDBX_txn) -> T, T>(
     (f)(*lck)
 }
 
+/// Poisons a transaction if dropped during a panic, so that the transaction cannot be committed
+/// if the panic is caught. Created around calls into user code, see [Transaction::is_poisoned()].
+pub(crate) struct PoisonGuard<'a>(pub(crate) &'a Mutex<*mut ffi::MDBX_txn>);
+
+impl Drop for PoisonGuard<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            txn_execute(self.0, |txn| unsafe { ffi::mdbx_txn_break(txn) });
+        }
+    }
+}
+
+/// Checks the sizes of an item against the limits of its database, so that oversized items are
+/// reported with their sizes rather than as [Error::BadValSize].
+///
+/// # Safety
+/// `txn` must be a live transaction and `dbi` a handle valid in it.
+pub(crate) unsafe fn check_item_size(
+    txn: *mut ffi::MDBX_txn,
+    dbi: ffi::MDBX_dbi,
+    key_len: usize,
+    data_len: usize,
+) -> Result<()> {
+    let env = ffi::mdbx_txn_env(txn);
+    let limits = |flags| {
+        (
+            ffi::mdbx_env_get_maxkeysize_ex(env, flags).max(0) as usize,
+            ffi::mdbx_env_get_maxvalsize_ex(env, flags).max(0) as usize,
+        )
+    };
+    // Most items fit the limits of any database, which spares looking up the flags.
+    let (plain, dup_sort) = (limits(0), limits(ffi::MDBX_DUPSORT));
+    if key_len <= plain.0.min(dup_sort.0) && data_len <= plain.1.min(dup_sort.1) {
+        return Ok(());
+    }
+    let mut flags: c_uint = 0;
+    let mut state: c_uint = 0;
+    mdbx_result(ffi::mdbx_dbi_flags_ex(txn, dbi, &mut flags, &mut state))?;
+    let (max_key, max_value) = limits(flags);
+    if key_len > max_key {
+        return Err(Error::KeyTooLarge {
+            len: key_len,
+            max: max_key,
+        });
+    }
+    if data_len > max_value {
+        return Err(Error::ValueTooLarge {
+            len: data_len,
+            max: max_value,
+        });
+    }
+    Ok(())
+}
+
+/// Copies the old value out of a page about to be modified by [Transaction::replace()].
+unsafe extern "C" fn preserve_value(
+    context: *mut c_void,
+    target: *mut ffi::MDBX_val,
+    src: *const c_void,
+    bytes: usize,
+) -> c_int {
+    let buf = &mut *(context as *mut Vec<u8>);
+    if bytes > 0 {
+        buf.extend_from_slice(slice::from_raw_parts(src as *const u8, bytes));
+    }
+    (*target).iov_base = buf.as_mut_ptr() as *mut c_void;
+    (*target).iov_len = bytes;
+    ffi::MDBX_SUCCESS
+}
+
 impl<'env, E> Transaction<'env, RW, E>
 where
     E: EnvironmentKind,
@@ -271,6 +631,35 @@ where
         self.open_db_with_flags(name, flags | DatabaseFlags::CREATE)
     }
 
+    /// Like [Transaction::create_db()], but orders the keys, and the duplicates of a
+    /// [DatabaseFlags::DUP_SORT] database, with custom comparators instead of the ones selected
+    /// by the flags. [None] keeps the default comparator.
+    ///
+    /// The comparators stay attached to the handle of the database in the environment, so later
+    /// [Transaction::open_db()] calls use them too. MDBX does not pass any context to
+    /// comparators, so they cannot be closures. Use [Transaction::check_order()] to find out
+    /// whether existing items are in the order of the comparators.
+    ///
+    /// # Safety
+    /// The comparators must define a total order, must not unwind, and must be the same every
+    /// time the database is opened, by any process. Otherwise items are put in the wrong place
+    /// and cannot be found anymore.
+    pub unsafe fn create_db_with_comparators<'txn>(
+        &'txn self,
+        name: Option<&str>,
+        flags: DatabaseFlags,
+        keycmp: ffi::MDBX_cmp_func,
+        datacmp: ffi::MDBX_cmp_func,
+    ) -> Result<Database<'txn>> {
+        Database::new_ex(
+            self,
+            name,
+            (flags | DatabaseFlags::CREATE).bits(),
+            keycmp,
+            datacmp,
+        )
+    }
+
     /// Stores an item into a database.
     ///
     /// This function stores key/data pairs in the database. The default
@@ -280,10 +669,11 @@ where
     pub fn put<'txn>(
         &'txn self,
         db: &Database<'txn>,
-        key: impl AsRef<[u8]>,
+        key: impl Encodable,
         data: impl AsRef<[u8]>,
         flags: WriteFlags,
     ) -> Result<()> {
+        let key = self.encode_key(db, key)?;
         let key = key.as_ref();
         let data = data.as_ref();
         let key_val: ffi::MDBX_val = ffi::MDBX_val {
@@ -294,23 +684,64 @@ where
             iov_len: data.len(),
             iov_base: data.as_ptr() as *mut c_void,
         };
-        mdbx_result(txn_execute(&self.txn, |txn| unsafe {
-            ffi::mdbx_put(txn, db.dbi(), &key_val, &mut data_val, flags.bits())
-        }))?;
+        txn_execute(&self.txn, |txn| unsafe {
+            check_item_size(txn, db.dbi(), key.len(), data.len())?;
+            mdbx_result(ffi::mdbx_put(
+                txn,
+                db.dbi(),
+                &key_val,
+                &mut data_val,
+                flags.bits(),
+            ))
+        })
+        .map_err(|e| self.op_error(e, db, Operation::Put, key))?;
 
         Ok(())
     }
 
+    /// Serializes the value with the format `F` and stores it into a database.
+    ///
+    /// See [Transaction::put()].
+    #[cfg(feature = "serde")]
+    pub fn put_ser<'txn, F, T>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+        value: &T,
+        flags: WriteFlags,
+    ) -> Result<()>
+    where
+        F: SerdeFormat,
+        T: serde::Serialize + ?Sized,
+    {
+        self.put(db, key, F::serialize(value)?, flags)
+    }
+
+    /// Compresses the value with [compress()] and stores it into a database.
+    ///
+    /// Read it back with [Decompressed](crate::Decompressed) as the value type.
+    pub fn put_compressed<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+        data: impl AsRef<[u8]>,
+        compression: Compression,
+        flags: WriteFlags,
+    ) -> Result<()> {
+        self.put(db, key, compress(data.as_ref(), compression)?, flags)
+    }
+
     /// Returns a buffer which can be used to write a value into the item at the
     /// given key and with the given length. The buffer must be completely
     /// filled by the caller.
     pub fn reserve<'txn>(
         &'txn self,
         db: &Database<'txn>,
-        key: impl AsRef<[u8]>,
+        key: impl Encodable,
         len: usize,
         flags: WriteFlags,
     ) -> Result<&'txn mut [u8]> {
+        let key = self.encode_key(db, key)?;
         let key = key.as_ref();
         let key_val: ffi::MDBX_val = ffi::MDBX_val {
             iov_len: key.len(),
@@ -321,15 +752,17 @@ where
             iov_base: ptr::null_mut::<c_void>(),
         };
         unsafe {
-            mdbx_result(txn_execute(&self.txn, |txn| {
-                ffi::mdbx_put(
+            txn_execute(&self.txn, |txn| {
+                check_item_size(txn, db.dbi(), key.len(), len)?;
+                mdbx_result(ffi::mdbx_put(
                     txn,
                     db.dbi(),
                     &key_val,
                     &mut data_val,
                     flags.bits() | ffi::MDBX_RESERVE,
-                )
-            }))?;
+                ))
+            })
+            .map_err(|e| self.op_error(e, db, Operation::Put, key))?;
             Ok(slice::from_raw_parts_mut(
                 data_val.iov_base as *mut u8,
                 data_val.iov_len,
@@ -337,6 +770,119 @@ where
         }
     }
 
+    /// Stores an item whose value is the concatenation of the given segments.
+    ///
+    /// The segments are copied directly into space reserved in the database, without assembling
+    /// the value in an intermediate buffer. Like [Transaction::reserve()], this is not supported
+    /// for databases opened with [DatabaseFlags::DUP_SORT].
+    pub fn put_vectored<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+        data: &[IoSlice<'_>],
+        flags: WriteFlags,
+    ) -> Result<()> {
+        let len = data.iter().map(|segment| segment.len()).sum();
+        let mut buf = self.reserve(db, key, len, flags)?;
+        for segment in data {
+            let (head, tail) = buf.split_at_mut(segment.len());
+            head.copy_from_slice(segment);
+            buf = tail;
+        }
+
+        Ok(())
+    }
+
+    /// Stores an item into a database, returning the value it replaced.
+    ///
+    /// The old value is borrowed from the database unless it was written by this transaction, in
+    /// which case it is copied before being overwritten. This is not supported for databases
+    /// opened with [DatabaseFlags::DUP_SORT].
+    pub fn replace<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+        data: impl AsRef<[u8]>,
+        flags: WriteFlags,
+    ) -> Result<Option<Cow<'txn, [u8]>>> {
+        let key = self.encode_key(db, key)?;
+        let key = key.as_ref();
+        let data = data.as_ref();
+        let key_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: key.len(),
+            iov_base: key.as_ptr() as *mut c_void,
+        };
+        let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: data.len(),
+            iov_base: data.as_ptr() as *mut c_void,
+        };
+        let mut old_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: 0,
+            iov_base: ptr::null_mut(),
+        };
+        let mut preserved: Vec<u8> = Vec::new();
+        mdbx_result(txn_execute(&self.txn, |txn| unsafe {
+            ffi::mdbx_replace_ex(
+                txn,
+                db.dbi(),
+                &key_val,
+                &mut data_val,
+                &mut old_val,
+                flags.bits(),
+                Some(preserve_value),
+                &mut preserved as *mut Vec<u8> as *mut c_void,
+            )
+        }))?;
+
+        Ok(if old_val.iov_base.is_null() {
+            None
+        } else if old_val.iov_base == preserved.as_mut_ptr() as *mut c_void {
+            Some(Cow::Owned(preserved))
+        } else if old_val.iov_base == data_val.iov_base {
+            // The value was already written by this transaction and left unchanged.
+            Some(Cow::Owned(data.to_vec()))
+        } else {
+            Some(Cow::Borrowed(unsafe {
+                slice::from_raw_parts(old_val.iov_base as *const u8, old_val.iov_len)
+            }))
+        })
+    }
+
+    /// Replaces the value of a key with the result of `f` applied to its current value, or to
+    /// [None] if the key is absent.
+    ///
+    /// This allows merge semantics such as counters or set unions without decoding and
+    /// re-encoding the item in the caller. The current value is borrowed from the database and
+    /// the result is stored with [Transaction::replace()], updating the existing item in place.
+    /// Returns [Error::Incompatible] for databases opened with [DatabaseFlags::DUP_SORT]. The
+    /// transaction is poisoned if `f` panics.
+    pub fn merge<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+        f: impl FnOnce(Option<&[u8]>) -> Vec<u8>,
+    ) -> Result<()> {
+        if self.db_flags(db)?.contains(DatabaseFlags::DUP_SORT) {
+            return Err(Error::Incompatible);
+        }
+
+        let key = self.encode_key(db, key)?;
+        let key = key.as_ref();
+        let old = self.get::<Cow<'txn, [u8]>>(db, key)?;
+        let flags = if old.is_some() {
+            WriteFlags::CURRENT
+        } else {
+            WriteFlags::NO_OVERWRITE
+        };
+        let new = {
+            let _guard = PoisonGuard(&self.txn);
+            f(old.as_deref())
+        };
+        self.replace(db, key, new, flags)?;
+
+        Ok(())
+    }
+
     /// Delete items from a database.
     /// This function removes key/data pairs from the database.
     ///
@@ -348,9 +894,10 @@ where
     pub fn del<'txn>(
         &'txn self,
         db: &Database<'txn>,
-        key: impl AsRef<[u8]>,
+        key: impl Encodable,
         data: Option<&[u8]>,
     ) -> Result<bool> {
+        let key = self.encode_key(db, key)?;
         let key = key.as_ref();
         let key_val: ffi::MDBX_val = ffi::MDBX_val {
             iov_len: key.len(),
@@ -373,7 +920,7 @@ where
         .map(|_| true)
         .or_else(|e| match e {
             Error::NotFound => Ok(false),
-            other => Err(other),
+            other => Err(self.op_error(other, db, Operation::Del, key)),
         })
     }
 
@@ -388,14 +935,63 @@ where
 
     /// Drops the database from the environment.
     ///
-    /// # Safety
-    /// Caller must close ALL other [Database] and [Cursor] instances pointing to the same dbi BEFORE calling this function.
-    pub unsafe fn drop_db<'txn>(&'txn self, db: Database<'txn>) -> Result<()> {
-        mdbx_result(txn_execute(&self.txn, |txn| {
-            ffi::mdbx_drop(txn, db.dbi(), true)
+    /// Returns [Error::DatabaseBusy] if other [Database] or [Cursor] instances still reference
+    /// the handle of the database, in any transaction. Instances count until they are dropped,
+    /// even once their transaction has ended.
+    pub fn drop_db<'txn>(&'txn self, db: Database<'txn>) -> Result<()> {
+        self.env.release_dbi(db, |dbi| {
+            mdbx_result(txn_execute(&self.txn, |txn| unsafe {
+                ffi::mdbx_drop(txn, dbi, true)
+            }))?;
+            Ok(())
+        })
+    }
+
+    /// Renames a database, returning a handle to it under its new name.
+    ///
+    /// The items are copied in order into a new database with the same flags and sequence, using
+    /// [WriteFlags::APPEND], before the old database is dropped, all within this transaction.
+    /// Returns [Error::KeyExist] if a database named `new` exists already, or
+    /// [Error::DatabaseBusy] if other [Database] or [Cursor] instances reference `old`.
+    pub fn rename_db<'txn>(&'txn self, old: &str, new: &str) -> Result<Database<'txn>> {
+        let source = self.open_db(Some(old))?;
+        if self.env.dbi_in_use(&source) {
+            return Err(Error::DatabaseBusy);
+        }
+        match self.open_db(Some(new)) {
+            Ok(_) => return Err(Error::KeyExist),
+            Err(Error::NotFound) => {}
+            Err(e) => return Err(e),
+        }
+        let flags = self.db_flags(&source)?;
+        let target = self.create_db(Some(new), flags)?;
+
+        {
+            let mut cursor = self.cursor(&target)?;
+            let mut prev: Option<Cow<'txn, [u8]>> = None;
+            for item in self.cursor(&source)? {
+                let (key, value) = item?;
+                let flags = match &prev {
+                    Some(prev) if *prev == key => WriteFlags::APPEND_DUP,
+                    _ => WriteFlags::APPEND,
+                };
+                cursor.put(&key, &value, flags)?;
+                prev = Some(key);
+            }
+        }
+
+        let mut sequence = 0;
+        mdbx_result(txn_execute(&self.txn, |txn| unsafe {
+            ffi::mdbx_dbi_sequence(txn, source.dbi(), &mut sequence, 0)
         }))?;
+        if sequence != 0 {
+            mdbx_result(txn_execute(&self.txn, |txn| unsafe {
+                ffi::mdbx_dbi_sequence(txn, target.dbi(), &mut 0, sequence)
+            }))?;
+        }
 
-        Ok(())
+        self.drop_db(source)?;
+        Ok(target)
     }
 }
 
@@ -403,14 +999,99 @@ impl<'env, E> Transaction<'env, RO, E>
 where
     E: EnvironmentKind,
 {
+    /// Wraps the transaction into an [ArcRoTransaction], which can be cloned and shared between
+    /// threads.
+    pub fn into_arc(self) -> ArcRoTransaction<'env, E> {
+        ArcRoTransaction(Arc::new(self))
+    }
+
+    /// Gets an item from a database as a slice borrowed from the memory map.
+    ///
+    /// A read-only transaction never sees pages modified by a writer, so unlike
+    /// [Transaction::get()] with [Cow](std::borrow::Cow) this neither checks whether the value
+    /// is dirty nor copies it.
+    pub fn get_slice<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+    ) -> Result<Option<&'txn [u8]>> {
+        let key = self.encode_key(db, key)?;
+        let key = key.as_ref();
+        let key_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: key.len(),
+            iov_base: key.as_ptr() as *mut c_void,
+        };
+        let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: 0,
+            iov_base: ptr::null_mut(),
+        };
+
+        txn_execute(&self.txn, |txn| unsafe {
+            match ffi::mdbx_get(txn, db.dbi(), &key_val, &mut data_val) {
+                ffi::MDBX_SUCCESS => Ok(Some(slice::from_raw_parts(
+                    data_val.iov_base as *const u8,
+                    data_val.iov_len,
+                ))),
+                ffi::MDBX_NOTFOUND => Ok(None),
+                err_code => Err(Error::from_err_code(err_code)),
+            }
+        })
+    }
+
     /// Closes the database handle.
     ///
-    /// # Safety
-    /// Caller must close ALL other [Database] and [Cursor] instances pointing to the same dbi BEFORE calling this function.
-    pub unsafe fn close_db(&self, db: Database<'_>) -> Result<()> {
-        mdbx_result(ffi::mdbx_dbi_close(self.env.env(), db.dbi()))?;
+    /// Returns [Error::DatabaseBusy] if other [Database] or [Cursor] instances still reference
+    /// the handle, in any transaction, see [Transaction::drop_db()].
+    pub fn close_db(&self, db: Database<'_>) -> Result<()> {
+        self.env.release_dbi(db, |dbi| {
+            mdbx_result(unsafe { ffi::mdbx_dbi_close(self.env.env(), dbi) })?;
+            Ok(())
+        })
+    }
+}
 
-        Ok(())
+/// A shared read-only transaction.
+///
+/// Clones refer to the same transaction, which is aborted once the last clone is dropped.
+pub struct ArcRoTransaction<'env, E>(Arc<Transaction<'env, RO, E>>)
+where
+    E: EnvironmentKind;
+
+impl<'env, E> Clone for ArcRoTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'env, E> Deref for ArcRoTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    type Target = Transaction<'env, RO, E>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'env, E> From<Transaction<'env, RO, E>> for ArcRoTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    fn from(txn: Transaction<'env, RO, E>) -> Self {
+        txn.into_arc()
+    }
+}
+
+impl<'env, E> fmt::Debug for ArcRoTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ArcRoTransaction").field(&*self.0).finish()
     }
 }
 
@@ -418,21 +1099,11 @@ impl<'env> Transaction<'env, RW, NoWriteMap> {
     /// Begins a new nested transaction inside of this transaction.
     pub fn begin_nested_txn(&mut self) -> Result<Transaction<'_, RW, NoWriteMap>> {
         txn_execute(&self.txn, |txn| {
-            let (tx, rx) = sync_channel(0);
-            self.env
-                .txn_manager
-                .as_ref()
-                .unwrap()
-                .send(TxnManagerMessage::Begin {
-                    parent: TxnPtr(txn),
-                    flags: RW::OPEN_FLAGS,
-                    sender: tx,
-                })
-                .unwrap();
-
-            rx.recv()
-                .unwrap()
-                .map(|ptr| Transaction::new_from_ptr(self.env, ptr.0))
+            self.env.begin_txn_ptr(txn, RW::OPEN_FLAGS).map(|ptr| {
+                let mut nested = Transaction::new_from_ptr(self.env, ptr.0, txn);
+                nested.nested = true;
+                nested
+            })
         })
     }
 }
@@ -453,25 +1124,31 @@ where
     E: EnvironmentKind,
 {
     fn drop(&mut self) {
+        // Committed transactions were untracked before being finished.
+        if self.committed {
+            return;
+        }
+        // Inherited from the parent process, see [Environment#fork-safety].
+        if self.env.is_inherited() {
+            return;
+        }
         txn_execute(&self.txn, |txn| {
-            if !self.committed {
-                if K::ONLY_CLEAN {
-                    unsafe {
-                        ffi::mdbx_txn_abort(txn);
-                    }
-                } else {
-                    let (sender, rx) = sync_channel(0);
-                    self.env
-                        .txn_manager
-                        .as_ref()
-                        .unwrap()
-                        .send(TxnManagerMessage::Abort {
-                            tx: TxnPtr(txn),
-                            sender,
-                        })
-                        .unwrap();
-                    rx.recv().unwrap().unwrap();
+            if K::ONLY_CLEAN {
+                self.env.untrack_txn(txn, !K::ONLY_CLEAN);
+                unsafe {
+                    ffi::mdbx_txn_abort(txn);
                 }
+            } else if self.on_owner_thread() {
+                self.env.untrack_txn(txn, !K::ONLY_CLEAN);
+                self.env.abort_txn_ptr(txn).unwrap();
+            } else {
+                // MDBX can only release the writer lock on the thread which took it.
+                log::error!(
+                    target: "libmdbx",
+                    "read-write transaction {} dropped on a thread which did not start it, \
+                     leaking it with the writer lock held",
+                    unsafe { ffi::mdbx_txn_id(txn) }
+                );
             }
         })
     }
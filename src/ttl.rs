@@ -0,0 +1,173 @@
+//! Expiring entries.
+//!
+//! Expiry times of entries written with [Transaction::put_with_ttl()] are kept in a companion
+//! database named [TTL_DB_NAME], which must be allowed for through
+//! [EnvironmentBuilder::set_max_dbs()](crate::EnvironmentBuilder::set_max_dbs). Expired entries
+//! stay readable until they are removed with [Transaction::purge_expired()].
+
+use crate::{
+    environment::dbi_name,
+    keys::{KeyBuilder, KeyReader},
+    Database, DatabaseFlags, Encodable, Environment, EnvironmentKind, Error, Result, Transaction,
+    WriteFlags, RW,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name of the database holding expiry times.
+pub const TTL_DB_NAME: &str = "__ttl";
+
+const BY_EXPIRY: u32 = 0;
+const BY_KEY: u32 = 1;
+
+fn to_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+fn by_key(table: &str, key: &[u8]) -> Vec<u8> {
+    KeyBuilder::new()
+        .u32(BY_KEY)
+        .bytes(table.as_bytes())
+        .raw(key)
+        .finish()
+}
+
+fn by_expiry(expiry: u64, table: &str, key: &[u8]) -> Vec<u8> {
+    KeyBuilder::new()
+        .u32(BY_EXPIRY)
+        .u64(expiry)
+        .bytes(table.as_bytes())
+        .raw(key)
+        .finish()
+}
+
+impl<'env, E> Transaction<'env, RW, E>
+where
+    E: EnvironmentKind,
+{
+    fn ttl_db(&self) -> Result<Database<'_>> {
+        self.create_db(Some(TTL_DB_NAME), DatabaseFlags::empty())
+    }
+
+    fn table_name(&self, db: &Database<'_>) -> String {
+        unsafe { dbi_name(self.txn(), db.dbi()) }.unwrap_or_default()
+    }
+
+    /// Stores an item into a database, to be removed by [Transaction::purge_expired()] once `ttl`
+    /// has passed.
+    ///
+    /// Storing the key again replaces its expiry time.
+    pub fn put_with_ttl<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+        data: impl AsRef<[u8]>,
+        ttl: Duration,
+        flags: WriteFlags,
+    ) -> Result<()> {
+        let key = key.encode();
+        let key = key.as_ref();
+        self.put(db, key, data, flags)?;
+
+        let ttl_db = self.ttl_db()?;
+        let table = self.table_name(db);
+        let by_key = by_key(&table, key);
+        if let Some(old) = self.get::<u64>(&ttl_db, &by_key)? {
+            self.del(&ttl_db, by_expiry(old, &table, key), None)?;
+        }
+        let expiry = SystemTime::now()
+            .checked_add(ttl)
+            .map_or(u64::MAX, to_millis);
+        self.put(&ttl_db, &by_key, expiry.encode(), WriteFlags::empty())?;
+        self.put(
+            &ttl_db,
+            by_expiry(expiry, &table, key),
+            b"",
+            WriteFlags::empty(),
+        )
+    }
+
+    /// Removes the expiry time of a key, so it is kept until deleted explicitly.
+    ///
+    /// This should be called when deleting an entry written by [Transaction::put_with_ttl()] or
+    /// overwriting it with a plain [Transaction::put()]. Returns `false` if the key had no expiry
+    /// time.
+    pub fn clear_ttl<'txn>(&'txn self, db: &Database<'txn>, key: impl Encodable) -> Result<bool> {
+        let key = key.encode();
+        let key = key.as_ref();
+        let ttl_db = self.ttl_db()?;
+        let table = self.table_name(db);
+        let by_key = by_key(&table, key);
+        match self.get::<u64>(&ttl_db, &by_key)? {
+            Some(expiry) => {
+                self.del(&ttl_db, by_expiry(expiry, &table, key), None)?;
+                self.del(&ttl_db, &by_key, None)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Returns the time at which a key written with [Transaction::put_with_ttl()] expires.
+    pub fn expiry<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl Encodable,
+    ) -> Result<Option<SystemTime>> {
+        let ttl_db = self.ttl_db()?;
+        let by_key = by_key(&self.table_name(db), key.encode().as_ref());
+        Ok(self
+            .get::<u64>(&ttl_db, by_key)?
+            .map(|expiry| UNIX_EPOCH + Duration::from_millis(expiry)))
+    }
+
+    /// Deletes all entries which expired at or before `now`, returning their number.
+    ///
+    /// All duplicates of expired keys in [DatabaseFlags::DUP_SORT] databases are deleted. Expiry
+    /// times of entries in databases which were dropped are removed without being counted.
+    pub fn purge_expired(&self, now: SystemTime) -> Result<usize> {
+        let ttl_db = self.ttl_db()?;
+        let now = to_millis(now);
+        let start = KeyBuilder::new().u32(BY_EXPIRY).finish();
+
+        let mut expired = Vec::new();
+        for item in self.cursor(&ttl_db)?.iter_from::<Vec<u8>, ()>(&start) {
+            let (entry, ()) = item?;
+            let mut reader = KeyReader::new(&entry);
+            if reader.u32()? != BY_EXPIRY || reader.u64()? > now {
+                break;
+            }
+            let table = String::from_utf8(reader.bytes()?).unwrap_or_default();
+            expired.push((table, reader.rest().to_vec(), entry));
+        }
+
+        let mut purged = 0;
+        for (table, key, entry) in &expired {
+            match self.open_db((!table.is_empty()).then_some(table.as_str())) {
+                Ok(db) => {
+                    self.del(&db, key, None)?;
+                    purged += 1;
+                }
+                Err(Error::NotFound) => {}
+                Err(error) => return Err(error),
+            }
+            self.del(&ttl_db, by_key(table, key), None)?;
+            self.del(&ttl_db, entry, None)?;
+        }
+        Ok(purged)
+    }
+}
+
+impl<E> Environment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Deletes all expired entries in a new read-write transaction, returning their number.
+    ///
+    /// See [Transaction::purge_expired()].
+    pub fn purge_expired(&self) -> Result<usize> {
+        self.update(|txn| txn.purge_expired(SystemTime::now()))
+    }
+}
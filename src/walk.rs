@@ -0,0 +1,216 @@
+use crate::{
+    error::mdbx_result, transaction::txn_execute, Environment, EnvironmentKind, Error, Result,
+    Transaction, TransactionKind,
+};
+use libc::{c_char, c_int, c_uint, c_void};
+use std::{
+    any::Any,
+    borrow::Cow,
+    ffi::CStr,
+    ops::ControlFlow,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// Kind of a page visited by [Transaction::walk_pages()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PageType {
+    /// A page which could not be read, see [PageVisit::error].
+    Broken,
+    Meta,
+    /// A run of overflow pages holding a single large value.
+    Large,
+    Branch,
+    Leaf,
+    /// A leaf of a [DatabaseFlags::DUP_FIXED](crate::DatabaseFlags::DUP_FIXED) database.
+    DupFixedLeaf,
+    /// Duplicates of a key stored inline in a leaf page.
+    SubpageLeaf,
+    /// Fixed-size duplicates of a key stored inline in a leaf page.
+    SubpageDupFixedLeaf,
+    /// Inline duplicates which could not be read, see [PageVisit::error].
+    SubpageBroken,
+}
+
+impl PageType {
+    fn from_mdbx(page_type: ffi::MDBX_page_type_t) -> Self {
+        match page_type {
+            ffi::MDBX_page_meta => PageType::Meta,
+            ffi::MDBX_page_large => PageType::Large,
+            ffi::MDBX_page_branch => PageType::Branch,
+            ffi::MDBX_page_leaf => PageType::Leaf,
+            ffi::MDBX_page_dupfixed_leaf => PageType::DupFixedLeaf,
+            ffi::MDBX_subpage_leaf => PageType::SubpageLeaf,
+            ffi::MDBX_subpage_dupfixed_leaf => PageType::SubpageDupFixedLeaf,
+            ffi::MDBX_subpage_broken => PageType::SubpageBroken,
+            _ => PageType::Broken,
+        }
+    }
+
+    /// Whether the page is stored inline in a leaf page rather than being a page of its own.
+    pub fn is_subpage(&self) -> bool {
+        matches!(
+            self,
+            PageType::SubpageLeaf | PageType::SubpageDupFixedLeaf | PageType::SubpageBroken
+        )
+    }
+}
+
+/// Owner of a page visited by [Transaction::walk_pages()].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PageOwner<'a> {
+    /// The meta pages at the start of the file.
+    Meta,
+    /// The garbage collector, which tracks freed pages.
+    Gc,
+    /// The main database.
+    Main,
+    /// A named database.
+    Table(Cow<'a, str>),
+}
+
+impl<'a> PageOwner<'a> {
+    unsafe fn from_mdbx(dbi: *const c_char) -> Self {
+        match dbi as isize {
+            0 => PageOwner::Main,
+            -1 => PageOwner::Gc,
+            -2 => PageOwner::Meta,
+            _ => PageOwner::Table(CStr::from_ptr(dbi).to_string_lossy()),
+        }
+    }
+
+    /// Converts into an owner which does not borrow the name of the database.
+    pub fn into_owned(self) -> PageOwner<'static> {
+        match self {
+            PageOwner::Meta => PageOwner::Meta,
+            PageOwner::Gc => PageOwner::Gc,
+            PageOwner::Main => PageOwner::Main,
+            PageOwner::Table(name) => PageOwner::Table(Cow::Owned(name.into_owned())),
+        }
+    }
+}
+
+/// A page, or run of pages, visited by [Transaction::walk_pages()].
+#[derive(Debug)]
+pub struct PageVisit<'a> {
+    /// Number of the first page.
+    pub pgno: u64,
+    /// Number of consecutive pages, greater than one only for [PageType::Large].
+    pub pages: u32,
+    /// Depth in the B-tree of the owner, starting at one for the root.
+    pub depth: i32,
+    pub owner: PageOwner<'a>,
+    /// Size in bytes of all pages, or of the inline data for subpages.
+    pub size: usize,
+    pub page_type: PageType,
+    /// Problem found with the page.
+    pub error: Option<Error>,
+    /// Number of items on the page.
+    pub entries: usize,
+    /// Bytes used by keys and values.
+    pub payload_bytes: usize,
+    /// Bytes used by page headers and item pointers.
+    pub header_bytes: usize,
+    /// Bytes left free.
+    pub unused_bytes: usize,
+}
+
+struct WalkContext<'a> {
+    visitor: &'a mut dyn FnMut(PageVisit<'_>) -> ControlFlow<()>,
+    panic: Option<Box<dyn Any + Send>>,
+    stopped: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn visit_page(
+    pgno: u64,
+    number: c_uint,
+    ctx: *mut c_void,
+    deep: c_int,
+    dbi: *const c_char,
+    page_size: usize,
+    page_type: ffi::MDBX_page_type_t,
+    err: ffi::MDBX_error_t,
+    nentries: usize,
+    payload_bytes: usize,
+    header_bytes: usize,
+    unused_bytes: usize,
+) -> c_int {
+    let ctx = &mut *(ctx as *mut WalkContext);
+    let visit = PageVisit {
+        pgno,
+        pages: number,
+        depth: deep,
+        owner: PageOwner::from_mdbx(dbi),
+        size: page_size,
+        page_type: PageType::from_mdbx(page_type),
+        error: (err != ffi::MDBX_SUCCESS).then(|| Error::from_err_code(err)),
+        entries: nentries,
+        payload_bytes,
+        header_bytes,
+        unused_bytes,
+    };
+    match panic::catch_unwind(AssertUnwindSafe(|| (ctx.visitor)(visit))) {
+        Ok(ControlFlow::Continue(())) => return ffi::MDBX_SUCCESS,
+        Ok(ControlFlow::Break(())) => {}
+        Err(e) => ctx.panic = Some(e),
+    }
+    // Only an error stops the walk.
+    ctx.stopped = true;
+    ffi::MDBX_EINTR
+}
+
+impl<'env, K, E> Transaction<'env, K, E>
+where
+    K: TransactionKind,
+    E: EnvironmentKind,
+{
+    /// Visits every page reachable in the snapshot of this transaction: the meta pages, the
+    /// garbage collector and all databases.
+    ///
+    /// Broken pages are reported through [PageVisit::error] instead of failing the walk. With
+    /// `check_keys_ordering` the order of keys on each page is verified as well. Returning
+    /// [ControlFlow::Break] from `visitor` stops the walk.
+    pub fn walk_pages(
+        &self,
+        check_keys_ordering: bool,
+        mut visitor: impl FnMut(PageVisit<'_>) -> ControlFlow<()>,
+    ) -> Result<()> {
+        let mut ctx = WalkContext {
+            visitor: &mut visitor,
+            panic: None,
+            stopped: false,
+        };
+        let res = txn_execute(&self.txn_mutex(), |txn| unsafe {
+            ffi::mdbx_env_pgwalk(
+                txn,
+                Some(visit_page),
+                &mut ctx as *mut WalkContext as *mut c_void,
+                !check_keys_ordering,
+            )
+        });
+        if let Some(e) = ctx.panic {
+            panic::resume_unwind(e);
+        }
+        if ctx.stopped {
+            return Ok(());
+        }
+        mdbx_result(res).map(|_| ())
+    }
+}
+
+impl<E> Environment<E>
+where
+    E: EnvironmentKind,
+{
+    /// Visits every page of the environment in a new read-only transaction.
+    ///
+    /// See [Transaction::walk_pages()].
+    pub fn walk_pages(
+        &self,
+        check_keys_ordering: bool,
+        visitor: impl FnMut(PageVisit<'_>) -> ControlFlow<()>,
+    ) -> Result<()> {
+        self.begin_ro_txn()?
+            .walk_pages(check_keys_ordering, visitor)
+    }
+}
@@ -1,5 +1,8 @@
 use libmdbx::*;
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    ops::{Bound, RangeFull},
+};
 use tempfile::tempdir;
 
 type Environment = libmdbx::Environment<NoWriteMap>;
@@ -46,7 +49,7 @@ fn test_get_dup() {
     txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
     txn.put(&db, b"key2", b"val3", WriteFlags::empty()).unwrap();
 
-    let mut cursor = txn.cursor(&db).unwrap();
+    let mut cursor = txn.dup_cursor(&db).unwrap();
     assert_eq!(cursor.first().unwrap(), Some((*b"key1", *b"val1")));
     assert_eq!(cursor.first_dup().unwrap(), Some(*b"val1"));
     assert_eq!(cursor.get_current().unwrap(), Some((*b"key1", *b"val1")));
@@ -98,7 +101,7 @@ fn test_get_dupfixed() {
     txn.put(&db, b"key2", b"val5", WriteFlags::empty()).unwrap();
     txn.put(&db, b"key2", b"val6", WriteFlags::empty()).unwrap();
 
-    let mut cursor = txn.cursor(&db).unwrap();
+    let mut cursor = txn.dup_cursor(&db).unwrap();
     assert_eq!(cursor.first().unwrap(), Some((*b"key1", *b"val1")));
     assert_eq!(cursor.get_multiple().unwrap(), Some(*b"val1val2val3"));
     assert_eq!(cursor.next_multiple::<(), ()>().unwrap(), None);
@@ -174,6 +177,71 @@ fn test_iter() {
     );
 }
 
+#[test]
+fn test_iter_batched() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let items = (0..2000u32)
+        .map(|i| (i.encode(), format!("value{i}").into_bytes()))
+        .collect::<Vec<_>>();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert!(cursor.iter_batched::<(), ()>(16).next().is_none());
+    for (key, value) in &items {
+        txn.put(&db, key, value, WriteFlags::empty()).unwrap();
+    }
+    // Dirty pages of a write transaction are read the same way.
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(
+        items,
+        cursor.iter_batched(64).collect::<Result<Vec<_>>>().unwrap()
+    );
+    drop(cursor);
+    drop(db);
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for batch in [0, 3, 100, 10_000] {
+        let mut cursor = txn.cursor(&db).unwrap();
+        assert_eq!(
+            items,
+            cursor
+                .iter_batched(batch)
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+        );
+        assert!(cursor.iter_batched::<(), ()>(batch).next().is_none());
+    }
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    cursor.set::<()>(&1000u32.encode()).unwrap();
+    assert_eq!(
+        items[1001..],
+        cursor.iter_batched(7).collect::<Result<Vec<_>>>().unwrap()
+    );
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.create_db(Some("dup"), DatabaseFlags::DUP_SORT).unwrap();
+    let dup_items = [
+        (*b"a", *b"1"),
+        (*b"a", *b"2"),
+        (*b"b", *b"1"),
+        (*b"c", *b"1"),
+    ];
+    for (key, value) in &dup_items {
+        txn.put(&db, key, value, WriteFlags::empty()).unwrap();
+    }
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(
+        dup_items.to_vec(),
+        cursor.iter_batched(3).collect::<Result<Vec<_>>>().unwrap()
+    );
+}
+
 #[test]
 fn test_iter_empty_database() {
     let dir = tempdir().unwrap();
@@ -198,7 +266,7 @@ fn test_iter_empty_dup_database() {
 
     let txn = env.begin_ro_txn().unwrap();
     let db = txn.open_db(None).unwrap();
-    let mut cursor = txn.cursor(&db).unwrap();
+    let mut cursor = txn.dup_cursor(&db).unwrap();
 
     assert!(cursor.iter::<(), ()>().next().is_none());
     assert!(cursor.iter_start::<(), ()>().next().is_none());
@@ -252,7 +320,7 @@ fn test_iter_dup() {
 
     let txn = env.begin_ro_txn().unwrap();
     let db = txn.open_db(None).unwrap();
-    let mut cursor = txn.cursor(&db).unwrap();
+    let mut cursor = txn.dup_cursor(&db).unwrap();
     assert_eq!(
         items,
         cursor
@@ -353,7 +421,7 @@ fn test_iter_del_get() {
         let txn = env.begin_rw_txn().unwrap();
         let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
         assert_eq!(
-            txn.cursor(&db)
+            txn.dup_cursor(&db)
                 .unwrap()
                 .iter_dup_of::<(), ()>(b"a")
                 .collect::<Result<Vec<_>>>()
@@ -375,7 +443,7 @@ fn test_iter_del_get() {
 
     let txn = env.begin_rw_txn().unwrap();
     let db = txn.open_db(None).unwrap();
-    let mut cursor = txn.cursor(&db).unwrap();
+    let mut cursor = txn.dup_cursor(&db).unwrap();
     assert_eq!(
         items,
         cursor
@@ -438,3 +506,426 @@ fn test_put_del() {
         )
     );
 }
+
+#[test]
+fn test_delete_range() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for i in 0..10u8 {
+        txn.put(&db, [b'k', i], [i], WriteFlags::empty()).unwrap();
+    }
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(
+        cursor
+            .delete_range((
+                Bound::Included(&b"k\x02"[..]),
+                Bound::Excluded(&b"k\x05"[..])
+            ))
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        cursor
+            .delete_range((
+                Bound::Excluded(&b"k\x06"[..]),
+                Bound::Included(&b"k\x08"[..])
+            ))
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        cursor
+            .delete_range((Bound::Included(&b"z"[..]), Bound::Unbounded))
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        cursor
+            .iter_start::<[u8; 2], ()>()
+            .map(|res| res.map(|(key, ())| key[1]))
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+        vec![0, 1, 5, 6, 9]
+    );
+
+    assert_eq!(cursor.delete_range::<RangeFull>(..).unwrap(), 5);
+    assert_eq!(cursor.first::<(), ()>().unwrap(), None);
+}
+
+#[test]
+fn test_delete_range_dup() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+    for key in [b"a", b"b", b"c"] {
+        for data in [b"1", b"2", b"3"] {
+            txn.put(&db, key, data, WriteFlags::empty()).unwrap();
+        }
+    }
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(
+        cursor
+            .delete_range((Bound::Unbounded, Bound::Included(&b"b"[..])))
+            .unwrap(),
+        6
+    );
+    assert_eq!(
+        cursor.iter_start().collect::<Result<Vec<_>>>().unwrap(),
+        vec![(*b"c", *b"1"), (*b"c", *b"2"), (*b"c", *b"3")]
+    );
+}
+
+#[test]
+fn test_seek_exact() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+    txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+    txn.put(&db, b"key1", b"val2", WriteFlags::empty()).unwrap();
+    txn.put(&db, b"key3", b"val3", WriteFlags::empty()).unwrap();
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(
+        cursor.seek_exact(b"key1").unwrap(),
+        Some((*b"key1", *b"val1"))
+    );
+    assert_eq!(cursor.seek_exact::<(), ()>(b"key2").unwrap(), None);
+    assert_eq!(
+        cursor.seek_exact(b"key3").unwrap(),
+        Some((*b"key3", *b"val3"))
+    );
+
+    assert_eq!(
+        cursor.seek_exact_dup(b"key1", b"val2").unwrap(),
+        Some((*b"key1", *b"val2"))
+    );
+    assert_eq!(cursor.next().unwrap(), Some((*b"key3", *b"val3")));
+    assert_eq!(
+        cursor.seek_exact_dup::<(), ()>(b"key1", b"val3").unwrap(),
+        None
+    );
+    assert_eq!(
+        cursor.seek_exact_dup::<(), ()>(b"key2", b"val1").unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_reserve() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let mut cursor = txn.cursor(&db).unwrap();
+
+    for i in 0..3u8 {
+        let buf = cursor.reserve(&[b'k', i], 4, WriteFlags::APPEND).unwrap();
+        buf.copy_from_slice(&[i; 4]);
+        assert_eq!(cursor.get_current().unwrap(), Some(([b'k', i], [i; 4])));
+    }
+    drop(cursor);
+
+    assert_eq!(txn.get(&db, b"k\x01").unwrap(), Some([1u8; 4]));
+}
+
+#[test]
+fn test_try_clone() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+    txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(cursor.first().unwrap(), Some((*b"key1", *b"val1")));
+
+    let mut cloned = cursor.try_clone().unwrap();
+    assert_eq!(cloned.get_current().unwrap(), Some((*b"key1", *b"val1")));
+    assert_eq!(cloned.next().unwrap(), Some((*b"key2", *b"val2")));
+    assert_eq!(cursor.get_current().unwrap(), Some((*b"key1", *b"val1")));
+
+    // The transaction can no longer be used, so the copy fails.
+    assert_eq!(unsafe { ffi::mdbx_txn_break(txn.txn()) }, 0);
+    assert!(matches!(cursor.try_clone(), Err(Error::BadTxn)));
+}
+
+#[test]
+fn test_get_sorted() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.create_db(Some("dup"), DatabaseFlags::DUP_SORT).unwrap();
+    for i in (0..100u8).step_by(2) {
+        txn.put(&db, [i], [i], WriteFlags::empty()).unwrap();
+        txn.put(&db, [i], [i + 1], WriteFlags::empty()).unwrap();
+    }
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(
+        cursor
+            .get_sorted::<_, [u8; 1]>([[2], [3], [4], [4], [60]])
+            .unwrap(),
+        [Some([2]), None, Some([4]), Some([4]), Some([60])]
+    );
+    // The cursor moved forward to the last key.
+    assert_eq!(
+        cursor.get_current::<[u8; 1], [u8; 1]>().unwrap(),
+        Some(([60], [60]))
+    );
+    assert_eq!(
+        cursor.get_sorted::<_, [u8; 1]>([[61], [99]]).unwrap(),
+        [None, None]
+    );
+    assert_eq!(cursor.get_current::<[u8; 1], [u8; 1]>().unwrap(), None);
+    assert!(matches!(
+        cursor.get_sorted::<_, ()>([[4], [2]]),
+        Err(Error::InvalidValue)
+    ));
+}
+
+#[test]
+fn test_iter_size_hint() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for i in 0..10u8 {
+        txn.put(&db, [i], [i], WriteFlags::empty()).unwrap();
+    }
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    let mut iter = cursor.iter::<(), ()>();
+    assert_eq!(iter.size_hint(), (10, Some(10)));
+    iter.next().unwrap().unwrap();
+    assert_eq!(iter.size_hint(), (9, Some(10)));
+    assert_eq!(iter.count(), 9);
+    // The cursor is at the end.
+    assert_eq!(cursor.iter::<(), ()>().size_hint(), (0, Some(10)));
+
+    assert_eq!(cursor.iter_start::<(), ()>().size_hint(), (10, Some(10)));
+    assert_eq!(cursor.iter_from::<(), ()>(&[3]).size_hint(), (7, Some(10)));
+    assert_eq!(cursor.iter_from::<(), ()>(&[10]).size_hint(), (0, Some(10)));
+    cursor.set::<()>(&[3]).unwrap();
+    assert_eq!(cursor.iter::<(), ()>().size_hint(), (6, Some(10)));
+    let mut iter = cursor.iter_batched::<(), ()>(4);
+    assert_eq!(iter.size_hint(), (6, Some(10)));
+    iter.next().unwrap().unwrap();
+    assert_eq!(iter.size_hint(), (5, Some(10)));
+    assert_eq!(iter.count(), 5);
+
+    // Collecting preallocates.
+    let items = txn
+        .cursor(&db)
+        .unwrap()
+        .into_iter()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(items.capacity(), 10);
+
+    let db = txn.create_db(Some("dup"), DatabaseFlags::DUP_SORT).unwrap();
+    for i in 0..5u8 {
+        txn.put(&db, b"a", [i], WriteFlags::empty()).unwrap();
+    }
+    txn.put(&db, b"b", b"0", WriteFlags::empty()).unwrap();
+    let mut cursor = txn.dup_cursor(&db).unwrap();
+    let mut iter = cursor.iter_dup_of::<(), ()>(b"a");
+    assert_eq!(iter.size_hint(), (5, Some(5)));
+    iter.next().unwrap().unwrap();
+    assert_eq!(iter.size_hint(), (0, Some(5)));
+    assert_eq!(iter.count(), 4);
+}
+
+#[test]
+fn test_update_with() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"key1", 1u64.to_be_bytes(), WriteFlags::empty())
+        .unwrap();
+    txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    let increment = |value: Option<&[u8]>| {
+        let n = value.map_or(0, |v| u64::from_be_bytes(v.try_into().unwrap()));
+        Some((n + 1).to_be_bytes().to_vec())
+    };
+    cursor.update_with(b"key1", increment).unwrap();
+    cursor.update_with(b"key3", increment).unwrap();
+    cursor.update_with(b"key2", |_| None).unwrap();
+    cursor.update_with(b"key4", |_| None).unwrap();
+
+    assert_eq!(
+        txn.get::<[u8; 8]>(&db, b"key1").unwrap(),
+        Some(2u64.to_be_bytes())
+    );
+    assert_eq!(txn.get::<()>(&db, b"key2").unwrap(), None);
+    assert_eq!(
+        txn.get::<[u8; 8]>(&db, b"key3").unwrap(),
+        Some(1u64.to_be_bytes())
+    );
+    assert_eq!(txn.get::<()>(&db, b"key4").unwrap(), None);
+}
+
+#[test]
+fn test_retain() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for i in 0..10u8 {
+        txn.put(&db, [b'k', i], [i], WriteFlags::empty()).unwrap();
+    }
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(
+        cursor
+            .retain_range(
+                (
+                    Bound::Excluded(&b"k\x01"[..]),
+                    Bound::Included(&b"k\x05"[..])
+                ),
+                |_, data| data[0] % 2 == 0
+            )
+            .unwrap(),
+        RetainStats {
+            scanned: 4,
+            retained: 2,
+            deleted: 2,
+        }
+    );
+    assert_eq!(
+        cursor.retain(|key, _| key[1] < 8).unwrap(),
+        RetainStats {
+            scanned: 8,
+            retained: 6,
+            deleted: 2,
+        }
+    );
+    assert_eq!(
+        cursor
+            .iter_start::<[u8; 2], ()>()
+            .map(|res| res.map(|(key, ())| key[1]))
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+        vec![0, 1, 2, 4, 6, 7]
+    );
+}
+
+#[test]
+fn test_dup_cursor_requires_dup_sort() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert!(matches!(txn.dup_cursor(&db), Err(Error::Incompatible)));
+
+    let db = txn.create_db(Some("dup"), DatabaseFlags::DUP_SORT).unwrap();
+    txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+    txn.put(&db, b"key1", b"val2", WriteFlags::empty()).unwrap();
+
+    let mut cursor = txn.dup_cursor(&db).unwrap();
+    assert_eq!(cursor.first().unwrap(), Some((*b"key1", *b"val1")));
+    assert_eq!(cursor.next_dup().unwrap(), Some((*b"key1", *b"val2")));
+    assert_eq!(
+        cursor.into_inner().prev().unwrap(),
+        Some((*b"key1", *b"val1"))
+    );
+}
+
+#[test]
+fn test_incompatible_op_is_error() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+    txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+    txn.put(&db, b"key1", b"val2", WriteFlags::empty()).unwrap();
+
+    let mut cursor = txn.dup_cursor(&db).unwrap();
+    assert_eq!(cursor.first().unwrap(), Some((*b"key1", *b"val1")));
+    assert!(matches!(
+        cursor.get_multiple::<()>(),
+        Err(Error::Incompatible)
+    ));
+    assert!(matches!(
+        cursor.next_multiple::<(), ()>(),
+        Err(Error::Incompatible)
+    ));
+    assert_eq!(cursor.get_both::<()>(b"key2", b"val1").unwrap(), None);
+}
+
+#[test]
+fn test_prefetch_hint() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for i in 0..1000u32 {
+        txn.put(&db, i.encode(), [0; 64], WriteFlags::empty())
+            .unwrap();
+    }
+    // Pages modified by the transaction are skipped.
+    let mut cursor = txn.cursor(&db).unwrap();
+    cursor.first::<(), ()>().unwrap();
+    cursor.prefetch_hint(8).unwrap();
+    drop(cursor);
+    drop(db);
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let mut cursor = txn.cursor(&db).unwrap();
+    cursor.prefetch_hint(8).unwrap();
+    cursor.first::<(), ()>().unwrap();
+    cursor.prefetch_hint(8).unwrap();
+    // Past the end of the memory map.
+    cursor.prefetch_hint(usize::MAX).unwrap();
+    assert_eq!(cursor.iter_start::<(), ()>().count(), 1000);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_dup_methods() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+    txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+    txn.put(&db, b"key1", b"val2", WriteFlags::empty()).unwrap();
+    txn.put(&db, b"key2", b"val3", WriteFlags::empty()).unwrap();
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(cursor.first().unwrap(), Some((*b"key1", *b"val1")));
+    assert_eq!(cursor.last_dup().unwrap(), Some(*b"val2"));
+    assert_eq!(cursor.prev_dup().unwrap(), Some((*b"key1", *b"val1")));
+    assert_eq!(cursor.get_both(b"key1", b"val2").unwrap(), Some(*b"val2"));
+    assert_eq!(
+        cursor
+            .iter_dup_of::<(), [u8; 4]>(b"key1")
+            .map(|item| item.unwrap().1)
+            .collect::<Vec<_>>(),
+        vec![*b"val1", *b"val2"]
+    );
+    assert_eq!(cursor.iter_dup_start::<(), ()>().count(), 2);
+}
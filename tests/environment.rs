@@ -1,5 +1,6 @@
 use byteorder::{ByteOrder, LittleEndian};
 use libmdbx::*;
+use std::{mem, sync::Arc, thread, time::Duration};
 use tempfile::tempdir;
 
 type Environment = libmdbx::Environment<NoWriteMap>;
@@ -48,6 +49,50 @@ fn test_begin_txn() {
     }
 }
 
+#[test]
+fn test_update_view() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    env.update(|txn| {
+        let db = txn.open_db(None)?;
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty())
+    })
+    .unwrap();
+
+    // Errors abort the transaction.
+    let res = env.update(|txn| {
+        let db = txn.open_db(None)?;
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty())?;
+        Err::<(), _>(Error::Problem)
+    });
+    assert!(matches!(res, Err(Error::Problem)));
+
+    // So do panics.
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        env.update::<(), Error>(|txn| {
+            let db = txn.open_db(None)?;
+            txn.put(&db, b"key3", b"val3", WriteFlags::empty())?;
+            panic!("boom");
+        })
+    }));
+    assert!(res.is_err());
+
+    let (val1, val2, val3) = env
+        .view(|txn| {
+            let db = txn.open_db(None)?;
+            Ok::<_, Error>((
+                txn.get::<Vec<u8>>(&db, b"key1")?,
+                txn.get::<Vec<u8>>(&db, b"key2")?,
+                txn.get::<Vec<u8>>(&db, b"key3")?,
+            ))
+        })
+        .unwrap();
+    assert_eq!(val1.as_deref(), Some(&b"val1"[..]));
+    assert_eq!(val2, None);
+    assert_eq!(val3, None);
+}
+
 #[test]
 fn test_open_db() {
     let dir = tempdir().unwrap();
@@ -81,6 +126,248 @@ fn test_close_database() {
     txn.open_db(Some("db")).unwrap();
 }
 
+#[test]
+fn test_subscribe() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    let commits = env.subscribe();
+
+    let mut txn = env.begin_rw_txn().unwrap();
+    let txnid = txn.id();
+    {
+        let nested = txn.begin_nested_txn().unwrap();
+        let db = nested.open_db(None).unwrap();
+        nested
+            .put(&db, b"key1", b"val1", WriteFlags::empty())
+            .unwrap();
+        nested.commit().unwrap();
+    }
+    txn.commit().unwrap();
+    assert_eq!(commits.try_recv(), Ok(txnid));
+    assert!(commits.try_recv().is_err());
+
+    // Aborted transactions are not reported.
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+    drop(txn);
+    assert!(commits.try_recv().is_err());
+
+    drop(commits);
+    env.update(|txn| {
+        let db = txn.open_db(None)?;
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty())
+    })
+    .unwrap();
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_temp_environment() {
+    use libmdbx::test_util::TempEnvironment;
+
+    let path = {
+        let env = TempEnvironment::<NoWriteMap>::new().unwrap();
+        env.put(None, b"key1", b"val1").unwrap();
+        env.put_all(Some("named"), [(1u32, b"one"), (2u32, b"two")])
+            .unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(Some("named")).unwrap();
+        assert_eq!(txn.get::<Vec<u8>>(&db, 2u32).unwrap().unwrap(), b"two");
+        assert!(env.path().exists());
+        env.path().to_path_buf()
+    };
+    assert!(!path.exists());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_environment() {
+    use futures_core::Stream;
+    use std::{future::poll_fn, pin::Pin};
+
+    let dir = tempdir().unwrap();
+    let env = AsyncEnvironment::new(Environment::new().open(dir.path()).unwrap());
+
+    env.write(|txn| {
+        let db = txn.open_db(None)?;
+        for i in 0..100u32 {
+            txn.put(&db, i.encode(), (i * 2).encode(), WriteFlags::empty())?;
+        }
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    let res = env
+        .write(|txn| {
+            let db = txn.open_db(None)?;
+            txn.put(&db, 100u32.encode(), b"", WriteFlags::empty())?;
+            Err::<(), _>(Error::Problem)
+        })
+        .await;
+    assert!(matches!(res, Err(Error::Problem)));
+
+    let value = env
+        .read(|txn| txn.get::<u32>(&txn.open_db(None)?, 21u32.encode()))
+        .await
+        .unwrap();
+    assert_eq!(value, Some(42));
+
+    let mut stream = env.stream_from::<u32, u32>(None, 90u32.encode());
+    let mut items = Vec::new();
+    while let Some(item) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+        items.push(item.unwrap());
+    }
+    assert_eq!(items, (90..100).map(|i| (i, i * 2)).collect::<Vec<_>>());
+
+    // Spans several batches.
+    let mut stream = env.stream::<u32, u32>(None);
+    let mut items = Vec::new();
+    while let Some(item) = stream.next().await {
+        items.push(item.unwrap());
+    }
+    assert_eq!(items, (0..100).map(|i| (i, i * 2)).collect::<Vec<_>>());
+
+    let mut stream = env.stream::<u32, u32>(Some("missing"));
+    let item = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+    assert!(item.unwrap().is_err());
+}
+
+#[test]
+fn test_batch_writer() {
+    let dir = tempdir().unwrap();
+    let env = Arc::new(Environment::new().open(dir.path()).unwrap());
+    let commits = env.subscribe();
+
+    let writer = BatchWriter::new(env.clone(), 10, Duration::from_secs(1));
+    let receipts = thread::scope(|s| {
+        let handles = (0..5u32)
+            .map(|t| {
+                let writer = &writer;
+                s.spawn(move || {
+                    (0..5u32)
+                        .map(|i| {
+                            writer.put(None, (t * 5 + i).encode(), b"val", WriteFlags::empty())
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    let existing = writer.del(None, 0u32.encode());
+    let missing = writer.del(None, 100u32.encode());
+    let conflict = writer.put(None, 1u32.encode(), b"", WriteFlags::NO_OVERWRITE);
+    for receipt in receipts {
+        receipt.wait().unwrap();
+    }
+    assert!(existing.wait().unwrap());
+    assert!(!missing.wait().unwrap());
+    assert!(matches!(conflict.wait(), Err(Error::KeyExist)));
+    drop(writer);
+
+    // 28 operations in batches of at most 10.
+    assert_eq!(commits.try_iter().count(), 3);
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(txn.db_stat(&db).unwrap().entries(), 24);
+}
+
+#[test]
+fn test_txn_tracking() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    let txn = env.begin_ro_txn().unwrap();
+    assert!(env.open_transactions().is_empty());
+    drop(txn);
+    drop(env);
+
+    let env = Environment::new()
+        .set_txn_tracking(true)
+        .open(dir.path())
+        .unwrap();
+    let ro = env.begin_ro_txn().unwrap();
+    let rw = env.begin_rw_txn().unwrap();
+
+    let mut origins = env.open_transactions();
+    origins.sort_by_key(|origin| origin.read_write);
+    assert_eq!(origins.len(), 2);
+    assert_eq!(origins[0].id, ro.id());
+    assert!(!origins[0].read_write);
+    assert_eq!(origins[1].id, rw.id());
+    assert!(origins[1].read_write);
+    assert_eq!(origins[1].thread, thread::current().id());
+    assert!(env.txn_origin(rw.id()).is_some());
+
+    rw.commit().unwrap();
+    assert_eq!(env.open_transactions().len(), 1);
+    drop(ro);
+    assert!(env.open_transactions().is_empty());
+}
+
+#[test]
+fn test_direct_commit() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new()
+        .set_direct_commit(true)
+        .open(dir.path())
+        .unwrap();
+    assert!(env.is_direct_commit());
+    let commits = env.subscribe();
+
+    let mut txn = env.begin_rw_txn().unwrap();
+    let txnid = txn.id();
+    {
+        let nested = txn.begin_nested_txn().unwrap();
+        let db = nested.open_db(None).unwrap();
+        nested
+            .put(&db, b"key1", b"val1", WriteFlags::empty())
+            .unwrap();
+        nested.commit().unwrap();
+    }
+    txn.commit().unwrap();
+    assert_eq!(commits.try_recv(), Ok(txnid));
+    assert!(commits.try_recv().is_err());
+
+    // Aborting releases the write lock for the next transaction.
+    drop(env.begin_rw_txn().unwrap());
+
+    // Transactions finished on their own thread may run on any thread.
+    let env = Arc::new(env);
+    let writer = {
+        let env = env.clone();
+        thread::spawn(move || {
+            env.update(|txn| {
+                let db = txn.open_db(None)?;
+                txn.put(&db, b"key2", b"val2", WriteFlags::empty())
+            })
+        })
+    };
+    writer.join().unwrap().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
+    assert_eq!(txn.get(&db, b"key2").unwrap(), Some(*b"val2"));
+    drop(db);
+    drop(txn);
+
+    // A transaction committed or dropped on another thread is leaked with the writer lock,
+    // until the environment is closed on the thread which started it.
+    let env = Arc::into_inner(env).unwrap();
+    let txn = env.begin_rw_txn().unwrap();
+    let txnid = txn.id();
+    thread::scope(|s| {
+        s.spawn(|| assert_eq!(txn.commit(), Err(Error::ThreadMismatch(Some(txnid)))));
+    });
+    assert_eq!(env.close().unwrap(), 1);
+}
+
 #[test]
 fn test_sync() {
     let dir = tempdir().unwrap();
@@ -134,6 +421,335 @@ fn test_stat() {
     assert_eq!(stat.entries(), 64);
 }
 
+#[test]
+fn test_begin_rw_txn_timeout() {
+    for direct_commit in [false, true] {
+        let dir = tempdir().unwrap();
+        let env = Environment::new()
+            .set_direct_commit(direct_commit)
+            .open(dir.path())
+            .unwrap();
+
+        let (locked, unlock) = std::sync::mpsc::channel();
+        thread::scope(|s| {
+            let writer = s.spawn(|| {
+                let txn = env.begin_rw_txn().unwrap();
+                locked.send(()).unwrap();
+                thread::sleep(Duration::from_millis(200));
+                txn.commit().unwrap();
+            });
+            unlock.recv().unwrap();
+
+            let start = std::time::Instant::now();
+            assert!(matches!(
+                env.begin_rw_txn_timeout(Duration::from_millis(50)),
+                Err(Error::Busy)
+            ));
+            let elapsed = start.elapsed();
+            assert!(elapsed >= Duration::from_millis(50));
+            assert!(elapsed < Duration::from_millis(150), "{elapsed:?}");
+
+            // Retried until the writer finishes.
+            env.begin_rw_txn_timeout(Duration::from_secs(10))
+                .unwrap()
+                .commit()
+                .unwrap();
+            writer.join().unwrap();
+        });
+    }
+}
+
+#[test]
+fn test_fork() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    let rw_txn = env.begin_rw_txn().unwrap();
+    let ro_txn = env.begin_ro_txn().unwrap();
+    assert!(!env.is_inherited());
+
+    match unsafe { libc::fork() } {
+        0 => {
+            // Other threads of the test harness may hold locks, e.g. of the allocator, so the
+            // child only makes calls which neither allocate nor block, and exits without
+            // dropping anything.
+            let ok = env.is_inherited()
+                && matches!(env.begin_ro_txn(), Err(Error::Inherited))
+                && matches!(env.begin_rw_txn(), Err(Error::Inherited));
+            unsafe { libc::_exit(if ok { 0 } else { 1 }) }
+        }
+        pid => {
+            assert!(pid > 0);
+            let mut status = 0;
+            assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+            assert!(libc::WIFEXITED(status));
+            assert_eq!(libc::WEXITSTATUS(status), 0);
+        }
+    }
+
+    rw_txn.commit().unwrap();
+    drop(ro_txn);
+    env.begin_rw_txn().unwrap().commit().unwrap();
+}
+
+#[test]
+fn test_log_level() {
+    struct Logger(parking_lot::Mutex<Vec<String>>);
+
+    impl log::Log for Logger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == "libmdbx"
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Logger = Logger(parking_lot::const_mutex(Vec::new()));
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+    set_log_level(LogLevel::Extra);
+
+    let dir = tempdir().unwrap();
+    Environment::new().open(dir.path()).unwrap();
+    set_log_level(LogLevel::Warn);
+
+    {
+        let messages = LOGGER.0.lock();
+        assert!(!messages.is_empty());
+        assert!(messages.iter().all(|message| !message.ends_with('\n')));
+    }
+
+    // Debug options share the global state of the log level.
+    let flags = setup_debug(LogLevel::Warn, DebugFlags::DUMP | DebugFlags::AUDIT);
+    assert!(flags.contains(DebugFlags::DUMP));
+    let env = Environment::new().open(dir.path()).unwrap();
+    let txn = env.begin_rw_txn().unwrap();
+    txn.put(
+        &txn.open_db(None).unwrap(),
+        b"key",
+        b"val",
+        WriteFlags::empty(),
+    )
+    .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(
+        setup_debug(LogLevel::Warn, DebugFlags::empty()),
+        DebugFlags::empty()
+    );
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_export_metrics() {
+    let dir = tempdir().unwrap();
+    let env = Arc::new(Environment::new().open(dir.path()).unwrap());
+    env.export_metrics().unwrap();
+
+    let exporter = env.export_metrics_every(Duration::from_millis(10));
+    env.update(|txn| {
+        let db = txn.open_db(None)?;
+        txn.put(&db, b"key", b"val", WriteFlags::empty())
+    })
+    .unwrap();
+    drop(env);
+    exporter.join().unwrap();
+}
+
+#[test]
+fn test_latency_recorder() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    assert!(env.latency_recorder().is_none());
+    drop(env);
+
+    let env = Environment::new()
+        .set_latency_recording(true)
+        .open(dir.path())
+        .unwrap();
+    for i in 0..10u32 {
+        let mut txn = env.begin_rw_txn().unwrap();
+        {
+            let nested = txn.begin_nested_txn().unwrap();
+            let db = nested.open_db(None).unwrap();
+            nested
+                .put(&db, i.to_be_bytes(), b"val", WriteFlags::empty())
+                .unwrap();
+            nested.commit().unwrap();
+        }
+        txn.commit().unwrap();
+    }
+    env.begin_ro_txn().unwrap().commit().unwrap();
+
+    let recorder = env.latency_recorder().unwrap();
+    let snapshot = recorder.snapshot();
+    assert_eq!(snapshot.whole.count, 10);
+    assert_eq!(snapshot.sync.count, 10);
+    assert!(snapshot.whole.quantile(0.99) <= snapshot.whole.max);
+
+    assert_eq!(recorder.reset(), snapshot);
+    assert_eq!(recorder.snapshot().whole.count, 0);
+}
+
+#[test]
+fn test_auto_grow() {
+    let write = |txn: &Transaction<'_, RW, NoWriteMap>| {
+        let db = txn.open_db(None)?;
+        for i in 0..1024u32 {
+            txn.put(&db, i.to_be_bytes(), [0; 1024], WriteFlags::empty())?;
+        }
+        Ok(())
+    };
+    let geometry = Geometry {
+        size: Some(..1024 * 1024),
+        ..Default::default()
+    };
+
+    let dir = tempdir().unwrap();
+    let env = Environment::new()
+        .set_geometry(geometry.clone())
+        .open(dir.path())
+        .unwrap();
+    assert!(matches!(env.update_retrying(write), Err(Error::MapFull)));
+    drop(env);
+
+    // Growth is bounded by the policy.
+    let env = Environment::new()
+        .set_geometry(geometry.clone())
+        .set_auto_grow(GrowthPolicy {
+            step: 1024 * 1024,
+            max_size: 1024 * 1024,
+            max_retries: 8,
+        })
+        .open(dir.path())
+        .unwrap();
+    let mut attempts = 0;
+    let res = env.update_retrying(|txn| {
+        attempts += 1;
+        write(txn)
+    });
+    assert!(matches!(res, Err(Error::MapFull)));
+    assert_eq!(attempts, 1);
+    drop(env);
+
+    let env = Environment::new()
+        .set_geometry(geometry)
+        .set_auto_grow(GrowthPolicy {
+            step: 1024 * 1024,
+            max_size: 16 * 1024 * 1024,
+            max_retries: 8,
+        })
+        .open(dir.path())
+        .unwrap();
+    let mut attempts = 0;
+    match env.update_retrying(|txn| {
+        attempts += 1;
+        write(txn)
+    }) {
+        Ok(()) => {
+            assert!(attempts > 1);
+            assert!(env.info().unwrap().geometry().max() > 1024 * 1024);
+            assert_eq!(env.stat().unwrap().entries(), 1024);
+        }
+        // The memory map could not be extended in place.
+        Err(Error::UnableExtendMapsize) => {}
+        Err(e) => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn test_slow_commit() {
+    let dir = tempdir().unwrap();
+    let slow = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let env = Environment::new()
+        .set_slow_commit_threshold(Duration::ZERO)
+        .set_slow_commit_callback({
+            let slow = slow.clone();
+            move |txnid, latency| slow.lock().unwrap().push((txnid, *latency))
+        })
+        .open(dir.path())
+        .unwrap();
+
+    let mut ids = Vec::new();
+    for i in 0..10u32 {
+        let txn = env.begin_rw_txn().unwrap();
+        ids.push(txn.id());
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            i.to_be_bytes(),
+            b"val",
+            WriteFlags::empty(),
+        )
+        .unwrap();
+        txn.commit().unwrap();
+    }
+
+    let slow = slow.lock().unwrap();
+    assert!(!slow.is_empty());
+    for (txnid, latency) in slow.iter() {
+        assert!(ids.contains(txnid));
+        assert!(latency.whole > Duration::ZERO);
+    }
+}
+
+#[test]
+fn test_reader_monitor() {
+    let dir = tempdir().unwrap();
+    let env = Arc::new(Environment::new().open(dir.path()).unwrap());
+    let reader = env.begin_ro_txn().unwrap();
+    for i in 0..5u32 {
+        env.update(|txn| {
+            let db = txn.open_db(None)?;
+            txn.put(&db, i.to_be_bytes(), b"val", WriteFlags::empty())
+        })
+        .unwrap();
+    }
+
+    let readers = env.readers().unwrap();
+    assert_eq!(readers.len(), 1);
+    assert_eq!(readers[0].txnid, reader.id());
+    assert_eq!(readers[0].lag, 5);
+    assert_eq!(readers[0].pid as u32, std::process::id());
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let monitor = env.spawn_reader_monitor(
+        ReaderMonitorOptions {
+            max_lag: 2,
+            interval: Duration::from_millis(10),
+            clear_stale: true,
+        },
+        move |reader| {
+            let _ = tx.send(reader.txnid);
+        },
+    );
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        reader.id()
+    );
+
+    drop(reader);
+    drop(env);
+    monitor.join().unwrap();
+}
+
+#[test]
+fn test_reader_check() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new()
+        .set_reader_check(true)
+        .open(dir.path())
+        .unwrap();
+    assert_eq!(env.stale_readers_cleared(), 0);
+
+    let _txn = env.begin_ro_txn().unwrap();
+    // Readers of a live process are never cleared.
+    assert_eq!(env.reader_check().unwrap(), 0);
+}
+
 #[test]
 fn test_info() {
     let map_size = 1024 * 1024;
@@ -153,6 +769,113 @@ fn test_info() {
     assert_eq!(info.num_readers(), 0);
 }
 
+#[test]
+fn test_info_sync() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new()
+        .set_flags(
+            Mode::ReadWrite {
+                sync_mode: SyncMode::SafeNoSync,
+            }
+            .into(),
+        )
+        .open(dir.path())
+        .unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    txn.put(
+        &txn.open_db(None).unwrap(),
+        b"key",
+        b"val",
+        WriteFlags::empty(),
+    )
+    .unwrap();
+    txn.commit().unwrap();
+
+    let info = env.info().unwrap();
+    assert!(info.unsync_volume() > 0);
+    let meta = info.meta_pages();
+    let recent = meta.iter().max_by_key(|meta| meta.txnid).unwrap();
+    assert_eq!(recent.txnid, info.last_txnid() as u64);
+    assert!(!recent.is_steady());
+
+    env.sync(true).unwrap();
+    let info = env.info().unwrap();
+    assert_eq!(info.unsync_volume(), 0);
+    assert!(info
+        .meta_pages()
+        .iter()
+        .any(|meta| meta.is_steady() && meta.txnid == info.last_txnid() as u64));
+}
+
+#[test]
+fn test_dump_load() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+    env.update(|txn| {
+        let plain = txn.create_db(Some("plain"), DatabaseFlags::empty())?;
+        txn.put(&plain, b"key\\1", b"val\x001", WriteFlags::empty())?;
+        txn.put(&plain, b"key2", b"val2", WriteFlags::empty())?;
+        let dups = txn.create_db(Some("dups"), DatabaseFlags::DUP_SORT)?;
+        txn.put(&dups, b"key", b"a", WriteFlags::empty())?;
+        txn.put(&dups, b"key", b"b", WriteFlags::empty())?;
+        Ok::<_, Error>(())
+    })
+    .unwrap();
+
+    let mut dump = Vec::new();
+    env.dump_to(&mut dump, DumpFormat::Print).unwrap();
+    let dump = String::from_utf8(dump).unwrap();
+    assert!(dump.starts_with("VERSION=3\n"));
+    assert!(dump.contains(
+        "format=print\ndatabase=dups\ntype=btree\ndb_pagesize=4096\nduplicates=1\ndupsort=1\n\
+         HEADER=END\n key\n a\n key\n b\nDATA=END\n"
+    ));
+    assert!(dump.contains("HEADER=END\n key2\n val2\n key\\5c1\n val\\001\nDATA=END\n"));
+
+    for format in [DumpFormat::Bytevalue, DumpFormat::Print] {
+        let mut dump = Vec::new();
+        env.dump_to(&mut dump, format).unwrap();
+
+        let dir = tempdir().unwrap();
+        let copy = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+        assert_eq!(copy.load_from(&dump[..]).unwrap(), 4);
+        let txn = copy.begin_ro_txn().unwrap();
+        let dups = txn.open_db(Some("dups")).unwrap();
+        assert_eq!(txn.db_flags(&dups).unwrap(), DatabaseFlags::DUP_SORT);
+        assert_eq!(txn.db_stat(&dups).unwrap().entries(), 2);
+        let plain = txn.open_db(Some("plain")).unwrap();
+        assert_eq!(
+            txn.get::<Vec<u8>>(&plain, b"key\\1").unwrap().unwrap(),
+            b"val\x001"
+        );
+
+        // The geometry of the copy may differ.
+        let mut reloaded = Vec::new();
+        copy.dump_to(&mut reloaded, format).unwrap();
+        let data = |dump: &[u8]| {
+            let dump = String::from_utf8(dump.to_vec()).unwrap();
+            dump[dump.find("format=").unwrap()..].to_string()
+        };
+        assert_eq!(data(&reloaded), data(&dump));
+    }
+
+    // The main database, as written by `mdbx_dump`.
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    let dump = "VERSION=3\nmapsize=1048576\nmaxreaders=114\nformat=bytevalue\ntype=btree\n\
+                db_pagesize=4096\nduplicates=0\nHEADER=END\n 6b6579\n 76616c\nDATA=END\n";
+    assert_eq!(env.load_from(dump.as_bytes()).unwrap(), 1);
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(txn.get::<Vec<u8>>(&db, b"key").unwrap().unwrap(), b"val");
+
+    assert!(matches!(
+        env.load_from(&b"VERSION=3\nHEADER=END\n 6b6\n"[..]),
+        Err(Error::DecodeError(_))
+    ));
+}
+
 #[test]
 fn test_freelist() {
     let dir = tempdir().unwrap();
@@ -183,3 +906,380 @@ fn test_freelist() {
     freelist = env.freelist().unwrap();
     assert!(freelist > 0);
 }
+
+#[test]
+fn test_gc_info() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    assert_eq!(env.gc_info().unwrap().records, vec![]);
+
+    for i in 0..64u64 {
+        let tx = env.begin_rw_txn().unwrap();
+        let db = tx.open_db(None).unwrap();
+        tx.put(&db, i.to_be_bytes(), [0; 256], WriteFlags::empty())
+            .unwrap();
+        tx.commit().unwrap();
+    }
+
+    let reader = env.begin_ro_txn().unwrap();
+    let snapshot = reader.id();
+    let tx = env.begin_rw_txn().unwrap();
+    tx.clear_db(&tx.open_db(None).unwrap()).unwrap();
+    tx.commit().unwrap();
+
+    let gc = reader.gc_info().unwrap();
+    assert!(gc.records.iter().all(|record| record.txnid <= snapshot));
+
+    let gc = env.gc_info().unwrap();
+    assert!(gc.oldest_reader_txnid <= snapshot);
+    assert!(gc.records.windows(2).all(|w| w[0].txnid < w[1].txnid));
+    for record in &gc.records {
+        let pages: u64 = record
+            .ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum();
+        assert_eq!(pages, record.pages);
+    }
+    let cleared = gc.records.last().unwrap();
+    assert!(cleared.txnid > snapshot);
+    assert!(gc.retained().any(|record| record == cleared));
+    assert!(gc.retained_pages() >= cleared.pages);
+}
+
+#[test]
+fn test_compact_to() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let tx = env.begin_rw_txn().unwrap();
+    let db = tx.open_db(None).unwrap();
+    for i in 0..2048u64 {
+        tx.put(&db, i.to_be_bytes(), [1; 256], WriteFlags::empty())
+            .unwrap();
+    }
+    tx.commit().unwrap();
+    let tx = env.begin_rw_txn().unwrap();
+    let db = tx.open_db(None).unwrap();
+    for i in 0..1024u64 {
+        tx.del(&db, i.to_be_bytes(), None).unwrap();
+    }
+    tx.commit().unwrap();
+
+    let path = dir.path().join("compact.mdbx");
+    let mut reports = Vec::new();
+    env.compact_to(&path, |progress| reports.push(progress))
+        .unwrap();
+    let last = reports.last().unwrap();
+    assert!(last.done);
+    assert!(reports[..reports.len() - 1].iter().all(|p| !p.done));
+    assert_eq!(last.bytes, std::fs::metadata(&path).unwrap().len());
+    assert!(last.pages > 0);
+    assert!(last.total_pages > 0);
+    assert!(matches!(
+        env.compact_to(&path, |_| {}),
+        Err(Error::Other(_))
+    ));
+
+    let copy = Environment::new()
+        .set_flags(EnvironmentFlags {
+            no_sub_dir: true,
+            ..Default::default()
+        })
+        .open(&path)
+        .unwrap();
+    assert_eq!(copy.stat().unwrap().entries(), 1024);
+    assert_eq!(copy.gc_info().unwrap().pages(), 0);
+}
+
+#[test]
+fn test_backup() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    let tx = env.begin_rw_txn().unwrap();
+    let db = tx.open_db(None).unwrap();
+    for i in 0..1024u64 {
+        tx.put(&db, i.to_be_bytes(), [1; 256], WriteFlags::empty())
+            .unwrap();
+    }
+    tx.commit().unwrap();
+
+    let mut backup = Vec::new();
+    env.backup(&mut backup).unwrap();
+    assert!(!backup.is_empty());
+
+    let restored = tempdir().unwrap();
+    std::fs::write(restored.path().join("mdbx.dat"), &backup).unwrap();
+    let copy = Environment::new().open(restored.path()).unwrap();
+    let tx = copy.begin_ro_txn().unwrap();
+    let db = tx.open_db(None).unwrap();
+    assert_eq!(tx.db_stat(&db).unwrap().entries(), 1024);
+    assert_eq!(
+        tx.get::<Vec<u8>>(&db, &1023u64.to_be_bytes()).unwrap(),
+        Some(vec![1; 256])
+    );
+
+    struct Failing;
+    impl std::io::Write for Failing {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::from_raw_os_error(libc::ENOSPC))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    assert!(matches!(
+        env.backup(Failing),
+        Err(Error::Other(libc::ENOSPC))
+    ));
+}
+
+#[test]
+fn test_vacuum() {
+    let dir = tempdir().unwrap();
+    let data = dir.path().join("mdbx.dat");
+    {
+        let env = Environment::new().open(dir.path()).unwrap();
+        let tx = env.begin_rw_txn().unwrap();
+        let db = tx.open_db(None).unwrap();
+        for i in 0..2048u64 {
+            tx.put(&db, i.to_be_bytes(), [1; 256], WriteFlags::empty())
+                .unwrap();
+        }
+        tx.commit().unwrap();
+        let tx = env.begin_rw_txn().unwrap();
+        let db = tx.open_db(None).unwrap();
+        for i in 16..2048u64 {
+            tx.del(&db, i.to_be_bytes(), None).unwrap();
+        }
+        tx.commit().unwrap();
+    }
+    let size = std::fs::metadata(&data).unwrap().len();
+
+    let env = Environment::new().vacuum(dir.path()).unwrap();
+    assert!(std::fs::metadata(&data).unwrap().len() < size);
+    assert!(!dir.path().join("mdbx.dat-vacuum").exists());
+    assert_eq!(env.gc_info().unwrap().pages(), 0);
+    let tx = env.begin_rw_txn().unwrap();
+    let db = tx.open_db(None).unwrap();
+    assert_eq!(tx.db_stat(&db).unwrap().entries(), 16);
+    tx.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+    tx.commit().unwrap();
+}
+
+#[test]
+fn test_bulk_loader() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+    let stats = BulkLoader::new(&env, Some("items"))
+        .set_txn_size(4096)
+        .load((0..1000u64).map(|i| (i, i.to_be_bytes())))
+        .unwrap();
+    assert_eq!(stats.items, 1000);
+    assert_eq!(stats.bytes, 16 * 1000);
+    assert_eq!(stats.transactions, 4);
+    assert!(stats.items_per_sec() > 0.0);
+
+    // Keys must also sort after the ones already in the database.
+    let mut loader = BulkLoader::new(&env, Some("items"));
+    assert!(matches!(
+        loader.load([(999u64, b"")]),
+        Err(Error::KeyMismatch)
+    ));
+    assert_eq!(loader.load([(1000u64, b"")]).unwrap().items, 1);
+    assert!(matches!(
+        loader
+            .set_txn_size(1)
+            .load([(1001u64, b""), (1003, b""), (1002, b"")]),
+        Err(Error::KeyMismatch)
+    ));
+
+    let mut loader = BulkLoader::new(&env, Some("dups"));
+    loader.set_flags(DatabaseFlags::DUP_SORT);
+    let stats = loader.load([("a", "1"), ("a", "2"), ("b", "1")]).unwrap();
+    assert_eq!(stats.transactions, 1);
+    assert!(matches!(
+        loader.load([("c", "2"), ("c", "1")]),
+        Err(Error::KeyMismatch)
+    ));
+
+    let txn = env.begin_ro_txn().unwrap();
+    let items = txn.open_db(Some("items")).unwrap();
+    assert_eq!(txn.db_stat(&items).unwrap().entries(), 1003);
+    assert_eq!(
+        txn.get::<u64>(&items, &500u64.to_be_bytes()).unwrap(),
+        Some(500)
+    );
+    let dups = txn.open_db(Some("dups")).unwrap();
+    assert_eq!(txn.db_stat(&dups).unwrap().entries(), 3);
+}
+
+#[test]
+fn test_sharded_environment() {
+    let dirs = [tempdir().unwrap(), tempdir().unwrap(), tempdir().unwrap()];
+    let paths = dirs.iter().map(|dir| dir.path());
+    let env =
+        ShardedEnvironment::open(&Environment::new(), paths.clone(), ShardRouting::Hash).unwrap();
+
+    let txn = env.begin_rw_txn();
+    for i in 0..100u32 {
+        txn.put(None, i, i.to_le_bytes(), WriteFlags::empty())
+            .unwrap();
+    }
+    txn.commit().unwrap();
+    for shard in env.shards() {
+        let entries = shard.stat().unwrap().entries();
+        assert!(entries > 10 && entries < 60, "{entries}");
+    }
+
+    let txn = env.begin_ro_txn();
+    assert_eq!(
+        txn.get::<[u8; 4]>(None, 42u32).unwrap(),
+        Some(42u32.to_le_bytes())
+    );
+    assert_eq!(txn.get::<[u8; 4]>(None, 100u32).unwrap(), None);
+    let keys = txn
+        .iter(None)
+        .unwrap()
+        .map(|item| u32::from_be_bytes(item.unwrap().0[..].try_into().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(keys, (0..100).collect::<Vec<_>>());
+    drop(txn);
+
+    // Shards which are not written to are not locked.
+    let txn = env.begin_rw_txn();
+    assert!(txn.del(None, 42u32, None).unwrap());
+    let other = (env.shard_of(&42u32.to_be_bytes()) + 1) % 3;
+    env.shards()[other]
+        .begin_rw_txn()
+        .unwrap()
+        .commit()
+        .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(
+        env.begin_ro_txn().get::<[u8; 4]>(None, 42u32).unwrap(),
+        None
+    );
+    drop(env);
+
+    let routing = ShardRouting::Range(vec![b"g".to_vec(), b"p".to_vec()]);
+    let env = ShardedEnvironment::open(&Environment::new(), paths.clone(), routing).unwrap();
+    assert_eq!(env.shard_of(b"apple"), 0);
+    assert_eq!(env.shard_of(b"g"), 1);
+    assert_eq!(env.shard_of(b"zebra"), 2);
+    assert!(matches!(
+        ShardedEnvironment::open(
+            &Environment::new(),
+            paths,
+            ShardRouting::Range(vec![b"p".to_vec(), b"g".to_vec()])
+        ),
+        Err(Error::Invalid)
+    ));
+}
+
+#[test]
+fn test_durability_check() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new()
+        .set_durability_check(true)
+        .open(dir.path())
+        .unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    txn.put(
+        &txn.open_db(None).unwrap(),
+        b"key",
+        b"val",
+        WriteFlags::empty(),
+    )
+    .unwrap();
+    txn.commit().unwrap();
+
+    std::fs::remove_file(dir.path().join("mdbx.dat")).unwrap();
+    let txn = env.begin_rw_txn().unwrap();
+    txn.put(
+        &txn.open_db(None).unwrap(),
+        b"key",
+        b"new",
+        WriteFlags::empty(),
+    )
+    .unwrap();
+    assert!(matches!(txn.commit(), Err(Error::StorageLost)));
+}
+
+#[test]
+fn test_close() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+    drop(db);
+    mem::forget(txn);
+    // Leaked read-only transactions are not counted.
+    mem::forget(env.begin_ro_txn().unwrap());
+    assert_eq!(env.close().unwrap(), 1);
+
+    // The leaked write transaction was aborted, releasing the writer lock.
+    let env = Environment::new().open(dir.path()).unwrap();
+    let mut txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(txn.get::<Vec<u8>>(&db, b"key").unwrap(), None);
+    drop(db);
+    // Leaked nested transactions are finished together with their parent.
+    mem::forget(txn.begin_nested_txn().unwrap());
+    txn.commit().unwrap();
+    assert_eq!(env.close().unwrap(), 0);
+
+    // In direct-commit mode, a write transaction leaked by another thread holds the writer lock
+    // of that thread and cannot be aborted.
+    let env = Environment::new()
+        .set_direct_commit(true)
+        .open(dir.path())
+        .unwrap();
+    thread::scope(|s| {
+        s.spawn(|| mem::forget(env.begin_rw_txn().unwrap()));
+    });
+    let error = env.close().unwrap_err();
+    assert_eq!(error.error, Error::ThreadMismatch(None));
+    // Dropping it leaks the transaction instead of aborting it.
+    drop(error);
+}
+
+#[test]
+fn test_ensure_capacity() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new()
+        .set_geometry(Geometry {
+            size: Some(..64 * 1024 * 1024),
+            growth_step: Some(64 * 1024),
+            ..Default::default()
+        })
+        .open(dir.path())
+        .unwrap();
+    let before = env.info().unwrap().geometry().current() as usize;
+    let shrink = env.info().unwrap().geometry().shrink();
+
+    let size = env.ensure_capacity(8 * 1024 * 1024).unwrap();
+    assert!(size >= before + 8 * 1024 * 1024);
+    assert_eq!(env.info().unwrap().geometry().current() as usize, size);
+    let file = std::fs::metadata(dir.path().join("mdbx.dat")).unwrap();
+    assert!(file.len() as usize >= size);
+
+    assert_eq!(env.info().unwrap().geometry().shrink(), shrink);
+
+    // There is enough room already.
+    assert_eq!(env.ensure_capacity(1024 * 1024).unwrap(), size);
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for i in 0..1024u32 {
+        txn.put(&db, i.to_be_bytes(), [0; 1024], WriteFlags::empty())
+            .unwrap();
+    }
+    drop(db);
+    txn.commit().unwrap();
+    assert_eq!(env.info().unwrap().geometry().shrink(), shrink);
+}
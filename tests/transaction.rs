@@ -1,9 +1,12 @@
 use libmdbx::*;
 use std::{
     borrow::Cow,
-    io::Write,
+    io::{IoSlice, Write},
+    net::IpAddr,
+    ops::Bound,
     sync::{Arc, Barrier},
     thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tempfile::tempdir;
 
@@ -53,7 +56,7 @@ fn test_put_get_del_multi() {
     let txn = env.begin_rw_txn().unwrap();
     let db = txn.open_db(None).unwrap();
     {
-        let mut cur = txn.cursor(&db).unwrap();
+        let mut cur = txn.dup_cursor(&db).unwrap();
         let iter = cur.iter_dup_of::<(), [u8; 4]>(b"key1");
         let vals = iter.map(|x| x.unwrap()).map(|(_, x)| x).collect::<Vec<_>>();
         assert_eq!(vals, vec![*b"val1", *b"val2", *b"val3"]);
@@ -69,7 +72,7 @@ fn test_put_get_del_multi() {
     let txn = env.begin_rw_txn().unwrap();
     let db = txn.open_db(None).unwrap();
     {
-        let mut cur = txn.cursor(&db).unwrap();
+        let mut cur = txn.dup_cursor(&db).unwrap();
         let iter = cur.iter_dup_of::<(), [u8; 4]>(b"key1");
         let vals = iter.map(|x| x.unwrap()).map(|(_, x)| x).collect::<Vec<_>>();
         assert_eq!(vals, vec![*b"val1", *b"val3"]);
@@ -202,9 +205,7 @@ fn test_drop_db() {
         {
             let txn = env.begin_rw_txn().unwrap();
             let db = txn.open_db(Some("test")).unwrap();
-            unsafe {
-                txn.drop_db(db).unwrap();
-            }
+            txn.drop_db(db).unwrap();
             assert!(matches!(
                 txn.open_db(Some("test")).unwrap_err(),
                 Error::NotFound
@@ -268,6 +269,64 @@ fn test_concurrent_readers_single_writer() {
     assert!(threads.into_iter().all(|b| b.join().unwrap()))
 }
 
+#[test]
+fn test_shared_ro_txn() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Transaction<RO, NoWriteMap>>();
+    assert_send_sync::<Transaction<RW, NoWriteMap>>();
+    assert_send_sync::<ArcRoTransaction<NoWriteMap>>();
+    assert_send_sync::<Cursor<RO>>();
+    assert_send_sync::<Database>();
+
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for i in 0..100u32 {
+        txn.put(&db, i.encode(), i.encode(), WriteFlags::empty())
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap().into_arc();
+
+    // Writes after the snapshot was taken are not visible to it.
+    let writer = env.begin_rw_txn().unwrap();
+    let db = writer.open_db(None).unwrap();
+    writer
+        .put(&db, 100u32.encode(), 100u32.encode(), WriteFlags::empty())
+        .unwrap();
+    writer.commit().unwrap();
+
+    let sums = thread::scope(|s| {
+        (0..4u32)
+            .map(|t| {
+                let txn = txn.clone();
+                s.spawn(move || {
+                    let db = txn.open_db(None).unwrap();
+                    let mut cursor = txn.cursor(&db).unwrap();
+                    cursor
+                        .iter_from::<u32, u32>(&(t * 25).encode())
+                        .take(25)
+                        .map(|item| item.unwrap().1)
+                        .sum::<u32>()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(sums, [300, 925, 1550, 2175]);
+
+    // The cursor can be moved to another thread as well.
+    let db = txn.open_db(None).unwrap();
+    let cursor = txn.cursor(&db).unwrap();
+    let count = thread::scope(|s| s.spawn(move || cursor.into_iter().count()).join().unwrap());
+    assert_eq!(count, 100);
+}
+
 #[test]
 fn test_concurrent_writers() {
     let dir = tempdir().unwrap();
@@ -357,6 +416,183 @@ fn test_stat() {
     }
 }
 
+#[test]
+fn test_walk_pages() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn
+        .create_db(Some("table"), DatabaseFlags::empty())
+        .unwrap();
+    for i in 0..1000u32 {
+        txn.put(&db, i.to_be_bytes(), [0; 100], WriteFlags::empty())
+            .unwrap();
+    }
+    txn.put(&db, b"large", [0; 20000], WriteFlags::empty())
+        .unwrap();
+    txn.commit().unwrap();
+
+    let mut owners = Vec::new();
+    let mut table_leaves = 0;
+    let mut large = 0;
+    env.walk_pages(true, |visit| {
+        assert!(visit.error.is_none());
+        if !owners.contains(&visit.owner) {
+            owners.push(visit.owner.clone().into_owned());
+        }
+        if visit.owner == PageOwner::Table("table".into()) {
+            match visit.page_type {
+                PageType::Leaf => table_leaves += 1,
+                PageType::Large => large += visit.pages,
+                _ => {}
+            }
+        }
+        std::ops::ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert!(owners.contains(&PageOwner::Meta));
+    assert!(owners.contains(&PageOwner::Main));
+    assert!(owners.contains(&PageOwner::Table("table".into())));
+
+    let txn = env.begin_ro_txn().unwrap();
+    let stat = txn.db_stat(&txn.open_db(Some("table")).unwrap()).unwrap();
+    assert_eq!(table_leaves, stat.leaf_pages());
+    assert_eq!(large as usize, stat.overflow_pages());
+
+    let mut visited = 0;
+    txn.walk_pages(false, |_| {
+        visited += 1;
+        std::ops::ControlFlow::Break(())
+    })
+    .unwrap();
+    assert_eq!(visited, 1);
+}
+
+#[test]
+fn test_check() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+    for round in 0..5u32 {
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn
+            .create_db(Some("table"), DatabaseFlags::empty())
+            .unwrap();
+        for i in 0..1000u32 {
+            txn.put(
+                &db,
+                i.to_be_bytes(),
+                round.to_be_bytes(),
+                WriteFlags::empty(),
+            )
+            .unwrap();
+        }
+        txn.put(&db, b"large", vec![0; 20000], WriteFlags::empty())
+            .unwrap();
+        txn.commit().unwrap();
+    }
+
+    let report = env.check(&CheckOptions::default()).unwrap();
+    assert!(report.is_ok(), "{:?}", report.problems);
+    assert!(report.free_pages > 0);
+    assert_eq!(
+        report.used_pages + report.free_pages,
+        report.allocated_pages
+    );
+    assert_eq!(
+        report
+            .problems_of(&PageOwner::Table("table".into()))
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn test_space_report() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn
+        .create_db(Some("table"), DatabaseFlags::empty())
+        .unwrap();
+    for i in 0..1000u32 {
+        txn.put(&db, i.to_be_bytes(), [0; 100], WriteFlags::empty())
+            .unwrap();
+    }
+    txn.put(&db, b"large", [0; 20000], WriteFlags::empty())
+        .unwrap();
+    txn.commit().unwrap();
+
+    let mut report = env.space_report().unwrap();
+    let txn = env.begin_ro_txn().unwrap();
+    let stat = txn.db_stat(&txn.open_db(Some("table")).unwrap()).unwrap();
+    let table = report.table(&PageOwner::Table("table".into())).unwrap();
+    assert_eq!(table.branch_pages, stat.branch_pages());
+    assert_eq!(table.leaf_pages, stat.leaf_pages());
+    assert_eq!(table.overflow_pages, stat.overflow_pages());
+    assert!(table.payload_bytes >= 1000 * 104 + 20000);
+    assert_eq!(
+        table.bytes(),
+        (stat.branch_pages() + stat.leaf_pages() + stat.overflow_pages()) as u64
+            * stat.page_size() as u64
+    );
+    assert!(table.unused_ratio() < 1.0);
+    assert!(report.table(&PageOwner::Meta).is_some());
+    assert!(report.total_bytes() > table.bytes());
+
+    report.sort_by_unused();
+    assert!(report
+        .tables
+        .windows(2)
+        .all(|w| w[0].unused_bytes >= w[1].unused_bytes));
+}
+
+#[test]
+fn test_db_report() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let small = txn
+        .create_db(Some("small"), DatabaseFlags::empty())
+        .unwrap();
+    txn.put(&small, b"key", b"val", WriteFlags::empty())
+        .unwrap();
+    let large = txn
+        .create_db(Some("large"), DatabaseFlags::empty())
+        .unwrap();
+    for i in 0..1000u32 {
+        txn.put(&large, i.encode(), [0; 64], WriteFlags::empty())
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let mut report = txn.db_report().unwrap();
+    report.sort_by_name();
+    let names = report
+        .tables
+        .iter()
+        .map(|table| table.name.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(names, [None, Some("large"), Some("small")]);
+    assert_eq!(report.tables[0].entries, 2);
+    assert_eq!(report.tables[2].entries, 1);
+
+    report.sort_by_size();
+    let largest = &report.tables[0];
+    assert_eq!(largest.name.as_deref(), Some("large"));
+    assert_eq!(largest.entries, 1000);
+    assert!(largest.depth > 1);
+    assert!(report.percentage(largest) > 50.0);
+    assert_eq!(
+        report.total_bytes(),
+        report.tables.iter().map(|table| table.bytes).sum::<u64>()
+    );
+}
+
 #[test]
 fn test_stat_dupsort() {
     let dir = tempdir().unwrap();
@@ -406,3 +642,1406 @@ fn test_stat_dupsort() {
         assert_eq!(stat.entries(), 8);
     }
 }
+
+#[cfg(feature = "bincode")]
+#[test]
+fn test_put_get_serde() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put_ser::<Bincode, _>(&db, b"key1", &(1u32, "val1"), WriteFlags::empty())
+        .unwrap();
+    {
+        let mut cursor = txn.cursor(&db).unwrap();
+        cursor
+            .put_ser::<Bincode, _>(b"key2", &(2u32, "val2"), WriteFlags::empty())
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(
+        txn.get_de::<Bincode, (u32, String)>(&db, b"key1").unwrap(),
+        Some((1, "val1".to_string()))
+    );
+    assert_eq!(
+        txn.get_de::<Bincode, (u32, String)>(&db, b"key3").unwrap(),
+        None
+    );
+    assert!(matches!(
+        txn.get_de::<Bincode, (u64, u64, u64)>(&db, b"key1"),
+        Err(Error::DecodeError(_))
+    ));
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(
+        cursor
+            .iter_start::<(), Serde<Bincode, (u32, String)>>()
+            .map(|res| res.map(|((), value)| value.into_inner().0))
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+        vec![1, 2]
+    );
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn test_get_rkyv() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let value = vec![1u64, 2, 3];
+    let bytes = rkyv::to_bytes::<_, 256>(&value).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"key1", &bytes, WriteFlags::empty()).unwrap();
+    txn.put(&db, b"key2", b"bad", WriteFlags::empty()).unwrap();
+    let archived = txn.get::<Rkyv<Vec<u64>>>(&db, b"key1").unwrap().unwrap();
+    assert_eq!(archived.as_slice(), &[1, 2, 3]);
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let archived = txn.get::<Rkyv<Vec<u64>>>(&db, b"key1").unwrap().unwrap();
+    assert_eq!(archived.as_slice(), &[1, 2, 3]);
+    assert!(matches!(
+        txn.get::<Rkyv<Vec<u64>>>(&db, b"key2"),
+        Err(Error::DecodeError(_))
+    ));
+}
+
+#[test]
+fn test_get_typed() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let addr = IpAddr::from([192, 168, 0, 1]);
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"u16", 7u16.encode(), WriteFlags::empty())
+        .unwrap();
+    txn.put(&db, b"u64", 42u64.encode(), WriteFlags::empty())
+        .unwrap();
+    txn.put(&db, b"str", "val".encode(), WriteFlags::empty())
+        .unwrap();
+    txn.put(&db, b"ip", addr.encode(), WriteFlags::empty())
+        .unwrap();
+    txn.put(&db, b"bad", [0xff, 0xfe], WriteFlags::empty())
+        .unwrap();
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(txn.get::<u16>(&db, b"u16").unwrap(), Some(7));
+    assert_eq!(txn.get::<u64>(&db, b"u64").unwrap(), Some(42));
+    assert_eq!(
+        txn.get::<String>(&db, b"str").unwrap(),
+        Some("val".to_string())
+    );
+    assert_eq!(txn.get::<IpAddr>(&db, b"ip").unwrap(), Some(addr));
+    assert!(matches!(
+        txn.get::<u32>(&db, b"u64"),
+        Err(Error::DecodeError(_))
+    ));
+    assert!(matches!(
+        txn.get::<String>(&db, b"bad"),
+        Err(Error::DecodeError(_))
+    ));
+}
+
+#[test]
+fn test_decode_error_context() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn
+        .create_db(Some("accounts"), DatabaseFlags::empty())
+        .unwrap();
+    txn.put(&db, b"alice", b"bad", WriteFlags::empty()).unwrap();
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(Some("accounts")).unwrap();
+    let context = |err: Error| match err {
+        Error::DecodeError(source) => *source.downcast::<DecodeErrorContext>().unwrap(),
+        other => panic!("unexpected error {other:?}"),
+    };
+
+    let err = context(txn.get::<u64>(&db, b"alice").unwrap_err());
+    assert_eq!(err.table.as_deref(), Some("accounts"));
+    assert_eq!(err.key, b"alice");
+    assert_eq!(err.key_len, 5);
+    assert_eq!(err.value_len, 3);
+    assert!(err
+        .to_string()
+        .starts_with("failed to decode entry in accounts (key alice of 5 bytes"));
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    let err = context(cursor.first::<(), u64>().unwrap_err());
+    assert_eq!(err.table.as_deref(), Some("accounts"));
+    assert_eq!(err.key, b"alice");
+    let err = context(cursor.iter_start::<(), u64>().next().unwrap().unwrap_err());
+    assert_eq!(err.value_len, 3);
+
+    let db = txn.open_db(None).unwrap();
+    let err = context(txn.get::<u64>(&db, b"accounts").unwrap_err());
+    assert_eq!(err.table, None);
+
+    let long_key = [0xab; DECODE_ERROR_MAX_KEY_LEN + 1];
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, long_key, b"bad", WriteFlags::empty()).unwrap();
+    let err = context(txn.get::<u64>(&db, long_key).unwrap_err());
+    assert_eq!(
+        err.key_dump(),
+        format!("<{}>", "ab".repeat(DECODE_ERROR_MAX_KEY_LEN))
+    );
+    assert!(err.to_string().contains("ab>... of 65 bytes"));
+}
+
+#[test]
+fn test_op_context() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn
+        .create_db(Some("accounts"), DatabaseFlags::empty())
+        .unwrap();
+    txn.put(&db, b"alice", b"1", WriteFlags::empty())
+        .op_context(&txn, &db, Operation::Put, b"alice")
+        .unwrap();
+
+    let err = txn
+        .put(&db, b"alice", b"2", WriteFlags::NO_OVERWRITE)
+        .op_context(&txn, &db, Operation::Put, b"alice")
+        .unwrap_err();
+    assert_eq!(err.operation, Operation::Put);
+    assert_eq!(err.table.as_deref(), Some("accounts"));
+    assert_eq!(err.key, b"alice");
+    assert_eq!(err.code(), Error::KeyExist.code());
+    assert!(err
+        .to_string()
+        .starts_with("put in accounts failed (key alice): MDBX_KEYEXIST"));
+    assert!(matches!(Error::from(err), Error::KeyExist));
+}
+
+#[test]
+fn test_op_context_setting() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new()
+        .set_max_dbs(1)
+        .set_op_context(true)
+        .open(dir.path())
+        .unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn
+        .create_db(Some("accounts"), DatabaseFlags::empty())
+        .unwrap();
+    txn.put(&db, b"alice", b"1", WriteFlags::empty()).unwrap();
+    assert_eq!(txn.get::<Vec<u8>>(&db, b"bob").unwrap(), None);
+    assert!(!txn.del(&db, b"bob", None).unwrap());
+
+    let err = txn
+        .put(&db, b"alice", b"2", WriteFlags::NO_OVERWRITE)
+        .unwrap_err();
+    assert_eq!(err, Error::KeyExist);
+    assert!(matches!(err.kind(), Error::KeyExist));
+    assert_eq!(err.code(), Error::KeyExist.code());
+    assert!(err
+        .to_string()
+        .starts_with("put in accounts failed (key alice): MDBX_KEYEXIST"));
+    let Error::Op(op) = err else {
+        panic!("{err:?}");
+    };
+    assert_eq!(op.operation, Operation::Put);
+
+    let err = txn
+        .reserve(&db, b"alice", 1, WriteFlags::NO_OVERWRITE)
+        .unwrap_err();
+    assert!(matches!(&err, Error::Op(op) if op.operation == Operation::Put && op.key == b"alice"));
+    assert_eq!(err, Error::KeyExist);
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(cursor.set::<()>(b"bob").unwrap(), None);
+    let err = cursor
+        .put(b"alice", b"3", WriteFlags::NO_OVERWRITE)
+        .unwrap_err();
+    assert!(matches!(&err, Error::Op(op) if op.operation == Operation::Put && op.key == b"alice"));
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn test_get_uuid() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let id = uuid::Uuid::from_u128(0x1234_5678_9abc_def0_0fed_cba9_8765_4321);
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, id.encode(), b"val", WriteFlags::empty())
+        .unwrap();
+    assert_eq!(
+        txn.cursor(&db).unwrap().first().unwrap(),
+        Some((id, *b"val"))
+    );
+}
+
+#[test]
+fn test_put_compressed() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"raw", b"val1", WriteFlags::empty()).unwrap();
+    txn.put_compressed(
+        &db,
+        b"none",
+        b"val2",
+        Compression::None,
+        WriteFlags::empty(),
+    )
+    .unwrap();
+    #[cfg(feature = "lz4")]
+    txn.put_compressed(
+        &db,
+        b"lz4",
+        b"val3".repeat(100),
+        Compression::Lz4,
+        WriteFlags::empty(),
+    )
+    .unwrap();
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(
+        &*txn.get::<Decompressed>(&db, b"raw").unwrap().unwrap(),
+        b"val1"
+    );
+    assert_eq!(
+        &*txn.get::<Decompressed>(&db, b"none").unwrap().unwrap(),
+        b"val2"
+    );
+    #[cfg(feature = "lz4")]
+    {
+        assert!(txn.get::<Vec<u8>>(&db, b"lz4").unwrap().unwrap().len() < 400);
+        assert_eq!(
+            &*txn.get::<Decompressed>(&db, b"lz4").unwrap().unwrap(),
+            &b"val3".repeat(100)[..]
+        );
+    }
+}
+
+#[test]
+fn test_tuple_keys() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for (account, block) in [("bob", 2u64), ("alice", 300), ("alice", 2), ("al", 1000)] {
+        txn.put(&db, (account, block), b"", WriteFlags::empty())
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let mut cursor = txn.cursor(&db).unwrap();
+    let keys = cursor
+        .iter_start::<(String, u64), ()>()
+        .map(|item| item.unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        [
+            ("al".to_string(), 1000),
+            ("alice".to_string(), 2),
+            ("alice".to_string(), 300),
+            ("bob".to_string(), 2),
+        ]
+    );
+
+    let (account, rest) = cursor
+        .set_range::<(String, Cow<[u8]>), ()>(&("alice", 0u64).encode())
+        .unwrap()
+        .unwrap()
+        .0;
+    assert_eq!(account, "alice");
+    assert!(matches!(rest, Cow::Borrowed(_)));
+}
+
+#[test]
+fn test_ttl() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(3).open(dir.path()).unwrap();
+    let hour = Duration::from_secs(3600);
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn
+        .create_db(Some("sessions"), DatabaseFlags::empty())
+        .unwrap();
+    txn.put_with_ttl(&db, b"short", b"1", hour, WriteFlags::empty())
+        .unwrap();
+    txn.put_with_ttl(&db, b"long", b"2", 3 * hour, WriteFlags::empty())
+        .unwrap();
+    txn.put_with_ttl(&db, b"renewed", b"3", hour, WriteFlags::empty())
+        .unwrap();
+    txn.put_with_ttl(&db, b"renewed", b"3", 3 * hour, WriteFlags::empty())
+        .unwrap();
+    txn.put_with_ttl(&db, b"cleared", b"4", hour, WriteFlags::empty())
+        .unwrap();
+    assert!(txn.clear_ttl(&db, b"cleared").unwrap());
+    assert!(!txn.clear_ttl(&db, b"cleared").unwrap());
+    txn.put(&db, b"plain", b"5", WriteFlags::empty()).unwrap();
+
+    let expiry = txn.expiry(&db, b"long").unwrap().unwrap();
+    assert!(expiry > SystemTime::now() + 2 * hour);
+    assert_eq!(txn.expiry(&db, b"plain").unwrap(), None);
+
+    let now = SystemTime::now();
+    assert_eq!(txn.purge_expired(now).unwrap(), 0);
+    assert_eq!(txn.purge_expired(now + 2 * hour).unwrap(), 1);
+    txn.commit().unwrap();
+    assert_eq!(env.purge_expired().unwrap(), 0);
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(Some("sessions")).unwrap();
+    let keys = txn
+        .cursor(&db)
+        .unwrap()
+        .iter_start::<Vec<u8>, ()>()
+        .map(|item| item.unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(keys, [&b"cleared"[..], b"long", b"plain", b"renewed"]);
+
+    assert_eq!(txn.purge_expired(now + 4 * hour).unwrap(), 2);
+    assert_eq!(txn.get::<Vec<u8>>(&db, b"long").unwrap(), None);
+    assert_eq!(txn.get::<Vec<u8>>(&db, b"renewed").unwrap(), None);
+    let ttl_db = txn.open_db(Some(ttl::TTL_DB_NAME)).unwrap();
+    assert_eq!(txn.db_stat(&ttl_db).unwrap().entries(), 0);
+
+    txn.put_with_ttl(&db, b"forever", b"6", Duration::MAX, WriteFlags::empty())
+        .unwrap();
+    assert_eq!(
+        txn.expiry(&db, b"forever").unwrap(),
+        Some(UNIX_EPOCH + Duration::from_millis(u64::MAX))
+    );
+    let temp = txn.create_db(Some("temp"), DatabaseFlags::empty()).unwrap();
+    txn.put_with_ttl(&temp, b"key", b"7", hour, WriteFlags::empty())
+        .unwrap();
+    txn.drop_db(temp).unwrap();
+    assert_eq!(txn.purge_expired(now + 4 * hour).unwrap(), 0);
+    assert_eq!(txn.db_stat(&ttl_db).unwrap().entries(), 2);
+}
+
+#[test]
+fn test_typed_keys() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for i in [256u64, 1, 65536] {
+        txn.put(&db, i, i.encode(), WriteFlags::empty()).unwrap();
+    }
+    txn.put(&db, "key", b"val", WriteFlags::empty()).unwrap();
+    assert_eq!(txn.get::<u64>(&db, 256u64).unwrap(), Some(256));
+    assert_eq!(txn.get::<[u8; 3]>(&db, "key").unwrap(), Some(*b"val"));
+    assert!(txn.del(&db, 1u64, None).unwrap());
+    assert_eq!(txn.get::<u64>(&db, 1u64).unwrap(), None);
+
+    let mut cursor = txn.cursor(&db).unwrap();
+    assert_eq!(
+        cursor
+            .iter_start::<u64, ()>()
+            .take(2)
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+        vec![(256, ()), (65536, ())]
+    );
+    drop(cursor);
+
+    // Byte string types which were accepted as AsRef<[u8]> keys.
+    let boxed: Box<[u8]> = b"key"[..].into();
+    assert_eq!(txn.get::<[u8; 3]>(&db, &boxed).unwrap(), Some(*b"val"));
+    assert_eq!(txn.get::<[u8; 3]>(&db, boxed).unwrap(), Some(*b"val"));
+    let shared: std::sync::Arc<[u8]> = b"key"[..].into();
+    assert_eq!(txn.get::<[u8; 3]>(&db, shared).unwrap(), Some(*b"val"));
+    assert_eq!(
+        txn.get::<[u8; 3]>(&db, &Cow::Borrowed(&b"key"[..]))
+            .unwrap(),
+        Some(*b"val")
+    );
+    let shared: std::rc::Rc<[u8]> = b"key"[..].into();
+    assert!(txn.del(&db, RawKey(shared), None).unwrap());
+}
+
+#[test]
+fn test_get_many() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for i in (0..1000u32).step_by(2) {
+        txn.put(&db, i.encode(), (i * 10).encode(), WriteFlags::empty())
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let keys = [500u32, 3, 998, 0, 500, 1001];
+    assert_eq!(
+        txn.get_many::<u32, _>(&db, keys).unwrap(),
+        [Some(5000), None, Some(9980), Some(0), Some(5000), None]
+    );
+    assert!(txn.get_many::<u32, u32>(&db, []).unwrap().is_empty());
+}
+
+#[test]
+fn test_get_sorted() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for i in (0..1000u32).step_by(2) {
+        txn.put(&db, i.encode(), (i * 10).encode(), WriteFlags::empty())
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let keys = [0u32, 3, 4, 4, 5, 500, 998, 999, 1001];
+    assert_eq!(
+        txn.get_sorted::<u32, _>(&db, keys).unwrap(),
+        [
+            Some(0),
+            None,
+            Some(40),
+            Some(40),
+            None,
+            Some(5000),
+            Some(9980),
+            None,
+            None
+        ]
+    );
+    assert!(txn.get_sorted::<u32, u32>(&db, []).unwrap().is_empty());
+    assert!(matches!(
+        txn.get_sorted::<u32, _>(&db, [4u32, 2]),
+        Err(Error::InvalidValue)
+    ));
+}
+
+#[test]
+fn test_get_slice() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+    drop(db);
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let value = txn.get_slice(&db, b"key1").unwrap().unwrap();
+    assert_eq!(value, b"val1");
+    assert!(txn.get_slice(&db, b"key2").unwrap().is_none());
+    // The slice is valid for the whole transaction, not just the handle.
+    drop(db);
+    assert_eq!(value, b"val1");
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn test_get_pod() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let record: [u32; 3] = [1, 2, 3];
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(
+        &db,
+        b"key1",
+        bytemuck::bytes_of(&record),
+        WriteFlags::empty(),
+    )
+    .unwrap();
+    txn.put(&db, b"key2", b"val", WriteFlags::empty()).unwrap();
+    assert_eq!(
+        txn.get::<PodValue<[u32; 3]>>(&db, b"key1")
+            .unwrap()
+            .unwrap()
+            .into_inner(),
+        record
+    );
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(
+        *txn.get::<PodValue<[u32; 3]>>(&db, b"key1")
+            .unwrap()
+            .unwrap(),
+        record
+    );
+    assert!(matches!(
+        txn.get::<PodValue<[u32; 3]>>(&db, b"key2"),
+        Err(Error::DecodeError(_))
+    ));
+}
+
+#[test]
+fn test_put_vectored() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put_vectored(
+        &db,
+        b"key1",
+        &[
+            IoSlice::new(b"head"),
+            IoSlice::new(b""),
+            IoSlice::new(b"payload"),
+        ],
+        WriteFlags::empty(),
+    )
+    .unwrap();
+    txn.put_vectored(&db, b"key2", &[], WriteFlags::empty())
+        .unwrap();
+
+    assert_eq!(
+        txn.get::<Vec<u8>>(&db, b"key1").unwrap(),
+        Some(b"headpayload".to_vec())
+    );
+    assert_eq!(txn.get::<Vec<u8>>(&db, b"key2").unwrap(), Some(vec![]));
+}
+
+#[test]
+fn test_sample_keys() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(3).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn
+        .create_db(Some("skewed"), DatabaseFlags::empty())
+        .unwrap();
+    assert_eq!(txn.sample_keys(&db, 4).unwrap(), KeyDistribution::default());
+    for i in 0..10_000u64 {
+        // Keys are skewed towards the small ones.
+        txn.put(
+            &db,
+            (i * i).to_be_bytes(),
+            vec![0; (i % 10) as usize],
+            WriteFlags::APPEND,
+        )
+        .unwrap();
+    }
+    let ints = txn
+        .create_db(Some("ints"), DatabaseFlags::INTEGER_KEY)
+        .unwrap();
+    for i in 0..10_000u64 {
+        txn.put(&ints, (i * 7).to_ne_bytes(), b"", WriteFlags::empty())
+            .unwrap();
+    }
+    let max = txn
+        .create_db(Some("max"), DatabaseFlags::INTEGER_KEY)
+        .unwrap();
+    txn.put(&max, u64::MAX.to_ne_bytes(), b"", WriteFlags::empty())
+        .unwrap();
+    assert_eq!(txn.sample_keys(&max, 2).unwrap().samples.len(), 1);
+    drop(max);
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(Some("skewed")).unwrap();
+    let dist = txn.sample_keys(&db, 5).unwrap();
+    assert_eq!(dist.entries, 10_000);
+    assert!(dist.samples.len() >= 3);
+    assert!(dist
+        .samples
+        .windows(2)
+        .all(|w| w[0].key < w[1].key && w[0].rank < w[1].rank));
+    assert_eq!(dist.samples[0].key, 0u64.to_be_bytes());
+    assert_eq!(
+        dist.samples.last().unwrap().key,
+        (9_999u64 * 9_999).to_be_bytes()
+    );
+    let median = u64::from_be_bytes(dist.quantile(0.5).unwrap().try_into().unwrap());
+    let median = (median as f64).sqrt() as u64;
+    assert!((3_500..6_500).contains(&median), "{median}");
+    assert_eq!(dist.value_len_quantile(0.0), Some(0));
+    assert!(dist.value_len_quantile(1.0).unwrap() < 10);
+
+    let ints = txn.open_db(Some("ints")).unwrap();
+    let dist = txn.sample_keys(&ints, 3).unwrap();
+    let median = u64::from_ne_bytes(dist.quantile(0.5).unwrap().try_into().unwrap());
+    assert!((3_500 * 7..6_500 * 7).contains(&median), "{median}");
+}
+
+#[test]
+fn test_replace_merge() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(
+        txn.replace(&db, b"key", b"val1", WriteFlags::empty())
+            .unwrap(),
+        None
+    );
+    // Written by this transaction, so the old value is copied before it is overwritten.
+    assert!(matches!(
+        txn.replace(&db, b"key", b"val2", WriteFlags::empty()).unwrap(),
+        Some(Cow::Owned(old)) if old == b"val1"
+    ));
+    assert_eq!(
+        txn.replace(&db, b"key", b"val2", WriteFlags::empty())
+            .unwrap()
+            .as_deref(),
+        Some(&b"val2"[..])
+    );
+    txn.commit().unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert!(matches!(
+        txn.replace(&db, b"key", b"val3", WriteFlags::empty())
+            .unwrap(),
+        Some(Cow::Borrowed(b"val2"))
+    ));
+    assert!(matches!(
+        txn.replace(&db, b"missing", b"val", WriteFlags::CURRENT),
+        Err(Error::NotFound)
+    ));
+    assert_eq!(
+        txn.get::<Vec<u8>>(&db, b"key").unwrap(),
+        Some(b"val3".to_vec())
+    );
+
+    let add = |n: u64| {
+        move |old: Option<&[u8]>| {
+            let old = old.map_or(0, |old| u64::from_be_bytes(old.try_into().unwrap()));
+            (old + n).to_be_bytes().to_vec()
+        }
+    };
+    txn.merge(&db, b"counter", add(5)).unwrap();
+    txn.merge(&db, b"counter", add(2)).unwrap();
+    txn.commit().unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.merge(&db, b"counter", add(3)).unwrap();
+    assert_eq!(txn.get::<u64>(&db, b"counter").unwrap(), Some(10));
+
+    let dup_db = txn.create_db(Some("dup"), DatabaseFlags::DUP_SORT).unwrap();
+    assert!(matches!(
+        txn.merge(&dup_db, b"counter", add(1)),
+        Err(Error::Incompatible)
+    ));
+}
+
+#[test]
+fn test_snapshot_diff() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    for (key, value) in [("a", "1"), ("b", "2"), ("c", "3"), ("d", "4")] {
+        txn.put(&db, key, value, WriteFlags::empty()).unwrap();
+    }
+    let dups = txn
+        .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+        .unwrap();
+    for value in ["1", "2"] {
+        txn.put(&dups, "k", value, WriteFlags::empty()).unwrap();
+    }
+    txn.commit().unwrap();
+    let before = env.begin_ro_txn().unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.del(&db, "b", None).unwrap();
+    txn.put(&db, "c", "30", WriteFlags::empty()).unwrap();
+    txn.put(&db, "e", "5", WriteFlags::empty()).unwrap();
+    let dups = txn.open_db(Some("dups")).unwrap();
+    txn.del(&dups, "k", Some(b"1")).unwrap();
+    txn.put(&dups, "k", "3", WriteFlags::empty()).unwrap();
+    txn.commit().unwrap();
+    let after = env.begin_ro_txn().unwrap();
+
+    let entries = diff::snapshot_diff(&before, &after, None)
+        .unwrap()
+        .filter(|entry| !matches!(entry, Ok(diff::DiffEntry::Changed { key, .. }) if **key == b"dups"[..]))
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            diff::DiffEntry::Removed {
+                key: Cow::Borrowed(b"b"),
+                value: Cow::Borrowed(b"2"),
+            },
+            diff::DiffEntry::Changed {
+                key: Cow::Borrowed(b"c"),
+                old: Cow::Borrowed(b"3"),
+                new: Cow::Borrowed(b"30"),
+            },
+            diff::DiffEntry::Added {
+                key: Cow::Borrowed(b"e"),
+                value: Cow::Borrowed(b"5"),
+            },
+        ]
+    );
+
+    let entries = diff::snapshot_diff(&before, &after, Some("dups"))
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            diff::DiffEntry::Removed {
+                key: Cow::Borrowed(b"k"),
+                value: Cow::Borrowed(b"1"),
+            },
+            diff::DiffEntry::Added {
+                key: Cow::Borrowed(b"k"),
+                value: Cow::Borrowed(b"3"),
+            },
+        ]
+    );
+    assert_eq!(
+        diff::snapshot_diff(&after, &after, None).unwrap().count(),
+        0
+    );
+
+    // Snapshots of two environments.
+    let other_dir = tempdir().unwrap();
+    let other = Environment::new().open(other_dir.path()).unwrap();
+    let txn = other.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, "a", "1", WriteFlags::empty()).unwrap();
+    let entries = diff::snapshot_diff(&txn, &after, None)
+        .unwrap()
+        .map(|entry| match entry.unwrap() {
+            diff::DiffEntry::Added { key, .. } => key.into_owned(),
+            entry => panic!("unexpected {entry:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        vec![
+            b"c".to_vec(),
+            b"d".to_vec(),
+            b"dups".to_vec(),
+            b"e".to_vec()
+        ]
+    );
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn test_secure_table() {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    /// A toy cipher: XOR with a keystream and a hash as tag. Not secure.
+    struct ToyCipher(u64);
+
+    impl ToyCipher {
+        fn tag(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> [u8; 8] {
+            let mut hasher = DefaultHasher::new();
+            (self.0, nonce, aad, ciphertext).hash(&mut hasher);
+            hasher.finish().to_le_bytes()
+        }
+
+        fn xor(&self, nonce: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut hasher = DefaultHasher::new();
+            (self.0, nonce).hash(&mut hasher);
+            let stream = hasher.finish().to_le_bytes();
+            data.iter()
+                .zip(stream.iter().cycle())
+                .map(|(a, b)| a ^ b)
+                .collect()
+        }
+    }
+
+    impl Aead for ToyCipher {
+        fn nonce_len(&self) -> usize {
+            12
+        }
+
+        fn seal(
+            &self,
+            nonce: &[u8],
+            aad: &[u8],
+            plaintext: &[u8],
+        ) -> std::result::Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+            let mut out = self.xor(nonce, plaintext);
+            out.extend_from_slice(&self.tag(nonce, aad, &out));
+            Ok(out)
+        }
+
+        fn open(
+            &self,
+            nonce: &[u8],
+            aad: &[u8],
+            ciphertext: &[u8],
+        ) -> std::result::Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+            let (ciphertext, tag) = ciphertext
+                .split_at_checked(ciphertext.len().wrapping_sub(8))
+                .ok_or("truncated")?;
+            if tag != self.tag(nonce, aad, ciphertext) {
+                return Err("authentication failed".into());
+            }
+            Ok(self.xor(nonce, ciphertext))
+        }
+    }
+
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+    let cipher = ToyCipher(42);
+
+    let txn = env.begin_rw_txn().unwrap();
+    let table = SecureTable::new(txn.open_db(None).unwrap(), &cipher);
+    table
+        .put(&txn, b"a", b"secret value", WriteFlags::empty())
+        .unwrap();
+    table
+        .put(&txn, b"b", b"other", WriteFlags::empty())
+        .unwrap();
+    assert_eq!(
+        table.get(&txn, b"a").unwrap(),
+        Some(b"secret value".to_vec())
+    );
+    assert_eq!(table.get(&txn, b"c").unwrap(), None);
+
+    // Values are stored encrypted, with a different nonce each time.
+    let stored = txn.get::<Vec<u8>>(table.db(), b"a").unwrap().unwrap();
+    assert_eq!(stored.len(), 12 + 12 + 8);
+    assert!(!stored.windows(6).any(|w| w == b"secret"));
+    assert_ne!(
+        encrypt(&cipher, b"a", b"x").unwrap(),
+        encrypt(&cipher, b"a", b"x").unwrap()
+    );
+
+    let items = table
+        .iter(&txn)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        vec![
+            (Cow::Borrowed(&b"a"[..]), b"secret value".to_vec()),
+            (Cow::Borrowed(&b"b"[..]), b"other".to_vec())
+        ]
+    );
+
+    // Values are bound to their key and to the cipher key.
+    txn.put(table.db(), b"c", &stored, WriteFlags::empty())
+        .unwrap();
+    assert!(matches!(table.get(&txn, b"c"), Err(Error::DecodeError(_))));
+    assert!(matches!(
+        decrypt(&ToyCipher(7), b"a", &stored),
+        Err(Error::DecodeError(_))
+    ));
+    assert!(matches!(
+        decrypt(&cipher, b"a", &stored[..4]),
+        Err(Error::DecodeError(_))
+    ));
+    assert!(table.del(&txn, b"a").unwrap());
+    txn.commit().unwrap();
+}
+
+#[test]
+fn test_rename_db() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(4).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn
+        .create_db(
+            Some("old"),
+            DatabaseFlags::DUP_SORT | DatabaseFlags::INTEGER_KEY,
+        )
+        .unwrap();
+    for i in 0..100u64 {
+        for dup in [b"a", b"b"] {
+            txn.put(&db, i.to_ne_bytes(), dup, WriteFlags::empty())
+                .unwrap();
+        }
+    }
+    txn.create_db(Some("taken"), DatabaseFlags::empty())
+        .unwrap();
+    drop(db);
+    txn.commit().unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    assert!(matches!(
+        txn.rename_db("old", "taken"),
+        Err(Error::KeyExist)
+    ));
+    assert!(matches!(
+        txn.rename_db("missing", "new"),
+        Err(Error::NotFound)
+    ));
+    let db = txn.rename_db("old", "new").unwrap();
+    assert_eq!(
+        txn.db_flags(&db).unwrap(),
+        DatabaseFlags::DUP_SORT | DatabaseFlags::INTEGER_KEY
+    );
+    assert_eq!(txn.db_stat(&db).unwrap().entries(), 200);
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    assert!(matches!(txn.open_db(Some("old")), Err(Error::NotFound)));
+    let db = txn.open_db(Some("new")).unwrap();
+    let items = txn
+        .cursor(&db)
+        .unwrap()
+        .iter_start::<[u8; 8], [u8; 1]>()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(items.len(), 200);
+    assert_eq!(items[3], (1u64.to_ne_bytes(), *b"b"));
+}
+
+#[test]
+fn test_dbi_cache() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(4).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    txn.create_db(Some("kept"), DatabaseFlags::empty()).unwrap();
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let dbi = txn.open_db(Some("kept")).unwrap().dbi();
+    drop(txn);
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(Some("kept")).unwrap();
+    assert_eq!(db.dbi(), dbi);
+    txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+    txn.commit().unwrap();
+
+    // Handles of databases created by an aborted transaction are not reused.
+    let txn = env.begin_rw_txn().unwrap();
+    txn.create_db(Some("aborted"), DatabaseFlags::empty())
+        .unwrap();
+    txn.open_db(Some("aborted")).unwrap();
+    drop(txn);
+    let txn = env.begin_ro_txn().unwrap();
+    assert!(matches!(txn.open_db(Some("aborted")), Err(Error::NotFound)));
+    let db = txn.open_db(Some("kept")).unwrap();
+    assert_eq!(
+        txn.get::<Vec<u8>>(&db, b"key").unwrap(),
+        Some(b"val".to_vec())
+    );
+    drop(db);
+    drop(txn);
+
+    // Cached handles closed behind the back of the environment are not returned.
+    let txn = env.begin_rw_txn().unwrap();
+    let dbi = txn.open_db(Some("kept")).unwrap().dbi();
+    assert_eq!(unsafe { ffi::mdbx_drop(txn.txn(), dbi, true) }, 0);
+    txn.commit().unwrap();
+    let txn = env.begin_ro_txn().unwrap();
+    assert!(matches!(txn.open_db(Some("kept")), Err(Error::NotFound)));
+}
+
+unsafe extern "C" fn cmp_descending(
+    a: *const ffi::MDBX_val,
+    b: *const ffi::MDBX_val,
+) -> libc::c_int {
+    let a = std::slice::from_raw_parts((*a).iov_base as *const u8, (*a).iov_len);
+    let b = std::slice::from_raw_parts((*b).iov_base as *const u8, (*b).iov_len);
+    b.cmp(a) as libc::c_int
+}
+
+#[test]
+fn test_comparators() {
+    let dir = tempdir().unwrap();
+    {
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+        let txn = env.begin_rw_txn().unwrap();
+        let db = unsafe {
+            txn.create_db_with_comparators(
+                Some("desc"),
+                DatabaseFlags::empty(),
+                Some(cmp_descending),
+                None,
+            )
+        }
+        .unwrap();
+        let plain = txn
+            .create_db(Some("plain"), DatabaseFlags::empty())
+            .unwrap();
+        for key in [b"a", b"c", b"b"] {
+            txn.put(&db, key, b"", WriteFlags::empty()).unwrap();
+            txn.put(&plain, key, b"", WriteFlags::empty()).unwrap();
+        }
+        let keys = txn
+            .cursor(&db)
+            .unwrap()
+            .iter_start::<Vec<u8>, ()>()
+            .map(|item| item.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![b"c".to_vec(), b"b".to_vec(), b"a".to_vec()]);
+        assert_eq!(txn.check_order(&db).unwrap(), None);
+        assert_eq!(txn.check_order(&plain).unwrap(), None);
+        txn.commit().unwrap();
+    }
+
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+    let txn = env.begin_rw_txn().unwrap();
+    let plain = unsafe {
+        txn.create_db_with_comparators(
+            Some("plain"),
+            DatabaseFlags::empty(),
+            Some(cmp_descending),
+            None,
+        )
+    }
+    .unwrap();
+    assert_eq!(txn.check_order(&plain).unwrap(), Some(b"b".to_vec()));
+}
+
+#[test]
+fn test_db_state() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.create_db(Some("db"), DatabaseFlags::empty()).unwrap();
+    assert!(txn.db_state(&db).unwrap().contains(DatabaseState::CREAT));
+    txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+    assert!(txn.db_state(&db).unwrap().contains(DatabaseState::DIRTY));
+    txn.commit().unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(Some("db")).unwrap();
+    let state = txn.db_state(&db).unwrap();
+    assert!(!state.intersects(DatabaseState::CREAT | DatabaseState::DIRTY));
+    txn.del(&db, b"key", None).unwrap();
+    let state = txn.db_state(&db).unwrap();
+    assert!(state.contains(DatabaseState::DIRTY) && !state.contains(DatabaseState::CREAT));
+}
+
+#[test]
+fn test_drop_db_busy() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.create_db(Some("db"), DatabaseFlags::empty()).unwrap();
+    txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+    drop(db);
+    txn.commit().unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(Some("db")).unwrap();
+    let other = txn.open_db(Some("db")).unwrap();
+    let cursor = txn.cursor(&other).unwrap();
+    drop(other);
+    assert!(matches!(txn.drop_db(db), Err(Error::DatabaseBusy)));
+    assert!(matches!(
+        txn.rename_db("db", "renamed"),
+        Err(Error::DatabaseBusy)
+    ));
+    drop(cursor);
+    txn.drop_db(txn.open_db(Some("db")).unwrap()).unwrap();
+    assert!(matches!(txn.open_db(Some("db")), Err(Error::NotFound)));
+    txn.commit().unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    txn.create_db(Some("kept"), DatabaseFlags::empty()).unwrap();
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(Some("kept")).unwrap();
+    let other = txn.open_db(Some("kept")).unwrap();
+    assert!(matches!(txn.close_db(db), Err(Error::DatabaseBusy)));
+    txn.close_db(other).unwrap();
+}
+
+#[test]
+fn test_database_methods() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.create_db(Some("db"), DatabaseFlags::DUP_SORT).unwrap();
+    assert_eq!(db.name(), Some("db"));
+    assert!(db.state(&txn).unwrap().contains(DatabaseState::CREAT));
+    txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+    assert_eq!(txn.open_db(None).unwrap().name(), None);
+    txn.prime_for_permaopen(db);
+    let (_, dbs) = txn.commit_and_rebind_open_dbs().unwrap();
+    let db = &dbs[0];
+
+    let txn = env.begin_ro_txn().unwrap();
+    assert_eq!(db.name(), Some("db"));
+    assert!(db.flags(&txn).unwrap().contains(DatabaseFlags::DUP_SORT));
+    assert_eq!(db.stat(&txn).unwrap().entries(), 1);
+}
+
+#[test]
+fn test_integer_table() {
+    use libmdbx::keys::{IntegerTable, OrderedF64};
+
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(3).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let table = IntegerTable::<u64>::create(&txn, Some("ints"), DatabaseFlags::empty()).unwrap();
+    for key in [256, 1, 2] {
+        table
+            .put(&txn, key, key.to_string(), WriteFlags::empty())
+            .unwrap();
+    }
+    let floats =
+        IntegerTable::<OrderedF64>::create(&txn, Some("floats"), DatabaseFlags::empty()).unwrap();
+    for key in [1.5, -2.0, 0.0] {
+        floats
+            .put(&txn, OrderedF64(key), b"", WriteFlags::empty())
+            .unwrap();
+    }
+    txn.create_db(Some("bytes"), DatabaseFlags::empty())
+        .unwrap();
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let table = IntegerTable::<u64>::open(&txn, Some("ints")).unwrap();
+    assert_eq!(
+        table
+            .iter(&txn)
+            .unwrap()
+            .map(|item| item.unwrap().0)
+            .collect::<Vec<_>>(),
+        vec![1, 2, 256]
+    );
+    assert_eq!(
+        table.get::<String, _, _>(&txn, 256).unwrap().as_deref(),
+        Some("256")
+    );
+    assert_eq!(table.get::<String, _, _>(&txn, 3).unwrap(), None);
+    // Big-endian integer keys would silently miss the item.
+    assert_eq!(
+        txn.get::<String>(table.db(), 256u64),
+        Err(Error::Incompatible)
+    );
+    assert_eq!(
+        txn.get::<String>(table.db(), 256u64.to_ne_bytes())
+            .unwrap()
+            .as_deref(),
+        Some("256")
+    );
+
+    let floats = IntegerTable::<OrderedF64>::open(&txn, Some("floats")).unwrap();
+    assert_eq!(
+        floats
+            .iter(&txn)
+            .unwrap()
+            .map(|item| item.unwrap().0 .0)
+            .collect::<Vec<_>>(),
+        vec![-2.0, 0.0, 1.5]
+    );
+
+    assert!(matches!(
+        IntegerTable::<u32>::open(&txn, Some("bytes")),
+        Err(Error::Incompatible)
+    ));
+}
+
+#[test]
+fn test_create_db_with() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let items = [("a", "1"), ("b", "1"), ("b", "2"), ("c", "1")];
+    let db = txn
+        .create_db_with(Some("lookup"), DatabaseFlags::DUP_SORT, items)
+        .unwrap();
+    assert_eq!(db.stat(&txn).unwrap().entries(), 4);
+    assert!(matches!(
+        txn.create_db_with(
+            Some("unsorted"),
+            DatabaseFlags::empty(),
+            [("b", ""), ("a", "")]
+        ),
+        Err(Error::KeyMismatch)
+    ));
+    drop(db);
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(Some("lookup")).unwrap();
+    let read = txn
+        .cursor(&db)
+        .unwrap()
+        .iter_start::<String, String>()
+        .map(|item| item.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        read,
+        items.map(|(k, v)| (k.to_string(), v.to_string())).to_vec()
+    );
+}
+
+#[test]
+fn test_item_size_limits() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    let key = vec![0; 4096];
+    match txn.put(&db, &key, b"", WriteFlags::empty()) {
+        Err(Error::KeyTooLarge { len, max }) => assert!(len == 4096 && max < len),
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(matches!(
+        txn.cursor(&db).unwrap().put(&key, b"", WriteFlags::empty()),
+        Err(Error::KeyTooLarge { .. })
+    ));
+    // Values of plain databases may span overflow pages.
+    txn.put(&db, b"key", vec![0; 65536], WriteFlags::empty())
+        .unwrap();
+
+    let dups = txn
+        .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+        .unwrap();
+    match txn.put(&dups, b"key", &key, WriteFlags::empty()) {
+        Err(error @ Error::ValueTooLarge { len: 4096, .. }) => {
+            assert_eq!(error.code(), Some(ffi::MDBX_BAD_VALSIZE));
+            assert!(error.to_string().contains("value of 4096 bytes"));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_poisoning() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"a", b"1", WriteFlags::empty()).unwrap();
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        txn.guarded(|txn| {
+            txn.put(&db, b"b", b"2", WriteFlags::empty()).unwrap();
+            panic!("half-way");
+        })
+    }));
+    assert!(res.is_err());
+    assert!(txn.is_poisoned());
+    assert!(txn.put(&db, b"c", b"3", WriteFlags::empty()).is_err());
+    drop(db);
+    assert!(matches!(txn.commit(), Err(Error::Poisoned)));
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(txn.get::<Vec<u8>>(&db, b"a").unwrap(), None);
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        txn.merge(&db, b"a", |_| panic!("merge")).unwrap();
+    }));
+    assert!(res.is_err());
+    assert!(txn.is_poisoned());
+    drop(db);
+    drop(txn);
+
+    let txn = env.begin_rw_txn().unwrap();
+    assert!(!txn.is_poisoned());
+    txn.guarded(|txn| txn.put(&txn.open_db(None).unwrap(), b"a", b"1", WriteFlags::empty()))
+        .unwrap();
+    txn.commit().unwrap();
+}
+
+fn exercise_kv(kv: &impl Kv) {
+    let all = (Bound::Unbounded, Bound::Unbounded);
+    assert_eq!(kv.get(Some("main"), b"a").unwrap(), None);
+    assert_eq!(kv.scan(Some("main"), all).unwrap(), vec![]);
+    assert!(!kv.del(Some("main"), b"a").unwrap());
+
+    kv.put(Some("main"), b"c", b"3").unwrap();
+    kv.put(Some("main"), b"a", b"1").unwrap();
+    kv.put(Some("main"), b"b", b"2").unwrap();
+    kv.put(Some("other"), b"a", b"4").unwrap();
+    assert_eq!(kv.get(Some("main"), b"a").unwrap(), Some(b"1".to_vec()));
+    assert_eq!(kv.get(Some("other"), b"a").unwrap(), Some(b"4".to_vec()));
+    assert_eq!(
+        kv.scan(Some("main"), (Bound::Excluded(b"a"), Bound::Unbounded))
+            .unwrap(),
+        vec![
+            (b"b".to_vec(), b"2".to_vec()),
+            (b"c".to_vec(), b"3".to_vec())
+        ]
+    );
+    assert_eq!(
+        kv.scan(Some("main"), (Bound::Unbounded, Bound::Excluded(b"b")))
+            .unwrap(),
+        vec![(b"a".to_vec(), b"1".to_vec())]
+    );
+
+    assert!(kv.del(Some("main"), b"b").unwrap());
+    assert!(!kv.del(Some("main"), b"b").unwrap());
+    assert_eq!(kv.scan(Some("main"), all).unwrap().len(), 2);
+}
+
+#[test]
+fn test_kv() {
+    exercise_kv(&MemoryKv::new());
+
+    let dir = tempdir().unwrap();
+    let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+    exercise_kv(&env.begin_rw_txn().unwrap());
+}
+
+#[test]
+fn test_get_ref() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+    {
+        let value = txn.get_ref(&db, b"key1").unwrap().unwrap();
+        assert_eq!(&*value, b"val1");
+    }
+    assert!(txn.get_ref(&db, b"key2").unwrap().is_none());
+
+    // Writes wait until the guard is dropped.
+    let value = txn.get_ref(&db, b"key1").unwrap().unwrap();
+    thread::scope(|s| {
+        let writer = s.spawn(|| txn.put(&db, b"key1", b"val2", WriteFlags::empty()).unwrap());
+        thread::sleep(Duration::from_millis(50));
+        assert!(!writer.is_finished());
+        assert_eq!(&*value, b"val1");
+        drop(value);
+        writer.join().unwrap();
+    });
+    assert_eq!(&*txn.get_ref(&db, b"key1").unwrap().unwrap(), b"val2");
+}